@@ -0,0 +1,29 @@
+//! Measure spawning many small tasks onto the default pool.
+//!
+//! Run with `cargo bench --bench spawn`.
+
+use std::{hint::black_box, time::Instant};
+
+use pasts::Executor;
+
+const TASKS: u32 = 100_000;
+
+fn main() {
+    let start = Instant::now();
+    let executor = Executor::default();
+
+    executor.clone().block_on(async move {
+        for i in 0..TASKS {
+            executor.spawn(async move {
+                black_box(i);
+            });
+        }
+    });
+
+    let elapsed = start.elapsed();
+
+    println!(
+        "spawn: {TASKS} tasks in {elapsed:?} ({:?}/task)",
+        elapsed / TASKS,
+    );
+}
@@ -0,0 +1,94 @@
+//! Measure waking many tasks in a burst, like a broadcast would.
+//!
+//! Run with `cargo bench --bench wake`.
+
+use std::{
+    cell::{Cell, RefCell},
+    future,
+    hint::black_box,
+    rc::Rc,
+    task::Waker,
+    time::Instant,
+};
+
+use pasts::{prelude::*, Executor};
+
+const TASKS: u64 = 1_000;
+const ROUNDS: u64 = 100;
+
+#[derive(Default)]
+struct Broadcast {
+    round: Cell<u64>,
+    wakers: RefCell<Vec<Waker>>,
+}
+
+impl Broadcast {
+    fn send(&self) {
+        self.round.set(self.round.get() + 1);
+
+        for waker in self.wakers.borrow_mut().drain(..) {
+            waker.wake();
+        }
+    }
+
+    async fn recv(&self, seen: u64) -> u64 {
+        future::poll_fn(|task| {
+            let round = self.round.get();
+
+            if round != seen {
+                return Ready(round);
+            }
+
+            self.wakers.borrow_mut().push(task.waker().clone());
+            Pending
+        })
+        .await
+    }
+}
+
+async fn yield_now() {
+    let mut yielded = false;
+
+    future::poll_fn(|task| {
+        if yielded {
+            return Ready(());
+        }
+
+        yielded = true;
+        task.waker().wake_by_ref();
+        Pending
+    })
+    .await;
+}
+
+fn main() {
+    let start = Instant::now();
+    let executor = Executor::default();
+    let broadcast = Rc::new(Broadcast::default());
+
+    executor.clone().block_on(async move {
+        for _ in 0..TASKS {
+            let broadcast = broadcast.clone();
+
+            executor.spawn_local(async move {
+                let mut seen = 0;
+
+                while seen < ROUNDS {
+                    seen = black_box(broadcast.recv(seen).await);
+                }
+            });
+        }
+
+        for _ in 0..ROUNDS {
+            yield_now().await;
+            broadcast.send();
+        }
+    });
+
+    let elapsed = start.elapsed();
+
+    println!(
+        "wake: {TASKS} tasks x {ROUNDS} bursts in {elapsed:?} ({:?}/wake)",
+        elapsed / (TASKS * ROUNDS) as u32,
+    );
+}
@@ -0,0 +1,19 @@
+//! Measure the deterministic workloads from `pasts::bench`.
+//!
+//! Run with `cargo bench --bench workloads --features bench`.
+
+use std::time::Instant;
+
+use pasts::bench;
+
+fn main() {
+    let start = Instant::now();
+    let counters = bench::ping_pong(100, 1_000);
+
+    println!("ping_pong: {:?} {counters:?}", start.elapsed());
+
+    let start = Instant::now();
+    let counters = bench::notifies(100, 1_000);
+
+    println!("notifies: {:?} {counters:?}", start.elapsed());
+}
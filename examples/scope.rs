@@ -0,0 +1,36 @@
+use core::time::Duration;
+
+use async_std::task::sleep;
+use pasts::{Executor, Scope};
+
+#[derive(Debug, PartialEq)]
+struct Error(&'static str);
+
+fn main() {
+    Executor::default().block_on(async {
+        let mut completed = Vec::new();
+        let scope = Scope::new();
+        let handle = scope.handle();
+
+        scope.spawn(async {
+            sleep(Duration::from_millis(10)).await;
+            Err(Error("connection lost"))
+        });
+        scope.spawn(async move {
+            // Tasks can spawn more tasks on the scope
+            handle.spawn(async {
+                sleep(Duration::from_secs(60)).await;
+                Ok(())
+            });
+            Ok(())
+        });
+        scope.spawn(async {
+            completed.push("quick task");
+            Ok(())
+        });
+
+        // The first error cancels the task that's still sleeping
+        assert_eq!(scope.await, Err(Error("connection lost")));
+        assert_eq!(completed, ["quick task"]);
+    });
+}
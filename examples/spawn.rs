@@ -10,7 +10,7 @@ fn main() {
     let executor = Executor::default();
 
     // Spawn before blocking puts the task on a queue.
-    executor.spawn_boxed(async {
+    executor.spawn(async {
         sleep(3.0).await;
         println!("3 seconds");
     });
@@ -18,11 +18,11 @@ fn main() {
     // Calling `block_on()` starting executing queued tasks.
     executor.clone().block_on(async move {
         // Spawn tasks (without being queued)
-        executor.spawn_boxed(async {
+        executor.spawn(async {
             sleep(1.0).await;
             println!("1 second");
         });
-        executor.spawn_boxed(async {
+        executor.spawn(async {
             sleep(2.0).await;
             println!("2 seconds");
         });
@@ -0,0 +1,40 @@
+use std::{sync::mpsc, task::Waker, thread};
+
+use pasts::{prelude::*, Executor};
+
+fn main() {
+    // Messages to the external event loop, sent when a task is woken
+    let (sender, receiver) = mpsc::channel();
+    let executor = Executor::default().wake_hook(move |_| {
+        let _ = sender.send(());
+    });
+
+    // Wake a task from another thread, like an OS callback would
+    let (waker_sender, waker_receiver) = mpsc::channel::<Waker>();
+
+    thread::spawn(move || {
+        let waker = waker_receiver.recv().unwrap();
+
+        waker.wake();
+    });
+
+    let mut woken = false;
+
+    executor.spawn_local(core::future::poll_fn(move |task| {
+        if woken {
+            println!("Woken from another thread");
+            return Ready(());
+        }
+
+        woken = true;
+        waker_sender.send(task.waker().clone()).unwrap();
+        Pending
+    }));
+
+    // The external event loop, running until pasts has no tasks left
+    let mut ticker = executor.ticker();
+
+    while ticker.tick().is_pending() {
+        receiver.recv().unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+//! Procedural macros for [pasts](https://docs.rs/pasts).
+//!
+//! Enable pasts' _`macros`_ feature to use these through pasts, rather than
+//! depending on this crate directly.
+
+#![forbid(unsafe_code, missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields,
+    Ident, ItemFn,
+};
+
+/// Derive `Notify` for a struct of notifys, producing an enum with a variant
+/// for each field's events.
+///
+/// The enum is named after the struct with `Event` appended, unless named
+/// with `#[notify(event = Name)]`.  Variants are named after the fields, in
+/// `UpperCamelCase`.  Fields marked with `#[notify(skip)]` aren't polled.
+/// The enum derives `Debug`, so each polled field's events must implement
+/// `Debug`.
+///
+/// Fields are polled in order, so earlier fields are preferred when more
+/// than one is ready.  Each field must be `Unpin`.
+///
+/// Since the struct is itself a `Notify`, it can be passed to `Loop::on()`
+/// with a single handler for the combined events.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// #[derive(Notify)]
+/// #[notify(event = Input)]
+/// struct Inputs {
+///     keys: notify::Ready<char>,
+///     clicks: notify::Ready<(i32, i32)>,
+///     #[notify(skip)]
+///     count: usize,
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut inputs = Inputs {
+///         keys: notify::ready('a'),
+///         clicks: notify::ready((4, 2)),
+///         count: 0,
+///     };
+///
+///     for _ in 0..2 {
+///         match inputs.next().await {
+///             Input::Keys(key) => assert_eq!(key, 'a'),
+///             Input::Clicks(click) => assert_eq!(click, (4, 2)),
+///         }
+///
+///         inputs.count += 1;
+///     }
+/// });
+/// ```
+#[proc_macro_derive(Notify, attributes(notify))]
+pub fn derive_notify(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    derive(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Run an `async fn main()` on a default `Executor`.
+///
+/// `main()` may return `()` or `Result<(), E>` where `E: Debug`, like a
+/// synchronous `main()`.  With pasts' _`web`_ feature, the future is spawned
+/// rather than blocked on, and errors are reported by panicking once it
+/// completes.
+///
+/// ```rust
+/// #[pasts::main]
+/// async fn main() -> Result<(), std::num::ParseIntError> {
+///     let answer: u32 = async { "42" }.await.parse()?;
+///
+///     assert_eq!(answer, 42);
+///     Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = TokenStream2::from(args);
+    let item = parse_macro_input!(item as ItemFn);
+
+    main_fn(&args, item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn main_fn(args: &TokenStream2, item: ItemFn) -> syn::Result<TokenStream2> {
+    if !args.is_empty() {
+        return Err(Error::new(args.span(), "expected no arguments"));
+    }
+
+    let ItemFn {
+        attrs,
+        vis,
+        mut sig,
+        block,
+    } = item;
+
+    if sig.asyncness.take().is_none() {
+        return Err(Error::new(sig.fn_token.span, "expected `async fn`"));
+    }
+
+    if !sig.inputs.is_empty() || !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.ident.span(),
+            "`main()` can't take arguments or generics",
+        ));
+    }
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            ::pasts::__private::main(async move #block)
+        }
+    })
+}
+
+fn derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "`Notify` can't be derived for generic structs",
+        ));
+    }
+
+    let Data::Struct(data) = input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "`Notify` can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = data.fields else {
+        return Err(Error::new(
+            input.ident.span(),
+            "`Notify` can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut event = format_ident!("{}Event", input.ident);
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("notify")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("event") {
+                event = meta.value()?.parse()?;
+                return Ok(());
+            }
+
+            Err(meta.error("expected `event = Name`"))
+        })?;
+    }
+
+    let mut variants = Vec::new();
+    let mut polls = Vec::new();
+
+    for field in fields.named {
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("notify")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    return Ok(());
+                }
+
+                Err(meta.error("expected `skip`"))
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+
+        let name = field.ident.expect("named field");
+        let ty = field.ty;
+        let variant = camel_case(&name);
+        let doc = format!("Event from `{name}`");
+
+        variants.push(quote! {
+            #[doc = #doc]
+            #variant(<#ty as ::pasts::notify::Notify>::Event)
+        });
+        polls.push(quote! {
+            if let ::core::task::Poll::Ready(event) =
+                ::pasts::notify::Notify::poll_next(
+                    ::core::pin::Pin::new(&mut this.#name),
+                    task,
+                )
+            {
+                return ::core::task::Poll::Ready(#event::#variant(event));
+            }
+        });
+    }
+
+    let ident = input.ident;
+    let vis = input.vis;
+    let doc = format!("Events produced by [`{ident}`]");
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Debug)]
+        #vis enum #event {
+            #(#variants),*
+        }
+
+        impl ::pasts::notify::Notify for #ident {
+            type Event = #event;
+
+            fn poll_next(
+                self: ::core::pin::Pin<&mut Self>,
+                task: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<#event> {
+                let this = ::core::pin::Pin::get_mut(self);
+
+                #(#polls)*
+
+                let _ = (this, task);
+                ::core::task::Poll::Pending
+            }
+        }
+    })
+}
+
+// Convert a `snake_case` field name to an `UpperCamelCase` variant name.
+fn camel_case(ident: &Ident) -> Ident {
+    let name = ident.to_string();
+    let name = name.trim_start_matches("r#");
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = true;
+
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+
+    Ident::new(&camel, ident.span())
+}
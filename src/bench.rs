@@ -0,0 +1,183 @@
+//! Deterministic workloads for measuring executor performance.
+//!
+//! Each workload runs to completion on a fresh [`Executor`], and returns
+//! [`Counters`] so that changes in the amount of work done can be told apart
+//! from changes in how fast it's done.
+
+use alloc::{rc::Rc, vec::Vec};
+use core::{
+    cell::RefCell,
+    future,
+    hint::black_box,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use std::sync::Arc;
+
+use crate::{notify::WakerSlot, prelude::*, Executor, Loop};
+
+/// Work done by a workload.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Number of times a task was polled
+    pub polls: u64,
+    /// Number of times a task was woken
+    pub wakes: u64,
+    /// Number of messages or events delivered
+    pub events: u64,
+}
+
+/// Run `pairs` pairs of tasks, each sending a message back and forth
+/// `rounds` times.
+pub fn ping_pong(pairs: usize, rounds: usize) -> Counters {
+    let events = Rc::new(RefCell::new(0));
+
+    let counters = run(|executor| {
+        for _ in 0..pairs {
+            let (ping, pong) = (Slot::default(), Slot::default());
+            let (ping_rx, pong_tx) = (ping.clone(), pong.clone());
+            let events = events.clone();
+
+            executor.spawn_local(async move {
+                for round in 0..rounds {
+                    ping.send(round);
+                    black_box(pong.recv().await);
+                    *events.borrow_mut() += 1;
+                }
+            });
+            executor.spawn_local(async move {
+                for _ in 0..rounds {
+                    pong_tx.send(ping_rx.recv().await);
+                }
+            });
+        }
+    });
+    let events = *events.borrow();
+
+    Counters { events, ..counters }
+}
+
+/// Run a [`Loop`] over `count` notifies, each producing `events` events, and
+/// only becoming ready every other poll.
+pub fn notifies(count: usize, events: usize) -> Counters {
+    let delivered = Rc::new(RefCell::new(0));
+    let total = delivered.clone();
+
+    let counters = run(move |executor| {
+        executor.spawn_local(async move {
+            let mut state = Notifies {
+                tickers: (0..count).map(|_| Ticker(events, false)).collect(),
+                remaining: count * events,
+                delivered: 0,
+            };
+
+            if state.remaining != 0 {
+                Loop::new(&mut state)
+                    .on(|s| &mut s.tickers[..], Notifies::tick)
+                    .await;
+            }
+
+            *total.borrow_mut() = state.delivered;
+        });
+    });
+    let events = *delivered.borrow();
+
+    Counters { events, ..counters }
+}
+
+// Run a workload, counting polls and wakes.
+fn run(spawn: impl FnOnce(&Executor)) -> Counters {
+    let polls = Arc::new(AtomicU64::new(0));
+    let wakes = Arc::new(AtomicU64::new(0));
+    let (p, w) = (polls.clone(), wakes.clone());
+    let executor = Executor::default()
+        .poll_hooks(
+            move |_| {
+                p.fetch_add(1, Ordering::Relaxed);
+            },
+            |_| {},
+        )
+        .wake_hook(move |_| {
+            w.fetch_add(1, Ordering::Relaxed);
+        });
+
+    spawn(&executor);
+    executor.block_on(async {});
+
+    Counters {
+        polls: polls.load(Ordering::Relaxed),
+        wakes: wakes.load(Ordering::Relaxed),
+        events: 0,
+    }
+}
+
+// Single-message channel between two tasks.
+#[derive(Clone, Default)]
+struct Slot(Rc<RefCell<(Option<usize>, WakerSlot)>>);
+
+impl Slot {
+    fn send(&self, message: usize) {
+        let mut slot = self.0.borrow_mut();
+
+        slot.0 = Some(message);
+        slot.1.wake();
+    }
+
+    async fn recv(&self) -> usize {
+        future::poll_fn(|task| {
+            let mut slot = self.0.borrow_mut();
+
+            if let Some(message) = slot.0.take() {
+                return Ready(message);
+            }
+
+            slot.1.register(task.waker());
+            Pending
+        })
+        .await
+    }
+}
+
+// Notify that produces an event every other poll, until out of events.
+struct Ticker(usize, bool);
+
+impl Notify for Ticker {
+    type Event = ();
+
+    fn poll_next(self: Pin<&mut Self>, task: &mut Task<'_>) -> Poll {
+        let this = self.get_mut();
+
+        if this.0 == 0 {
+            return Pending;
+        }
+
+        this.1 = !this.1;
+
+        if this.1 {
+            task.waker().wake_by_ref();
+            return Pending;
+        }
+
+        this.0 -= 1;
+        Ready(())
+    }
+}
+
+struct Notifies {
+    tickers: Vec<Ticker>,
+    remaining: usize,
+    delivered: u64,
+}
+
+impl Notifies {
+    fn tick(&mut self, (index, ()): (usize, ())) -> Poll {
+        black_box(index);
+        self.delivered += 1;
+        self.remaining -= 1;
+
+        if self.remaining == 0 {
+            return Ready(());
+        }
+
+        Pending
+    }
+}
@@ -0,0 +1,242 @@
+use alloc::{
+    boxed::Box, collections::VecDeque, string::String, sync::Arc, vec::Vec,
+};
+use core::{fmt, task::Waker};
+use std::{
+    io,
+    panic::{self, AssertUnwindSafe},
+    sync::{Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+use crate::prelude::*;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct State {
+    limit: usize,
+    running: usize,
+    queue: VecDeque<Job>,
+    // Incremented each time the length of the queue changes.
+    changes: u64,
+    watchers: Vec<Waker>,
+}
+
+impl State {
+    // Let the `BlockingQueue`s know that the length of the queue changed.
+    fn changed(&mut self) {
+        self.changes = self.changes.wrapping_add(1);
+
+        for waker in self.watchers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+// Threads for running blocking closures, up to a limit.  Threads exit once
+// there's nothing left in the queue.
+pub(crate) struct Threads(Mutex<State>);
+
+impl Default for Threads {
+    fn default() -> Self {
+        Self::new(available_parallelism())
+    }
+}
+
+impl Threads {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self(Mutex::new(State {
+            limit: limit.max(1),
+            running: 0,
+            queue: VecDeque::new(),
+            changes: 0,
+            watchers: Vec::new(),
+        }))
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // Jobs never run while the lock is held, so it can't be poisoned
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Run `job` on a thread if there's room in the budget, otherwise queue it
+    // for the next thread that's free.
+    pub(crate) fn run(self: &Arc<Self>, job: Job) {
+        let mut state = self.state();
+
+        if state.running == state.limit {
+            state.queue.push_back(job);
+            state.changed();
+            return;
+        }
+
+        state.running += 1;
+        drop(state);
+
+        let threads = self.clone();
+
+        spawn_thread(Some("pasts-blocking".into()), move || threads.work(job))
+            .expect("failed to spawn blocking thread");
+    }
+
+    fn work(&self, mut job: Job) {
+        loop {
+            job();
+
+            let mut state = self.state();
+            let Some(next) = state.queue.pop_front() else {
+                state.running -= 1;
+                return;
+            };
+
+            state.changed();
+            job = next;
+        }
+    }
+}
+
+struct Output<T> {
+    output: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// Future for the output of a closure running on a blocking thread.
+///
+/// Created with
+/// [`Executor::spawn_blocking()`](crate::Executor::spawn_blocking()).
+/// Dropping the handle doesn't stop the closure.  If the closure panicked,
+/// awaiting the handle continues the panic.
+pub struct BlockingHandle<T>(Arc<Mutex<Output<T>>>);
+
+impl<T> fmt::Debug for BlockingHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BlockingHandle")
+    }
+}
+
+impl<T> Future for BlockingHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
+        let mut output = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match output.output.take() {
+            Some(Ok(output)) => Ready(output),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                output.waker = Some(t.waker().clone());
+                Pending
+            }
+        }
+    }
+}
+
+/// [`Notify`] of the number of closures waiting for a blocking thread.
+///
+/// Created with
+/// [`Executor::blocking_queue()`](crate::Executor::blocking_queue()).  The
+/// first event is the current length of the queue, and after that there's an
+/// event each time it changes.
+pub struct BlockingQueue {
+    threads: Arc<Threads>,
+    seen: Option<u64>,
+}
+
+impl fmt::Debug for BlockingQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingQueue")
+            .field("len", &self.threads.state().queue.len())
+            .finish()
+    }
+}
+
+impl Notify for BlockingQueue {
+    type Event = usize;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<usize> {
+        let this = self.get_mut();
+        let mut state = this.threads.state();
+
+        if this.seen != Some(state.changes) {
+            this.seen = Some(state.changes);
+            return Ready(state.queue.len());
+        }
+
+        if !state.watchers.iter().any(|w| w.will_wake(t.waker())) {
+            state.watchers.push(t.waker().clone());
+        }
+
+        Pending
+    }
+}
+
+// Get the number of threads that can run at once, or 1 if unknown.
+pub(crate) fn available_parallelism() -> usize {
+    #[cfg(not(feature = "web_lite"))]
+    {
+        thread::available_parallelism().map_or(1, usize::from)
+    }
+
+    #[cfg(feature = "web_lite")]
+    {
+        crate::web::hardware_concurrency()
+    }
+}
+
+// Start a thread running `f`.  On the web, it's started by the spawner set
+// with `web::thread_spawner()`, and isn't named.
+pub(crate) fn spawn_thread(
+    name: Option<String>,
+    f: impl FnOnce() + Send + 'static,
+) -> io::Result<()> {
+    #[cfg(not(feature = "web_lite"))]
+    {
+        let mut builder = thread::Builder::new();
+
+        if let Some(name) = name {
+            builder = builder.name(name);
+        }
+
+        builder.spawn(f).map(drop)
+    }
+
+    #[cfg(feature = "web_lite")]
+    {
+        drop(name);
+        crate::web::spawn_thread(Box::new(f));
+        Ok(())
+    }
+}
+
+// Run `f` on one of `threads`, returning a handle to its output.
+pub(crate) fn spawn<T, F>(threads: &Arc<Threads>, f: F) -> BlockingHandle<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let shared = Arc::new(Mutex::new(Output {
+        output: None,
+        waker: None,
+    }));
+    let handle = BlockingHandle(shared.clone());
+
+    threads.run(Box::new(move || {
+        let output = panic::catch_unwind(AssertUnwindSafe(f));
+        let mut shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+
+        shared.output = Some(output);
+
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }));
+    handle
+}
+
+// Watch the length of the queue of `threads`.
+pub(crate) fn queue(threads: &Arc<Threads>) -> BlockingQueue {
+    BlockingQueue {
+        threads: threads.clone(),
+        seen: None,
+    }
+}
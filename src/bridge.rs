@@ -0,0 +1,110 @@
+use alloc::{sync::Arc, task::Wake};
+use core::{fmt, task::Waker};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::prelude::*;
+
+#[derive(Default)]
+struct Bridge(Mutex<Option<Waker>>);
+
+impl Bridge {
+    fn lock(&self) -> MutexGuard<'_, Option<Waker>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Wake for Bridge {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let waker = self.lock().take();
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Forwards wakes to whichever task last polled through the bridge, from a
+/// waker that stays the same.
+///
+/// Futures and [`Notify`]s don't care which runtime polls them, but they
+/// clone the waker again each time it changes.  Polling through a bridge
+/// hands them the same waker every time, whether the outer task is on pasts,
+/// tokio or async-std, or moves between them.  Wakes are forwarded straight
+/// to the outer task, so there's no busy polling.
+///
+/// ```rust
+/// use core::future::poll_fn;
+///
+/// use pasts::{
+///     prelude::*,
+///     time::{self, Duration},
+///     WakerBridge,
+/// };
+///
+/// let bridge = WakerBridge::new();
+/// let mut interval = time::interval(Duration::from_millis(1));
+///
+/// // A pasts notify, polled from an async-std task
+/// async_std::task::block_on(async move {
+///     for _ in 0..3 {
+///         poll_fn(|t| bridge.poll(t, |t| Pin::new(&mut interval).poll_next(t)))
+///             .await;
+///     }
+/// });
+/// ```
+#[derive(Clone)]
+pub struct WakerBridge(Arc<Bridge>, Waker);
+
+impl fmt::Debug for WakerBridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WakerBridge")
+            .field("registered", &self.0.lock().is_some())
+            .finish()
+    }
+}
+
+impl Default for WakerBridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WakerBridge {
+    /// Create a bridge, with no task to wake yet.
+    pub fn new() -> Self {
+        let bridge = Arc::new(Bridge::default());
+        let waker = bridge.clone().into();
+
+        Self(bridge, waker)
+    }
+
+    /// Set the task to wake on the next wake, replacing the previous one.
+    pub fn register(&self, waker: &Waker) {
+        let mut slot = self.0.lock();
+
+        match &mut *slot {
+            Some(old) if old.will_wake(waker) => {}
+            slot => *slot = Some(waker.clone()),
+        }
+    }
+
+    /// Get the bridge's waker, which wakes the registered task.
+    pub fn waker(&self) -> &Waker {
+        &self.1
+    }
+
+    /// Register `t`'s waker, and call `poll` with the bridge's waker.
+    pub fn poll<T>(
+        &self,
+        t: &mut Task<'_>,
+        poll: impl FnOnce(&mut Task<'_>) -> Poll<T>,
+    ) -> Poll<T> {
+        self.register(t.waker());
+        poll(&mut Task::from_waker(&self.1))
+    }
+}
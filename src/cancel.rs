@@ -0,0 +1,259 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    fmt,
+    task::Waker,
+};
+
+use crate::prelude::*;
+
+// Flag that tasks can wait to be set, once.
+#[derive(Default)]
+pub(crate) struct Signal {
+    fired: Cell<bool>,
+    wakers: RefCell<Vec<Waker>>,
+}
+
+impl Signal {
+    // Set the flag, and wake the tasks waiting for it.
+    pub(crate) fn fire(&self) {
+        self.fired.set(true);
+
+        for waker in self.wakers.take() {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn is_fired(&self) -> bool {
+        self.fired.get()
+    }
+
+    // Check if the flag is set, or register to wake the task once it is.
+    pub(crate) fn poll(&self, t: &mut Task<'_>) -> Poll {
+        if self.fired.get() {
+            return Ready(());
+        }
+
+        let mut wakers = self.wakers.borrow_mut();
+
+        if !wakers.iter().any(|w| w.will_wake(t.waker())) {
+            wakers.push(t.waker().clone());
+        }
+
+        Pending
+    }
+}
+
+/// Shared token for cancelling work.
+///
+/// Clones of a token are cancelled together.  Pass a clone to
+/// [`NotifyExt::until()`] to stop a notify once the token is cancelled, or
+/// await [`CancelToken::cancelled()`] directly.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*, CancelToken};
+///
+/// pasts::Executor::default().block_on(async {
+///     let token = CancelToken::new();
+///     let mut events = notify::pending::<u32>().until(token.clone());
+///
+///     token.cancel();
+///     assert_eq!(events.next().await, None);
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct CancelToken(Rc<Signal>);
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CancelToken")
+            .field(&self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancelToken {
+    /// Create a new token, which isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel this token and its clones, waking the tasks waiting on them.
+    pub fn cancel(&self) {
+        self.0.fire();
+    }
+
+    /// Check if this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_fired()
+    }
+
+    /// Get a future that completes once this token is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled(self.clone())
+    }
+
+    // Check if cancelled, or register to wake the task once it is.
+    pub(crate) fn poll(&self, t: &mut Task<'_>) -> Poll {
+        self.0.poll(t)
+    }
+}
+
+/// Future returned from [`CancelToken::cancelled()`].
+#[derive(Debug)]
+pub struct Cancelled(CancelToken);
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        self.0.poll(t)
+    }
+}
+
+/// Handle for aborting an [`Abortable`].
+///
+/// Clones abort the same [`Abortable`].
+///
+/// ```rust
+/// use pasts::{AbortHandle, Abortable, Aborted};
+///
+/// pasts::Executor::default().block_on(async {
+///     let (handle, registration) = AbortHandle::new_pair();
+///     let forever = Box::pin(core::future::pending::<()>());
+///     let abortable = Abortable::new(forever, registration);
+///
+///     handle.abort();
+///     assert_eq!(abortable.await, Err(Aborted));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct AbortHandle(Rc<Signal>);
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AbortHandle")
+            .field(&self.is_aborted())
+            .finish()
+    }
+}
+
+impl AbortHandle {
+    /// Create a handle, and the registration for the [`Abortable`] it
+    /// aborts.
+    pub fn new_pair() -> (Self, AbortRegistration) {
+        let signal = Rc::new(Signal::default());
+
+        (Self(signal.clone()), AbortRegistration(signal))
+    }
+
+    /// Abort the [`Abortable`], waking its task.
+    ///
+    /// If it has already completed, this does nothing.
+    pub fn abort(&self) {
+        self.0.fire();
+    }
+
+    /// Check if [`AbortHandle::abort()`] has been called.
+    pub fn is_aborted(&self) -> bool {
+        self.0.is_fired()
+    }
+}
+
+/// Registration that connects an [`Abortable`] to its [`AbortHandle`].
+///
+/// Created with [`AbortHandle::new_pair()`].
+pub struct AbortRegistration(Rc<Signal>);
+
+impl fmt::Debug for AbortRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AbortRegistration")
+    }
+}
+
+/// Error returned from an [`Abortable`] that was aborted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Aborted;
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("aborted")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Aborted {}
+
+/// Future or [`Notify`] that can be stopped from outside with an
+/// [`AbortHandle`].
+///
+/// Once aborted, the wrapped future or notify is dropped, and [`Aborted`] is
+/// returned in place of its output.  Unlike cancelling a task, this can stop
+/// one part of a task, such as a single handler's future in a
+/// [`Loop`](crate::Loop).
+///
+/// A notify produces `Err(Aborted)` once, and then never produces another
+/// event.
+pub struct Abortable<F> {
+    inner: Option<F>,
+    registration: AbortRegistration,
+}
+
+impl<F> fmt::Debug for Abortable<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Abortable")
+            .field("aborted", &self.registration.0.is_fired())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> Abortable<F> {
+    /// Wrap `inner`, so that it can be aborted through the [`AbortHandle`]
+    /// paired with `registration`.
+    pub fn new(inner: F, registration: AbortRegistration) -> Self {
+        Self {
+            inner: Some(inner),
+            registration,
+        }
+    }
+
+    /// Check if this has been aborted.
+    pub fn is_aborted(&self) -> bool {
+        self.registration.0.is_fired()
+    }
+
+    // Poll `inner` with `poll`, unless aborted.
+    fn poll_with<T>(
+        &mut self,
+        t: &mut Task<'_>,
+        poll: impl FnOnce(&mut F, &mut Task<'_>) -> Poll<T>,
+    ) -> Poll<Result<T, Aborted>> {
+        let Some(ref mut inner) = self.inner else {
+            return Pending;
+        };
+
+        if self.registration.0.poll(t).is_ready() {
+            self.inner = None;
+            return Ready(Err(Aborted));
+        }
+
+        poll(inner, t).map(Ok)
+    }
+}
+
+impl<F: Future + Unpin> Future for Abortable<F> {
+    type Output = Result<F::Output, Aborted>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        self.get_mut()
+            .poll_with(t, |inner, t| Pin::new(inner).poll(t))
+    }
+}
+
+impl<N: Notify + Unpin> Notify for Abortable<N> {
+    type Event = Result<N::Event, Aborted>;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        self.get_mut().poll_with(t, N::poll_next_unpin)
+    }
+}
@@ -0,0 +1,248 @@
+use alloc::{collections::VecDeque, sync::Arc};
+use core::{fmt, task::Waker};
+use std::sync::{Mutex as StdMutex, MutexGuard as StdGuard, PoisonError};
+
+use crate::{
+    prelude::*,
+    sync::{Mutex, MutexGuard, OwnedMutexGuard},
+};
+
+// A task waiting on a `Condvar`.
+#[derive(Default)]
+struct Waiter {
+    notified: bool,
+    waker: Option<Waker>,
+}
+
+type Slot = Arc<StdMutex<Waiter>>;
+
+fn lock<T>(mutex: &StdMutex<T>) -> StdGuard<'_, T> {
+    // Nothing panics while holding these locks, so they can't be poisoned
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Condition variable, for waiting on a [`Mutex`]'s value to change.
+///
+/// Waiting unlocks the mutex, and locks it again once notified.  Notifying
+/// doesn't need the mutex to be locked, but should come after changing the
+/// value the waiters are checking.
+///
+/// ```rust
+/// use std::{collections::VecDeque, sync::Arc};
+///
+/// use pasts::sync::{Condvar, Mutex};
+///
+/// let executor = pasts::Executor::default();
+/// let queue = Arc::new((Mutex::new(VecDeque::new()), Condvar::new()));
+/// let consumer = queue.clone();
+///
+/// executor.spawn(async move {
+///     let (mutex, condvar) = &*consumer;
+///     let mut queue = mutex.lock().await;
+///
+///     // Wait until the queue isn't empty
+///     while queue.is_empty() {
+///         queue = condvar.wait(queue).await;
+///     }
+///
+///     assert_eq!(queue.pop_front(), Some("job"));
+/// });
+/// executor.spawn(async move {
+///     let (mutex, condvar) = &*queue;
+///
+///     mutex.lock().await.push_back("job");
+///     condvar.notify_one();
+/// });
+/// executor.block_on(async {});
+/// ```
+#[derive(Default)]
+pub struct Condvar {
+    waiters: StdMutex<VecDeque<Slot>>,
+}
+
+impl fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Condvar")
+            .field("waiters", &lock(&self.waiters).len())
+            .finish()
+    }
+}
+
+impl Condvar {
+    /// Create a condition variable with no waiters.
+    pub const fn new() -> Self {
+        Self {
+            waiters: StdMutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Unlock `guard`'s mutex and wait to be notified, then lock it again.
+    ///
+    /// Check the condition being waited for in a loop, since another task
+    /// may lock the mutex and change the value first.
+    pub fn wait<'a, T>(&'a self, guard: MutexGuard<'a, T>) -> Wait<'a, T> {
+        Wait(State::new(self, guard))
+    }
+
+    /// Like [`Condvar::wait()`], for an [`OwnedMutexGuard`].
+    pub fn wait_owned<T>(&self, guard: OwnedMutexGuard<T>) -> WaitOwned<'_, T> {
+        WaitOwned(State::new(self, guard))
+    }
+
+    /// Wake the task that's been waiting the longest, if any.
+    pub fn notify_one(&self) {
+        let slot = lock(&self.waiters).pop_front();
+
+        if let Some(slot) = slot {
+            notify(&slot);
+        }
+    }
+
+    /// Wake every waiting task.
+    pub fn notify_all(&self) {
+        let waiters = core::mem::take(&mut *lock(&self.waiters));
+
+        for slot in waiters {
+            notify(&slot);
+        }
+    }
+}
+
+fn notify(slot: &Slot) {
+    let waker = {
+        let mut waiter = lock(slot);
+
+        waiter.notified = true;
+        waiter.waker.take()
+    };
+
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+// Progress of waiting on a condvar, with guard `G` for mutex `M`.
+enum Step<G, M> {
+    Unlock(G),
+    Wait(M, Slot),
+    Relock(M),
+    Done,
+}
+
+struct State<'a, G, M> {
+    condvar: &'a Condvar,
+    step: Step<G, M>,
+}
+
+impl<'a, G, M> State<'a, G, M> {
+    fn new(condvar: &'a Condvar, guard: G) -> Self {
+        Self {
+            condvar,
+            step: Step::Unlock(guard),
+        }
+    }
+
+    fn poll(
+        &mut self,
+        t: &mut Task<'_>,
+        unlock: fn(G) -> M,
+        relock: fn(&M, &mut Task<'_>) -> Poll<G>,
+    ) -> Poll<G> {
+        loop {
+            match core::mem::replace(&mut self.step, Step::Done) {
+                Step::Unlock(guard) => {
+                    let slot = Slot::default();
+
+                    // Queue up before unlocking, so notifications aren't lost
+                    lock(&self.condvar.waiters).push_back(slot.clone());
+                    self.step = Step::Wait(unlock(guard), slot);
+                }
+                Step::Wait(mutex, slot) => {
+                    let mut waiter = lock(&slot);
+
+                    if !waiter.notified {
+                        waiter.waker = Some(t.waker().clone());
+                        drop(waiter);
+                        self.step = Step::Wait(mutex, slot);
+                        return Pending;
+                    }
+
+                    self.step = Step::Relock(mutex);
+                }
+                Step::Relock(mutex) => {
+                    if let Ready(guard) = relock(&mutex, t) {
+                        return Ready(guard);
+                    }
+
+                    self.step = Step::Relock(mutex);
+                    return Pending;
+                }
+                Step::Done => panic!("polled after completion"),
+            }
+        }
+    }
+}
+
+impl<G, M> Drop for State<'_, G, M> {
+    fn drop(&mut self) {
+        let notified = match self.step {
+            Step::Wait(_, ref slot) => {
+                let mut waiters = lock(&self.condvar.waiters);
+
+                waiters.retain(|waiter| !Arc::ptr_eq(waiter, slot));
+                lock(slot).notified
+            }
+            Step::Relock(_) => true,
+            Step::Unlock(_) | Step::Done => false,
+        };
+
+        // Pass the notification on, rather than losing it
+        if notified {
+            self.condvar.notify_one();
+        }
+    }
+}
+
+/// Future returned from [`Condvar::wait()`].
+pub struct Wait<'a, T>(State<'a, MutexGuard<'a, T>, &'a Mutex<T>>);
+
+impl<T> fmt::Debug for Wait<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Wait")
+    }
+}
+
+impl<T> Unpin for Wait<'_, T> {}
+
+impl<'a, T> Future for Wait<'a, T> {
+    type Output = MutexGuard<'a, T>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        self.get_mut()
+            .0
+            .poll(t, MutexGuard::unlock, |mutex, t| Mutex::poll_lock(mutex, t))
+    }
+}
+
+/// Future returned from [`Condvar::wait_owned()`].
+pub struct WaitOwned<'a, T>(State<'a, OwnedMutexGuard<T>, Arc<Mutex<T>>>);
+
+impl<T> fmt::Debug for WaitOwned<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WaitOwned")
+    }
+}
+
+impl<T> Unpin for WaitOwned<'_, T> {}
+
+impl<T> Future for WaitOwned<'_, T> {
+    type Output = OwnedMutexGuard<T>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        self.get_mut().0.poll(
+            t,
+            OwnedMutexGuard::unlock,
+            Mutex::poll_lock_owned,
+        )
+    }
+}
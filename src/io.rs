@@ -0,0 +1,445 @@
+//! Readiness of OS I/O sources, such as local sockets, as [`Notify`]s.
+//!
+//! A [`Source`] registers an I/O handle with
+//! [async-io](https://docs.rs/async-io)'s reactor and puts it in non-blocking
+//! mode.  Its [`Source::readable()`] and [`Source::writable()`] notifys produce
+//! an event each time the handle becomes ready, after which reads and writes
+//! through [`Source::get_ref()`] can be retried until they return
+//! [`io::ErrorKind::WouldBlock`].
+//!
+//! Any handle with a file descriptor can be a source on Unix, and any socket
+//! on Windows.  [`AsyncTcpStream`], [`AsyncTcpListener`] and [`AsyncUdpSocket`]
+//! wrap sources with futures for the usual socket operations, which is
+//! enough for small servers, and [`Device`] does the same for character
+//! devices on Unix.  Windows named pipes can't be, since the standard
+//! library has no type for them, and the reactor only supports sockets.
+//!
+//! ```rust
+//! # #[cfg(unix)] {
+//! use std::{
+//!     io::{Read, Write},
+//!     os::unix::net::UnixStream,
+//! };
+//!
+//! use pasts::{io::Source, prelude::*};
+//!
+//! let (a, b) = Source::<UnixStream>::pair().unwrap();
+//!
+//! pasts::Executor::default().block_on(async move {
+//!     a.writable().next().await.unwrap();
+//!     a.get_ref().write_all(b"ping").unwrap();
+//!     b.readable().next().await.unwrap();
+//!
+//!     let mut buf = [0; 4];
+//!
+//!     b.get_ref().read_exact(&mut buf).unwrap();
+//!     assert_eq!(&buf, b"ping");
+//! });
+//! # }
+//! ```
+
+#[cfg(windows)]
+use std::os::windows::io::AsSocket;
+#[cfg(unix)]
+use std::os::{
+    fd::AsFd,
+    unix::net::{UnixListener, UnixStream},
+};
+use std::{
+    fmt,
+    io::{self, Read as _, Write as _},
+    net::{SocketAddr, TcpListener, TcpStream, UdpSocket},
+};
+#[cfg(unix)]
+use std::{
+    fs::{File, OpenOptions},
+    os::unix::net::UnixDatagram,
+    path::Path,
+};
+
+use async_io::Async;
+
+use crate::prelude::*;
+
+/// I/O handle registered for readiness notifications.
+pub struct Source<T>(Async<T>);
+
+impl<T: fmt::Debug> fmt::Debug for Source<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Source").field(self.get_ref()).finish()
+    }
+}
+
+#[cfg(unix)]
+impl<T: AsFd> Source<T> {
+    /// Register `io`, putting it in non-blocking mode.
+    pub fn new(io: T) -> io::Result<Self> {
+        Async::new(io).map(Self)
+    }
+}
+
+#[cfg(windows)]
+impl<T: AsSocket> Source<T> {
+    /// Register `io`, putting it in non-blocking mode.
+    pub fn new(io: T) -> io::Result<Self> {
+        Async::new(io).map(Self)
+    }
+}
+
+impl<T> Source<T> {
+    /// Get the handle, for reading and writing once it's ready.
+    pub fn get_ref(&self) -> &T {
+        self.0.get_ref()
+    }
+
+    /// Unregister the handle, and put it back in blocking mode.
+    pub fn into_inner(self) -> io::Result<T> {
+        self.0.into_inner()
+    }
+
+    /// Get a [`Notify`] for when the handle becomes readable.
+    pub fn readable(&self) -> Readable<'_, T> {
+        Readable(&self.0)
+    }
+
+    /// Get a [`Notify`] for when the handle becomes writable.
+    pub fn writable(&self) -> Writable<'_, T> {
+        Writable(&self.0)
+    }
+
+    /// Retry the non-blocking read `op` each time the handle becomes
+    /// readable, until it doesn't return [`io::ErrorKind::WouldBlock`].
+    pub fn read_with<'a, R>(
+        &'a self,
+        op: impl FnMut(&T) -> io::Result<R> + Unpin + 'a,
+    ) -> impl Future<Output = io::Result<R>> + 'a {
+        Retry {
+            source: &self.0,
+            write: false,
+            op,
+        }
+    }
+
+    /// Retry the non-blocking write `op` each time the handle becomes
+    /// writable, until it doesn't return [`io::ErrorKind::WouldBlock`].
+    pub fn write_with<'a, R>(
+        &'a self,
+        op: impl FnMut(&T) -> io::Result<R> + Unpin + 'a,
+    ) -> impl Future<Output = io::Result<R>> + 'a {
+        Retry {
+            source: &self.0,
+            write: true,
+            op,
+        }
+    }
+}
+
+// Future that retries a non-blocking operation on readiness.
+struct Retry<'a, T, F> {
+    source: &'a Async<T>,
+    write: bool,
+    op: F,
+}
+
+impl<T, R, F> Future for Retry<'_, T, F>
+where
+    F: FnMut(&T) -> io::Result<R> + Unpin,
+{
+    type Output = io::Result<R>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            match (this.op)(this.source.get_ref()) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                output => return Ready(output),
+            }
+
+            let ready = if this.write {
+                this.source.poll_writable(t)
+            } else {
+                this.source.poll_readable(t)
+            };
+
+            match ready {
+                Pending => return Pending,
+                Ready(Err(e)) => return Ready(Err(e)),
+                Ready(Ok(())) => {}
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Source<UnixStream> {
+    /// Create a pair of connected Unix sockets.
+    pub fn pair() -> io::Result<(Self, Self)> {
+        let (a, b) = UnixStream::pair()?;
+
+        Ok((Self::new(a)?, Self::new(b)?))
+    }
+
+    /// Connect to the Unix socket at `path`.
+    ///
+    /// Connecting to a listening Unix socket doesn't wait on the listener to
+    /// accept, so this only blocks if its backlog is full.
+    pub fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(UnixStream::connect(path)?)
+    }
+}
+
+#[cfg(unix)]
+impl Source<UnixListener> {
+    /// Listen on a Unix socket at `path`.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(UnixListener::bind(path)?)
+    }
+
+    /// Get a [`Notify`] for connections, as they're accepted.
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming(&self.0)
+    }
+}
+
+#[cfg(unix)]
+impl Source<UnixDatagram> {
+    /// Create a pair of connected Unix datagram sockets.
+    pub fn pair() -> io::Result<(Self, Self)> {
+        let (a, b) = UnixDatagram::pair()?;
+
+        Ok((Self::new(a)?, Self::new(b)?))
+    }
+
+    /// Bind a Unix datagram socket to `path`.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(UnixDatagram::bind(path)?)
+    }
+}
+
+/// [`Notify`] returned from [`Source::readable()`].
+pub struct Readable<'a, T>(&'a Async<T>);
+
+impl<T> fmt::Debug for Readable<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Readable").finish_non_exhaustive()
+    }
+}
+
+impl<T> Notify for Readable<'_, T> {
+    type Event = io::Result<()>;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        self.0.poll_readable(t)
+    }
+}
+
+/// [`Notify`] returned from [`Source::writable()`].
+pub struct Writable<'a, T>(&'a Async<T>);
+
+impl<T> fmt::Debug for Writable<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Writable").finish_non_exhaustive()
+    }
+}
+
+impl<T> Notify for Writable<'_, T> {
+    type Event = io::Result<()>;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        self.0.poll_writable(t)
+    }
+}
+
+/// [`Notify`] returned from [`Source::incoming()`].
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Incoming<'a>(&'a Async<UnixListener>);
+
+#[cfg(unix)]
+impl Notify for Incoming<'_> {
+    type Event = io::Result<Source<UnixStream>>;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        loop {
+            match self.0.poll_readable(t) {
+                Pending => return Pending,
+                Ready(Err(e)) => return Ready(Err(e)),
+                Ready(Ok(())) => {}
+            }
+
+            // Readiness may be stale, in which case wait for the next
+            match self.0.get_ref().accept() {
+                Ok((stream, _)) => return Ready(Source::new(stream)),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Ready(Err(e)),
+            }
+        }
+    }
+}
+
+/// TCP connection, with futures for reading and writing.
+#[derive(Debug)]
+pub struct AsyncTcpStream(Source<TcpStream>);
+
+impl AsyncTcpStream {
+    /// Register a connected `stream`, putting it in non-blocking mode.
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        Source::new(stream).map(Self)
+    }
+
+    /// Connect to `addr`, without blocking while the connection is made.
+    pub async fn connect(addr: impl Into<SocketAddr>) -> io::Result<Self> {
+        Async::<TcpStream>::connect(addr)
+            .await
+            .map(|s| Self(Source(s)))
+    }
+
+    /// Get the [`Source`], for readiness notifys and the [`TcpStream`].
+    pub fn source(&self) -> &Source<TcpStream> {
+        &self.0
+    }
+
+    /// Read into `buf`, returning how many bytes were read.
+    ///
+    /// Reads zero bytes once the peer has closed its side of the connection.
+    pub fn read<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.0.read_with(move |mut stream| stream.read(buf))
+    }
+
+    /// Write from `buf`, returning how many bytes were written.
+    pub fn write<'a>(
+        &'a self,
+        buf: &'a [u8],
+    ) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.0.write_with(move |mut stream| stream.write(buf))
+    }
+}
+
+/// TCP listener, with a future for accepting connections.
+#[derive(Debug)]
+pub struct AsyncTcpListener(Source<TcpListener>);
+
+impl AsyncTcpListener {
+    /// Listen for connections on `addr`.
+    pub fn bind(addr: impl Into<SocketAddr>) -> io::Result<Self> {
+        Source::new(TcpListener::bind(addr.into())?).map(Self)
+    }
+
+    /// Get the [`Source`], for readiness notifys and the [`TcpListener`].
+    pub fn source(&self) -> &Source<TcpListener> {
+        &self.0
+    }
+
+    /// Accept the next connection.
+    pub async fn accept(&self) -> io::Result<(AsyncTcpStream, SocketAddr)> {
+        let (stream, addr) = self.0.read_with(TcpListener::accept).await?;
+
+        Ok((AsyncTcpStream::new(stream)?, addr))
+    }
+}
+
+/// UDP socket, with futures for sending and receiving datagrams.
+#[derive(Debug)]
+pub struct AsyncUdpSocket(Source<UdpSocket>);
+
+impl AsyncUdpSocket {
+    /// Bind a UDP socket to `addr`.
+    pub fn bind(addr: impl Into<SocketAddr>) -> io::Result<Self> {
+        Source::new(UdpSocket::bind(addr.into())?).map(Self)
+    }
+
+    /// Get the [`Source`], for readiness notifys and the [`UdpSocket`].
+    ///
+    /// Use [`UdpSocket::connect()`] through it to set the address for
+    /// [`AsyncUdpSocket::send()`] and [`AsyncUdpSocket::recv()`].
+    pub fn source(&self) -> &Source<UdpSocket> {
+        &self.0
+    }
+
+    /// Send a datagram to `addr`, returning how many bytes were sent.
+    pub fn send_to<'a>(
+        &'a self,
+        buf: &'a [u8],
+        addr: impl Into<SocketAddr>,
+    ) -> impl Future<Output = io::Result<usize>> + 'a {
+        let addr = addr.into();
+
+        self.0.write_with(move |socket| socket.send_to(buf, addr))
+    }
+
+    /// Receive a datagram into `buf`, returning its length and sender.
+    pub fn recv_from<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + 'a {
+        self.0.read_with(move |socket| socket.recv_from(buf))
+    }
+
+    /// Send a datagram to the connected address.
+    pub fn send<'a>(
+        &'a self,
+        buf: &'a [u8],
+    ) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.0.write_with(move |socket| socket.send(buf))
+    }
+
+    /// Receive a datagram from the connected address into `buf`.
+    pub fn recv<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.0.read_with(move |socket| socket.recv(buf))
+    }
+}
+
+/// Character device, such as a serial port or input event node, with futures
+/// for reading and writing.
+///
+/// Regular files are always ready, so the reactor refuses to register them.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Device(Source<File>);
+
+#[cfg(unix)]
+impl Device {
+    /// Register an open `device`, putting it in non-blocking mode.
+    pub fn new(device: File) -> io::Result<Self> {
+        Source::new(device).map(Self)
+    }
+
+    /// Open the device at `path` for reading and writing.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(OpenOptions::new().read(true).write(true).open(path)?)
+    }
+
+    /// Open the device at `path` for reading only, as needed for devices like
+    /// `/dev/input/event*`.
+    pub fn open_read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+
+    /// Get the [`Source`], for readiness notifys and the [`File`].
+    pub fn source(&self) -> &Source<File> {
+        &self.0
+    }
+
+    /// Read into `buf`, returning how many bytes were read.
+    pub fn read<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.0.read_with(move |mut file| file.read(buf))
+    }
+
+    /// Write from `buf`, returning how many bytes were written.
+    pub fn write<'a>(
+        &'a self,
+        buf: &'a [u8],
+    ) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.0.write_with(move |mut file| file.write(buf))
+    }
+}
@@ -0,0 +1,313 @@
+use alloc::rc::Rc;
+#[cfg(feature = "std")]
+use core::any::Any;
+use core::{cell::RefCell, fmt};
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{notify::WakerSlot, prelude::*};
+
+struct State<T> {
+    output: Option<T>,
+    handle: WakerSlot,
+    task: WakerSlot,
+    cancelled: bool,
+    // Set if the task was dropped before completing
+    dropped: bool,
+    // Set once the handle is gone, so nothing needs the task's waker
+    detached: bool,
+}
+
+/// A handle to the output of a spawned task.
+///
+/// Awaiting the handle waits for the task to complete, and returns its output.
+/// If the task is dropped before it completes (for example, when it's
+/// cancelled, or the executor stops running with the task still pending),
+/// [`TaskDropped`] is returned instead.  By default, dropping the handle
+/// detaches the task so it keeps running in the background; use
+/// [`JoinHandle::cancel_on_drop()`] to cancel the task instead.
+///
+/// Created with
+/// [`Executor::spawn_with_handle()`](crate::Executor::spawn_with_handle()).
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<State<T>>>,
+    cancel_on_drop: bool,
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("cancel_on_drop", &self.cancel_on_drop)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> JoinHandle<T> {
+    /// Cancel the task when this handle is dropped, rather than detaching it.
+    pub fn cancel_on_drop(mut self) -> Self {
+        self.cancel_on_drop = true;
+        self
+    }
+
+    /// Let the task keep running in the background, discarding its output.
+    pub fn detach(mut self) {
+        self.cancel_on_drop = false;
+    }
+
+    /// Cancel the task, dropping it next time the executor gets to it.
+    pub fn cancel(mut self) {
+        self.cancel_on_drop = true;
+    }
+
+    /// Check if the task has completed.
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().output.is_some()
+    }
+}
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.borrow_mut();
+
+        state.detached = true;
+
+        // Don't keep the task's waker alive once it can't be cancelled
+        if !self.cancel_on_drop {
+            state.task.take();
+            return;
+        }
+
+        state.cancelled = true;
+
+        state.task.wake();
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, TaskDropped>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+
+        if let Some(output) = state.output.take() {
+            return Ready(Ok(output));
+        }
+
+        if state.dropped {
+            return Ready(Err(TaskDropped));
+        }
+
+        state.handle.register(t.waker());
+        Pending
+    }
+}
+
+/// Error from a [`JoinHandle`] whose task was dropped before completing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TaskDropped;
+
+impl fmt::Display for TaskDropped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("task dropped before completing")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TaskDropped {}
+
+// Task that sends its output to a `JoinHandle`.
+pub(crate) struct Joinable<F: Future> {
+    // Taken once complete
+    future: Option<Pin<Box<F>>>,
+    state: Rc<RefCell<State<F::Output>>>,
+}
+
+impl<F: Future> Future for Joinable<F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        let this = self.get_mut();
+
+        if this.state.borrow().cancelled {
+            return Ready(());
+        }
+
+        let Some(future) = this.future.as_mut() else {
+            return Ready(());
+        };
+        let output = future.as_mut().poll(t);
+        let mut state = this.state.borrow_mut();
+        let Ready(output) = output else {
+            // Only needed for the handle to cancel the task
+            if !state.detached {
+                state.task.register(t.waker());
+            }
+
+            return Pending;
+        };
+
+        state.output = Some(output);
+
+        state.handle.wake();
+
+        // Don't keep the waker alive past completion
+        state.task.take();
+        drop(state);
+        this.future = None;
+        Ready(())
+    }
+}
+
+impl<F: Future> Drop for Joinable<F> {
+    fn drop(&mut self) {
+        if self.future.is_none() {
+            return;
+        }
+
+        // Wake the handle, so it doesn't wait forever
+        let mut state = self.state.borrow_mut();
+
+        state.dropped = true;
+
+        state.handle.wake();
+    }
+}
+
+/// Error from a task spawned with
+/// [`Executor::spawn_catching()`](crate::Executor::spawn_catching()) that
+/// panicked.
+#[cfg(feature = "std")]
+pub struct Panicked(Box<dyn Any + Send>);
+
+#[cfg(feature = "std")]
+impl Panicked {
+    /// Get the panic message, if the task panicked with a string.
+    pub fn message(&self) -> Option<&str> {
+        if let Some(message) = self.0.downcast_ref::<&str>() {
+            return Some(message);
+        }
+
+        self.0.downcast_ref::<String>().map(String::as_str)
+    }
+
+    /// Get the panic payload, for example to continue the panic with
+    /// [`std::panic::resume_unwind()`].
+    pub fn into_payload(self) -> Box<dyn Any + Send> {
+        self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Panicked").field(&self.message()).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            Some(message) => write!(f, "task panicked: {message}"),
+            None => f.write_str("task panicked"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Panicked {}
+
+// Future that returns a panic while polling as an error.
+//
+// The task owns the future and is dropped once it completes, so nothing can
+// observe its state after a panic; that's what makes `AssertUnwindSafe` okay
+// here without asking the caller for it.
+#[cfg(feature = "std")]
+pub(crate) struct CatchUnwind<F>(Pin<Box<F>>);
+
+#[cfg(feature = "std")]
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Panicked>;
+
+    fn poll(mut self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let future = self.0.as_mut();
+
+        match panic::catch_unwind(AssertUnwindSafe(|| future.poll(t))) {
+            Ok(Ready(output)) => Ready(Ok(output)),
+            Ok(Pending) => Pending,
+            Err(payload) => Ready(Err(Panicked(payload))),
+        }
+    }
+}
+
+// Wrap a future to return panics as errors.
+#[cfg(feature = "std")]
+pub(crate) fn catch_unwind<F: Future>(f: F) -> CatchUnwind<F> {
+    CatchUnwind(Box::pin(f))
+}
+
+// Future that calls `cancel` if it's dropped before completing.
+pub(crate) struct OnCancel<F, C: FnOnce()> {
+    future: Pin<Box<F>>,
+    cancel: Option<C>,
+}
+
+impl<F, C: FnOnce()> Unpin for OnCancel<F, C> {}
+
+impl<F: Future, C: FnOnce()> Future for OnCancel<F, C> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<F::Output> {
+        let this = self.get_mut();
+        let output = this.future.as_mut().poll(t);
+
+        if output.is_ready() {
+            this.cancel = None;
+        }
+
+        output
+    }
+}
+
+impl<F, C: FnOnce()> Drop for OnCancel<F, C> {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+// Wrap a future to call `cancel` if it's dropped before completing.
+pub(crate) fn on_cancel<F: Future, C: FnOnce()>(
+    f: F,
+    cancel: C,
+) -> OnCancel<F, C> {
+    OnCancel {
+        future: Box::pin(f),
+        cancel: Some(cancel),
+    }
+}
+
+// Create a task along with its handle.
+pub(crate) fn joinable<F: Future>(
+    f: F,
+) -> (Joinable<F>, JoinHandle<F::Output>) {
+    let state = Rc::new(RefCell::new(State {
+        output: None,
+        handle: WakerSlot::new(),
+        task: WakerSlot::new(),
+        cancelled: false,
+        dropped: false,
+        detached: false,
+    }));
+    let task = Joinable {
+        future: Some(Box::pin(f)),
+        state: state.clone(),
+    };
+    let handle = JoinHandle {
+        state,
+        cancel_on_drop: false,
+    };
+
+    (task, handle)
+}
@@ -5,6 +5,29 @@
 //!
 //!  - Disable _`std`_ to use pasts without the standard library.
 //!  - Enable _`web`_ to use pasts within the javascript DOM.
+//!  - Enable _`web_lite`_ instead of _`web`_ for smaller wasm binaries, with a
+//!    minimal scheduler in place of `wasm-bindgen-futures`.  Everything
+//!    documented for _`web`_ also applies to _`web_lite`_.  Both add the
+//!    [`web`](mod@web) module.  With _`web`_, wasm built with atomics can also
+//!    use [`Executor::spawn_blocking()`] and `Workers`.
+//!  - Enable _`stream`_ to use any `futures_core::Stream` as a [`Notify`].
+//!  - Enable _`allocator_api`_ (nightly only) to allocate tasks with a custom
+//!    allocator, or fallibly with [`Executor::try_spawn_alloc()`].
+//!  - Enable _`async_main`_ to spawn onto an [`Executor`] through
+//!    `async_main::Spawn`.
+//!  - Enable _`winit`_ to run an [`Executor`] on winit's event loop, with the
+//!    [`winit`](mod@winit) module.
+//!  - Enable _`io`_ for [`Notify`]s on the readiness of OS I/O sources, such as
+//!    Unix sockets, with the [`io`](mod@io) module.
+//!  - Enable _`test_util`_ to record traces of an [`Executor`]'s scheduling,
+//!    with the [`test_util`](mod@test_util) module, and to check custom
+//!    [`Notify`]s with [`notify::conformance`].
+//!  - Enable _`stress_test`_ to run the [`stress`](mod@stress) tests against an
+//!    [`Executor`] with a custom [`Pool`].
+//!  - Enable _`macros`_ to derive [`Notify`] for structs of notifys, and for
+//!    the `#[pasts::main]` attribute.
+//!  - Enable _`log`_ to log debug-level records (with the `pasts` target) as
+//!    executors spawn and complete tasks, park, and are unparked by wakes.
 //!
 //! # Getting Started
 //!
@@ -38,6 +61,7 @@
 //! ```
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 #![forbid(unsafe_code, missing_docs)]
 #![doc(
     html_logo_url = "https://ardaku.github.io/mm/logo.svg",
@@ -46,16 +70,105 @@
 )]
 
 extern crate alloc;
+// The lite web scheduler's queue is thread-local
+#[cfg(all(feature = "web_lite", not(feature = "std")))]
+extern crate std;
 
+#[cfg(all(feature = "io", not(feature = "web_lite")))]
+pub mod io;
 pub mod notify;
+pub mod sync;
+pub mod time;
 
+#[cfg(all(feature = "stress_test", not(feature = "web_lite")))]
+pub mod stress;
+#[cfg(all(feature = "test_util", not(feature = "web_lite")))]
+pub mod test_util;
+#[cfg(feature = "web_lite")]
+pub mod web;
+#[cfg(all(feature = "winit", not(feature = "web_lite")))]
+pub mod winit;
+
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+
+#[cfg(all(
+    feature = "std",
+    any(
+        not(feature = "web_lite"),
+        all(feature = "web", target_feature = "atomics")
+    )
+))]
+mod blocking;
+#[cfg(feature = "std")]
+mod bridge;
+mod cancel;
+#[cfg(feature = "std")]
+mod condvar;
+mod join;
+#[cfg(not(feature = "web_lite"))]
+mod list;
 mod r#loop;
+#[cfg(feature = "std")]
+mod mutex;
+mod park;
+#[cfg(feature = "std")]
+mod progress;
+mod raw_wake;
+mod scope;
+mod shutdown;
 mod spawn;
+mod stats;
+#[cfg(any(feature = "std", feature = "web_lite"))]
+mod timer;
+#[cfg(feature = "std")]
+mod work;
+#[cfg(all(
+    feature = "std",
+    any(
+        not(feature = "web_lite"),
+        all(feature = "web", target_feature = "atomics")
+    )
+))]
+mod workers;
+
+#[cfg(feature = "macros")]
+pub use pasts_macros::main;
 
 use self::prelude::*;
+#[cfg(all(
+    feature = "std",
+    any(
+        not(feature = "web_lite"),
+        all(feature = "web", target_feature = "atomics")
+    )
+))]
 pub use self::{
-    r#loop::Loop,
-    spawn::{Executor, Park, Pool},
+    blocking::{BlockingHandle, BlockingQueue},
+    workers::Workers,
+};
+#[cfg(feature = "std")]
+pub use self::{bridge::WakerBridge, join::Panicked, spawn::DrainPolicy};
+pub use self::{
+    cancel::{
+        AbortHandle, AbortRegistration, Abortable, Aborted, CancelToken,
+        Cancelled,
+    },
+    join::{JoinHandle, TaskDropped},
+    park::{Parker, Unparker},
+    r#loop::{
+        switch, Flow, IntoPoll, Loop, Merged, ModeLoop, PinLoop, PollOrder,
+        Transition,
+    },
+    raw_wake::{RawWake, RawWakeHandle, RawWakeNotify},
+    scope::{Scope, ScopeHandle},
+    shutdown::OnShutdown,
+    spawn::{
+        Diagnostic, Executor, Idle, PanicPolicy, Park, Pool, Spawn, Spawner,
+        TaskId, Ticker, TimedOut,
+    },
+    stats::{ExecutorStats, StatsSnapshot},
 };
 
 pub mod prelude {
@@ -82,3 +195,153 @@ pub mod prelude {
     /// scheduled to receive a wakeup instead.
     pub type Poll<T = ()> = core::task::Poll<T>;
 }
+
+// Support for `#[pasts::main]`, not public API.
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    use core::fmt::Debug;
+
+    use crate::{prelude::*, Executor};
+
+    // Output of `main()`.
+    pub trait Output: 'static {
+        // Value to return from `main()` when the future is spawned, rather
+        // than blocked on.
+        fn spawned() -> Self;
+
+        // Handle the output of a spawned future.
+        fn report(self);
+    }
+
+    impl Output for () {
+        fn spawned() -> Self {}
+
+        fn report(self) {}
+    }
+
+    impl<E: Debug + 'static> Output for Result<(), E> {
+        fn spawned() -> Self {
+            Ok(())
+        }
+
+        fn report(self) {
+            if let Err(error) = self {
+                panic!("Error: {error:?}");
+            }
+        }
+    }
+
+    pub fn main<T: Output>(f: impl Future<Output = T> + 'static) -> T {
+        #[cfg(feature = "web_lite")]
+        {
+            Executor::default().block_on(async { f.await.report() });
+            T::spawned()
+        }
+
+        #[cfg(not(feature = "web_lite"))]
+        {
+            let output = alloc::rc::Rc::new(core::cell::Cell::new(None));
+            let slot = output.clone();
+
+            Executor::default()
+                .block_on(async move { slot.set(Some(f.await)) });
+            output.take().expect("main future completed")
+        }
+    }
+}
+
+/// Pin values to the stack, without allocating.
+///
+/// This shadows each variable with a [`Pin<&mut T>`](Pin) to its value, so
+/// that `!Unpin` futures and notifies can be polled without boxing them.
+///
+/// ```rust
+/// use pasts::prelude::*;
+///
+/// async fn ready() -> u32 {
+///     42
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     // Shadow existing variables
+///     let future = ready();
+///
+///     pasts::pin!(future);
+///     assert_eq!(future.as_mut().await, 42);
+///
+///     // Or declare new ones
+///     pasts::pin! {
+///         let first = ready().fuse();
+///         let second = ready().fuse();
+///     }
+///
+///     let mut notifys: &mut [Pin<&mut dyn Notify<Event = u32>>] =
+///         &mut [first, second];
+///
+///     assert_eq!(notifys.next().await, (0, 42));
+///     assert_eq!(notifys.next().await, (1, 42));
+/// });
+/// ```
+#[macro_export]
+macro_rules! pin {
+    ($($x:ident),* $(,)?) => {
+        $(
+            #[allow(unused_mut)]
+            let mut $x = ::core::pin::pin!($x);
+        )*
+    };
+    ($(let $x:ident = $init:expr;)*) => {
+        $(
+            #[allow(unused_mut)]
+            let mut $x = ::core::pin::pin!($init);
+        )*
+    };
+}
+
+/// Create a fixed-size array of fused, stack-pinned [`Notify`]s from a list of
+/// futures, without allocating.
+///
+/// The array must be bound with `let`, so that the pinned futures live until
+/// the end of the enclosing block.  Each element is a
+/// `Pin<&mut dyn Notify<Event = T>>`.
+///
+/// ```rust
+/// use pasts::{prelude::*, Loop};
+///
+/// struct App<'a> {
+///     tasks: [Pin<&'a mut dyn Notify<Event = &'static str>>; 2],
+///     done: usize,
+/// }
+///
+/// impl App<'_> {
+///     fn completion(&mut self, (id, val): (usize, &str)) -> Poll {
+///         println!("Task {id} completed: {val}");
+///         self.done += 1;
+///
+///         if self.done == self.tasks.len() {
+///             return Ready(());
+///         }
+///
+///         Pending
+///     }
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     let tasks = pasts::tasks_static![async { "Hello" }, async { "World" }];
+///     let mut app = App { tasks, done: 0 };
+///
+///     Loop::new(&mut app)
+///         .on(|s| &mut s.tasks[..], App::completion)
+///         .await;
+/// });
+/// ```
+#[macro_export]
+macro_rules! tasks_static {
+    ($($task:expr),* $(,)?) => {
+        [$(
+            ::core::pin::pin!($crate::notify::Fuse::fuse($task))
+                as ::core::pin::Pin<&mut dyn $crate::notify::Notify<Event = _>>
+        ),*]
+    };
+}
@@ -0,0 +1,142 @@
+use alloc::vec::Vec;
+use core::mem;
+
+// Doubly-linked list node, linked by index rather than by pointer so that
+// removal is O(1) and keys stay valid as the list changes.
+
+enum Node<T> {
+    Used {
+        value: T,
+        prev: Option<usize>,
+        next: Option<usize>,
+    },
+    Free(Option<usize>),
+}
+
+// Doubly-linked list with stable keys, stored in a slab.
+pub(crate) struct List<T> {
+    nodes: Vec<Node<T>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    free: Option<usize>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            head: None,
+            tail: None,
+            free: None,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Key of the first value in the list.
+    pub(crate) fn first(&self) -> Option<usize> {
+        self.head
+    }
+
+    // Key of the value after `key` in the list.
+    pub(crate) fn next(&self, key: usize) -> Option<usize> {
+        match self.nodes[key] {
+            Node::Used { next, .. } => next,
+            Node::Free(_) => None,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        match self.nodes.get_mut(key)? {
+            Node::Used { value, .. } => Some(value),
+            Node::Free(_) => None,
+        }
+    }
+
+    // Append a value, returning its key.
+    pub(crate) fn push_back(&mut self, value: T) -> usize {
+        let node = Node::Used {
+            value,
+            prev: self.tail,
+            next: None,
+        };
+        let key = match self.free {
+            Some(key) => {
+                let Node::Free(free) = mem::replace(&mut self.nodes[key], node)
+                else {
+                    unreachable!()
+                };
+
+                self.free = free;
+                key
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        match self.tail {
+            Some(tail) => self.set_next(tail, Some(key)),
+            None => self.head = Some(key),
+        }
+
+        self.tail = Some(key);
+        self.len += 1;
+        key
+    }
+
+    // Remove the value at `key`, freeing the key for reuse.
+    pub(crate) fn remove(&mut self, key: usize) -> Option<T> {
+        let Node::Used { .. } = self.nodes.get(key)? else {
+            return None;
+        };
+        let Node::Used { value, prev, next } =
+            mem::replace(&mut self.nodes[key], Node::Free(self.free))
+        else {
+            unreachable!()
+        };
+
+        match prev {
+            Some(prev) => self.set_next(prev, next),
+            None => self.head = next,
+        }
+
+        match next {
+            Some(next) => self.set_prev(next, prev),
+            None => self.tail = prev,
+        }
+
+        self.free = Some(key);
+        self.len -= 1;
+        Some(value)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter().filter_map(|node| match node {
+            Node::Used { value, .. } => Some(value),
+            Node::Free(_) => None,
+        })
+    }
+
+    fn set_next(&mut self, key: usize, link: Option<usize>) {
+        if let Node::Used { next, .. } = &mut self.nodes[key] {
+            *next = link;
+        }
+    }
+
+    fn set_prev(&mut self, key: usize, link: Option<usize>) {
+        if let Node::Used { prev, .. } = &mut self.nodes[key] {
+            *prev = link;
+        }
+    }
+}
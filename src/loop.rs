@@ -3,9 +3,199 @@ use crate::{prelude::*, Notify};
 pub trait Stateful<S, T>: Unpin {
     fn state(&mut self) -> &mut S;
 
-    fn poll(&mut self, _: &mut Task<'_>) -> Poll<Poll<T>> {
+    // Number of registered handlers.
+    fn len(&self) -> usize {
+        0
+    }
+
+    // Poll the handler at `index`, in registration order.
+    fn poll(&mut self, _index: usize, _: &mut Task<'_>) -> Poll<Poll<T>> {
         Pending
     }
+
+    // Poll every handler in one pass, newest first.
+    fn poll_newest(&mut self, _batch: bool, _: &mut Task<'_>) -> Poll<Poll<T>> {
+        Pending
+    }
+}
+
+/// Whether a [`Loop`] handler keeps the loop going, or exits it with a value.
+///
+/// Handlers may return this instead of a [`Poll`], which reads better when
+/// the loop's own meaning of [`Ready`] and [`Pending`] gets confused with the
+/// executor's.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*, Flow, Loop};
+///
+/// struct App {
+///     ticks: notify::Ready<u32>,
+/// }
+///
+/// impl App {
+///     fn tick(&mut self, tick: u32) -> Flow<u32> {
+///         Flow::Break(tick)
+///     }
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut app = App {
+///         ticks: notify::ready(7),
+///     };
+///     let tick = Loop::new(&mut app).on(|s| &mut s.ticks, App::tick).await;
+///
+///     assert_eq!(tick, 7);
+/// });
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Flow<T = ()> {
+    /// Keep handling events
+    Continue,
+    /// Exit the loop with a value
+    Break(T),
+}
+
+/// Conversion into the [`Poll`] that a [`Loop`] uses to decide whether to
+/// exit, implemented for [`Poll`] and [`Flow`].
+pub trait IntoPoll<T> {
+    /// Convert into a [`Poll`], where [`Ready`] exits the loop.
+    fn into_poll(self) -> Poll<T>;
+}
+
+impl<T> IntoPoll<T> for Poll<T> {
+    #[inline(always)]
+    fn into_poll(self) -> Poll<T> {
+        self
+    }
+}
+
+impl<T> IntoPoll<T> for Flow<T> {
+    #[inline(always)]
+    fn into_poll(self) -> Poll<T> {
+        match self {
+            Flow::Continue => Pending,
+            Flow::Break(output) => Ready(output),
+        }
+    }
+}
+
+/// Order that a [`Loop`] checks its handlers' notifys in.
+///
+/// After an event is handled, the loop goes back to the start of the order,
+/// so handlers that come first take priority over the ones after them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PollOrder {
+    /// Check the most recently registered handler first (default)
+    #[default]
+    NewestFirst,
+    /// Check the first registered handler first
+    OldestFirst,
+    /// Start after the last handler that produced an event, so that every
+    /// handler gets a fair turn
+    RoundRobin,
+}
+
+// Order and batching of a loop's handlers, shared by `Loop` and `PinLoop`.
+#[derive(Copy, Clone, Debug)]
+struct Schedule {
+    order: PollOrder,
+    start: usize,
+    batch: bool,
+}
+
+impl Schedule {
+    const fn new() -> Self {
+        Self {
+            order: PollOrder::NewestFirst,
+            start: 0,
+            batch: false,
+        }
+    }
+
+    // Poll `len` handlers with `poll`, until one exits the loop or none are
+    // ready.
+    #[inline(always)]
+    fn poll<T>(
+        &mut self,
+        len: usize,
+        t: &mut Task<'_>,
+        mut poll: impl FnMut(usize, &mut Task<'_>) -> Poll<Poll<T>>,
+    ) -> Poll<T> {
+        let mut handled = false;
+
+        'events: loop {
+            for i in 0..len {
+                let index = match self.order {
+                    PollOrder::NewestFirst => len - 1 - i,
+                    PollOrder::OldestFirst => i,
+                    PollOrder::RoundRobin => (self.start + i) % len,
+                };
+
+                match poll(index, t) {
+                    Ready(Ready(output)) => {
+                        self.start = index + 1;
+                        return Ready(output);
+                    }
+                    Ready(Pending) if self.batch => {
+                        self.start = index + 1;
+                        handled = true;
+                    }
+                    Ready(Pending) => {
+                        self.start = index + 1;
+                        continue 'events;
+                    }
+                    Pending => {}
+                }
+            }
+
+            // Notifys that were ready didn't register a wakeup
+            if handled {
+                t.waker().wake_by_ref();
+            }
+
+            return Pending;
+        }
+    }
+
+    // Poll with `poll_newest` for the default order, which checks every
+    // handler in one pass instead of looking each one up by index.
+    #[inline(always)]
+    fn poll_newest<T>(
+        &mut self,
+        t: &mut Task<'_>,
+        mut poll_newest: impl FnMut(bool, &mut Task<'_>) -> Poll<Poll<T>>,
+    ) -> Poll<T> {
+        loop {
+            match poll_newest(self.batch, t) {
+                Ready(Ready(output)) => return Ready(output),
+                // Notifys that were ready didn't register a wakeup
+                Ready(Pending) if self.batch => {
+                    t.waker().wake_by_ref();
+                    return Pending;
+                }
+                Ready(Pending) => {}
+                Pending => return Pending,
+            }
+        }
+    }
+}
+
+// Continue a newest-first pass from one handler's `poll` to the handlers
+// registered before it.
+#[inline(always)]
+fn then_older<T>(
+    poll: Poll<Poll<T>>,
+    batch: bool,
+    older: impl FnOnce() -> Poll<Poll<T>>,
+) -> Poll<Poll<T>> {
+    match poll {
+        Pending => older(),
+        Ready(Pending) if batch => match older() {
+            Ready(Ready(output)) => Ready(Ready(output)),
+            _ => Ready(Pending),
+        },
+        poll => poll,
+    }
 }
 
 #[derive(Debug)]
@@ -34,6 +224,7 @@ impl<S, T> Stateful<S, T> for Never<'_, S> {
 #[derive(Debug)]
 pub struct Loop<S: Unpin, T, F: Stateful<S, T>> {
     other: F,
+    schedule: Schedule,
     _phantom: core::marker::PhantomData<(S, T)>,
 }
 
@@ -43,22 +234,168 @@ impl<'a, S: Unpin, T> Loop<S, T, Never<'a, S>> {
         let other = Never(state);
         let _phantom = core::marker::PhantomData;
 
-        Loop { other, _phantom }
+        Loop {
+            other,
+            schedule: Schedule::new(),
+            _phantom,
+        }
     }
 }
 
 impl<S: Unpin, T, F: Stateful<S, T>> Loop<S, T, F> {
     /// Register an event handler.
-    pub fn on<N: Notify + Unpin + ?Sized>(
+    ///
+    /// By default, handlers registered later are checked first; see
+    /// [`Loop::order()`].
+    ///
+    /// `then` returns either a [`Poll`] or a [`Flow`], where [`Ready`] or
+    /// [`Flow::Break`] exits the loop with a value.
+    pub fn on<N: Notify + Unpin + ?Sized, R: IntoPoll<T>>(
         self,
         noti: impl for<'a> FnMut(&'a mut S) -> &'a mut N + Unpin,
-        then: fn(&mut S, N::Event) -> Poll<T>,
+        then: fn(&mut S, N::Event) -> R,
     ) -> Loop<S, T, impl Stateful<S, T>> {
-        let other = self.other;
-        let _phantom = core::marker::PhantomData;
-        let other = Looper { other, noti, then };
+        let Self {
+            other,
+            schedule,
+            _phantom,
+        } = self;
+        let index = other.len();
+        let other = Looper {
+            other,
+            index,
+            noti,
+            then,
+        };
 
-        Loop { other, _phantom }
+        Loop {
+            other,
+            schedule,
+            _phantom,
+        }
+    }
+
+    /// Register an event handler for futures created by `future`, without
+    /// having to fuse them or store them in the state.
+    ///
+    /// `future` is called when the loop starts, and again after each time
+    /// `then` handles a future's output without exiting the loop.  The
+    /// futures may not borrow the state.
+    ///
+    /// ```rust
+    /// use pasts::{prelude::*, Loop};
+    ///
+    /// struct Counter(u32);
+    ///
+    /// impl Counter {
+    ///     fn count(&mut self, count: u32) -> Poll<u32> {
+    ///         self.0 = count;
+    ///
+    ///         if count == 3 {
+    ///             return Ready(count);
+    ///         }
+    ///
+    ///         Pending
+    ///     }
+    /// }
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut counter = Counter(0);
+    ///     let count = Loop::new(&mut counter)
+    ///         .on_future(
+    ///             |s| {
+    ///                 let next = s.0 + 1;
+    ///
+    ///                 async move { next }
+    ///             },
+    ///             Counter::count,
+    ///         )
+    ///         .await;
+    ///
+    ///     assert_eq!(count, 3);
+    /// });
+    /// ```
+    pub fn on_future<N: Future, R: IntoPoll<T>>(
+        self,
+        future: impl FnMut(&mut S) -> N + Unpin,
+        then: fn(&mut S, N::Output) -> R,
+    ) -> Loop<S, T, impl Stateful<S, T>> {
+        let Self {
+            other,
+            schedule,
+            _phantom,
+        } = self;
+        let index = other.len();
+        let other = FutureLooper {
+            other,
+            index,
+            make: future,
+            future: None,
+            then,
+        };
+
+        Loop {
+            other,
+            schedule,
+            _phantom,
+        }
+    }
+
+    /// Set the order that handlers are checked in.
+    pub fn order(mut self, order: PollOrder) -> Self {
+        self.schedule.order = order;
+        self
+    }
+
+    /// Check every handler once per wake, handling all events that are ready
+    /// before yielding to the executor.
+    ///
+    /// Without batching, the loop goes back to the first handler after each
+    /// event, and only yields once none of the notifys are ready.
+    pub fn batch(mut self) -> Self {
+        self.schedule.batch = true;
+        self
+    }
+
+    /// Turn the loop into a [`Notify`] that produces each value a handler
+    /// exits with, instead of completing after the first.
+    ///
+    /// This allows the results of several handlers to be merged into one
+    /// stream of events, which can then be handled by a larger loop.
+    ///
+    /// ```rust
+    /// use pasts::{notify, prelude::*, Loop, PollOrder};
+    ///
+    /// struct Counters {
+    ///     evens: LocalBoxNotify<'static, u32>,
+    ///     odds: LocalBoxNotify<'static, u32>,
+    /// }
+    ///
+    /// fn counter(mut count: u32) -> LocalBoxNotify<'static, u32> {
+    ///     Box::pin(notify::poll_fn(move |_| {
+    ///         count += 2;
+    ///         Ready(count)
+    ///     }))
+    /// }
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut counters = Counters {
+    ///         evens: counter(0),
+    ///         odds: counter(1),
+    ///     };
+    ///     let mut merged = Loop::new(&mut counters)
+    ///         .on(|s| &mut s.evens, |_, n| Ready(n))
+    ///         .on(|s| &mut s.odds, |_, n| Ready(n))
+    ///         .order(PollOrder::RoundRobin)
+    ///         .into_notify();
+    ///
+    ///     assert_eq!(merged.next().await, 2);
+    ///     assert_eq!(merged.next().await, 3);
+    ///     assert_eq!(merged.next().await, 4);
+    /// });
+    /// ```
+    pub fn into_notify(self) -> Merged<S, T, F> {
+        Merged(self)
     }
 }
 
@@ -66,26 +403,130 @@ impl<S: Unpin, T: Unpin, F: Stateful<S, T>> Future for Loop<S, T, F> {
     type Output = T;
 
     #[inline]
-    fn poll(mut self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
-        while let Ready(output) = Pin::new(&mut self.other).poll(t) {
-            if let Ready(output) = output {
-                return Ready(output);
-            }
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        let len = this.other.len();
+        let other = &mut this.other;
+
+        if this.schedule.order == PollOrder::NewestFirst {
+            return this
+                .schedule
+                .poll_newest(t, |batch, t| other.poll_newest(batch, t));
         }
 
-        Pending
+        this.schedule.poll(len, t, |index, t| other.poll(index, t))
     }
 }
 
-struct Looper<S, T, E, F: Stateful<S, T>, P> {
+/// The [`Notify`] returned from [`Loop::into_notify()`]
+#[derive(Debug)]
+pub struct Merged<S: Unpin, T, F: Stateful<S, T>>(Loop<S, T, F>);
+
+impl<S: Unpin, T: Unpin, F: Stateful<S, T>> Notify for Merged<S, T, F> {
+    type Event = T;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
+        Pin::new(&mut self.get_mut().0).poll(t)
+    }
+}
+
+/// Where a [`switch()`] state machine goes after a mode's loop exits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Transition<M, T = ()> {
+    /// Switch to another mode, with its own set of handlers
+    To(M),
+    /// Exit the state machine with a value
+    Exit(T),
+}
+
+/// Boxed loop for one mode of a [`switch()`] state machine.
+pub type ModeLoop<'a, M, T = ()> =
+    Pin<Box<dyn Future<Output = Transition<M, T>> + 'a>>;
+
+/// Run `state` as a state machine, starting in `mode`, until a mode exits
+/// with [`Transition::Exit`].
+///
+/// `run` creates the loop for each mode, so that each mode only has the
+/// handlers it needs, instead of every handler checking which mode the
+/// application is in.  When a mode's loop exits with [`Transition::To`], its
+/// handlers are dropped and `run` is called with the next mode.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*, Flow, Loop, ModeLoop, Transition};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Mode {
+///     Menu,
+///     InGame,
+/// }
+///
+/// struct App {
+///     keys: LocalBoxNotify<'static, char>,
+///     score: u32,
+/// }
+///
+/// impl App {
+///     fn menu(&mut self, key: char) -> Flow<Transition<Mode, u32>> {
+///         match key {
+///             'p' => Flow::Break(Transition::To(Mode::InGame)),
+///             _ => Flow::Continue,
+///         }
+///     }
+///
+///     fn play(&mut self, key: char) -> Flow<Transition<Mode, u32>> {
+///         match key {
+///             'q' => Flow::Break(Transition::Exit(self.score)),
+///             'm' => Flow::Break(Transition::To(Mode::Menu)),
+///             _ => {
+///                 self.score += 1;
+///                 Flow::Continue
+///             }
+///         }
+///     }
+/// }
+///
+/// fn run(app: &mut App, mode: Mode) -> ModeLoop<'_, Mode, u32> {
+///     match mode {
+///         Mode::Menu => Box::pin(Loop::new(app).on(|s| &mut s.keys, App::menu)),
+///         Mode::InGame => Box::pin(Loop::new(app).on(|s| &mut s.keys, App::play)),
+///     }
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut keys = "xpabmxpcq".chars();
+///     let mut app = App {
+///         keys: Box::pin(notify::poll_fn(move |_| Ready(keys.next().unwrap()))),
+///         score: 0,
+///     };
+///
+///     assert_eq!(pasts::switch(&mut app, Mode::Menu, run).await, 3);
+/// });
+/// ```
+pub async fn switch<S, M, T>(
+    state: &mut S,
+    mut mode: M,
+    run: for<'a> fn(&'a mut S, M) -> ModeLoop<'a, M, T>,
+) -> T {
+    loop {
+        match run(state, mode).await {
+            Transition::To(next) => mode = next,
+            Transition::Exit(output) => return output,
+        }
+    }
+}
+
+struct Looper<S, E, F, P, R> {
     other: F,
+    index: usize,
     noti: P,
-    then: fn(&mut S, E) -> Poll<T>,
+    then: fn(&mut S, E) -> R,
 }
 
-impl<S, T, E, F, N, P> Stateful<S, T> for Looper<S, T, E, F, P>
+impl<S, T, E, F, N, P, R> Stateful<S, T> for Looper<S, E, F, P, R>
 where
     F: Stateful<S, T>,
+    R: IntoPoll<T>,
     N: Notify<Event = E> + Unpin + ?Sized,
     P: for<'a> FnMut(&'a mut S) -> &'a mut N + Unpin,
 {
@@ -95,14 +536,268 @@ where
     }
 
     #[inline]
-    fn poll(&mut self, t: &mut Task<'_>) -> Poll<Poll<T>> {
+    fn len(&self) -> usize {
+        self.index + 1
+    }
+
+    #[inline]
+    fn poll(&mut self, index: usize, t: &mut Task<'_>) -> Poll<Poll<T>> {
+        if index != self.index {
+            return self.other.poll(index, t);
+        }
+
+        let state = self.other.state();
+        let poll = (self.noti)(state).poll_next_unpin(t);
+
+        poll.map(|x| (self.then)(state, x).into_poll())
+    }
+
+    #[inline]
+    fn poll_newest(&mut self, batch: bool, t: &mut Task<'_>) -> Poll<Poll<T>> {
+        let poll = self.poll(self.index, t);
+
+        then_older(poll, batch, || self.other.poll_newest(batch, t))
+    }
+}
+
+struct FutureLooper<S, F, N: Future, P, R> {
+    other: F,
+    index: usize,
+    make: P,
+    future: Option<Pin<Box<N>>>,
+    then: fn(&mut S, N::Output) -> R,
+}
+
+impl<S, T, F, N, P, R> Stateful<S, T> for FutureLooper<S, F, N, P, R>
+where
+    F: Stateful<S, T>,
+    R: IntoPoll<T>,
+    N: Future,
+    P: FnMut(&mut S) -> N + Unpin,
+{
+    #[inline]
+    fn state(&mut self) -> &mut S {
+        self.other.state()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.index + 1
+    }
+
+    #[inline]
+    fn poll(&mut self, index: usize, t: &mut Task<'_>) -> Poll<Poll<T>> {
+        if index != self.index {
+            return self.other.poll(index, t);
+        }
+
         let state = self.other.state();
-        let poll = Pin::new((self.noti)(state)).poll_next(t);
+        let future = match self.future {
+            Some(ref mut future) => future,
+            None => self.future.insert(Box::pin((self.make)(state))),
+        };
+        let Ready(output) = future.as_mut().poll(t) else {
+            return Pending;
+        };
+        let poll = (self.then)(state, output).into_poll();
 
-        if let Ready(out) = poll.map(|x| (self.then)(state, x)) {
-            Ready(out)
-        } else {
-            self.other.poll(t)
+        // Start the next future, reusing the allocation
+        match poll {
+            Ready(_) => self.future = None,
+            Pending => future.set((self.make)(state)),
         }
+
+        Ready(poll)
+    }
+
+    #[inline]
+    fn poll_newest(&mut self, batch: bool, t: &mut Task<'_>) -> Poll<Poll<T>> {
+        let poll = self.poll(self.index, t);
+
+        then_older(poll, batch, || self.other.poll_newest(batch, t))
+    }
+}
+
+pub trait PinStateful<S: ?Sized, T>: Unpin {
+    fn state(&mut self) -> Pin<&mut S>;
+
+    // Number of registered handlers.
+    fn len(&self) -> usize {
+        0
+    }
+
+    // Poll the handler at `index`, in registration order.
+    fn poll(&mut self, _index: usize, _: &mut Task<'_>) -> Poll<Poll<T>> {
+        Pending
+    }
+
+    // Poll every handler in one pass, newest first.
+    fn poll_newest(&mut self, _batch: bool, _: &mut Task<'_>) -> Poll<Poll<T>> {
+        Pending
+    }
+}
+
+#[derive(Debug)]
+pub struct PinNever<'a, S: ?Sized>(Pin<&'a mut S>);
+
+impl<S: ?Sized, T> PinStateful<S, T> for PinNever<'_, S> {
+    fn state(&mut self) -> Pin<&mut S> {
+        self.0.as_mut()
+    }
+}
+
+/// Composable asynchronous event loop over pinned state, which may be
+/// `!Unpin`.
+///
+/// This works like [`Loop`], except that handlers receive a
+/// [`Pin<&mut S>`](Pin), and the closures that pick each [`Notify`] out of
+/// the state project the pin onto it.  This allows self-referential futures
+/// (such as `async` blocks) to be stored directly in the state, without
+/// boxing.  Fields can be projected with crates like `pin-project`.
+///
+/// ```rust
+/// use pasts::{prelude::*, PinLoop};
+///
+/// async fn fetch() -> &'static str {
+///     "Hello, world!"
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     // An `async fn`'s future is `!Unpin`
+///     let state = core::pin::pin!(fetch().fuse());
+///
+///     let greeting = PinLoop::new(state)
+///         .on(|s| s, |_, greeting| Ready(greeting))
+///         .await;
+///
+///     assert_eq!(greeting, "Hello, world!");
+/// });
+/// ```
+#[derive(Debug)]
+pub struct PinLoop<S: ?Sized, T, F: PinStateful<S, T>> {
+    other: F,
+    schedule: Schedule,
+    _phantom: core::marker::PhantomData<fn(&S) -> T>,
+}
+
+impl<'a, S: ?Sized, T> PinLoop<S, T, PinNever<'a, S>> {
+    /// Create an empty event loop over pinned state.
+    pub fn new(state: Pin<&'a mut S>) -> Self {
+        let other = PinNever(state);
+        let _phantom = core::marker::PhantomData;
+
+        PinLoop {
+            other,
+            schedule: Schedule::new(),
+            _phantom,
+        }
+    }
+}
+
+impl<S: ?Sized, T, F: PinStateful<S, T>> PinLoop<S, T, F> {
+    /// Register an event handler.
+    ///
+    /// `noti` projects the pinned state onto the [`Notify`] to handle, and
+    /// `then` returns either a [`Poll`] or a [`Flow`].
+    pub fn on<N: Notify + ?Sized, R: IntoPoll<T>>(
+        self,
+        noti: impl for<'a> FnMut(Pin<&'a mut S>) -> Pin<&'a mut N> + Unpin,
+        then: fn(Pin<&mut S>, N::Event) -> R,
+    ) -> PinLoop<S, T, impl PinStateful<S, T>> {
+        let Self {
+            other,
+            schedule,
+            _phantom,
+        } = self;
+        let index = other.len();
+        let other = PinLooper {
+            other,
+            index,
+            noti,
+            then,
+        };
+
+        PinLoop {
+            other,
+            schedule,
+            _phantom,
+        }
+    }
+
+    /// Set the order that handlers are checked in.
+    pub fn order(mut self, order: PollOrder) -> Self {
+        self.schedule.order = order;
+        self
+    }
+
+    /// Check every handler once per wake, like [`Loop::batch()`].
+    pub fn batch(mut self) -> Self {
+        self.schedule.batch = true;
+        self
+    }
+}
+
+impl<S: ?Sized, T, F: PinStateful<S, T>> Unpin for PinLoop<S, T, F> {}
+
+impl<S: ?Sized, T, F: PinStateful<S, T>> Future for PinLoop<S, T, F> {
+    type Output = T;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        let len = this.other.len();
+        let other = &mut this.other;
+
+        if this.schedule.order == PollOrder::NewestFirst {
+            return this
+                .schedule
+                .poll_newest(t, |batch, t| other.poll_newest(batch, t));
+        }
+
+        this.schedule.poll(len, t, |index, t| other.poll(index, t))
+    }
+}
+
+struct PinLooper<S: ?Sized, E, F, P, R> {
+    other: F,
+    index: usize,
+    noti: P,
+    then: fn(Pin<&mut S>, E) -> R,
+}
+
+impl<S, T, E, F, N, P, R> PinStateful<S, T> for PinLooper<S, E, F, P, R>
+where
+    S: ?Sized,
+    F: PinStateful<S, T>,
+    R: IntoPoll<T>,
+    N: Notify<Event = E> + ?Sized,
+    P: for<'a> FnMut(Pin<&'a mut S>) -> Pin<&'a mut N> + Unpin,
+{
+    #[inline]
+    fn state(&mut self) -> Pin<&mut S> {
+        self.other.state()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.index + 1
+    }
+
+    #[inline]
+    fn poll(&mut self, index: usize, t: &mut Task<'_>) -> Poll<Poll<T>> {
+        if index != self.index {
+            return self.other.poll(index, t);
+        }
+
+        let poll = (self.noti)(self.other.state()).poll_next(t);
+
+        poll.map(|x| (self.then)(self.other.state(), x).into_poll())
+    }
+
+    #[inline]
+    fn poll_newest(&mut self, batch: bool, t: &mut Task<'_>) -> Poll<Poll<T>> {
+        let poll = self.poll(self.index, t);
+
+        then_older(poll, batch, || self.other.poll_newest(batch, t))
     }
 }
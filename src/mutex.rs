@@ -0,0 +1,406 @@
+// An async mutex, for holding shared state across `.await`s.
+//
+// Without `unsafe`, a guard can't point into the mutex, so locking moves the
+// value out into the guard, and dropping the guard moves it back.  An empty
+// slot means the mutex is locked.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+    task::Waker,
+};
+use std::sync::{self, PoisonError};
+
+use crate::prelude::*;
+
+struct State<T> {
+    value: Option<T>,
+    waiters: Vec<Waker>,
+}
+
+/// Mutual exclusion lock that tasks wait on asynchronously, instead of
+/// blocking the thread.
+///
+/// Unlike [`std::sync::Mutex`], its guards can be held across `.await`s.
+/// Locking moves the value into the guard (and unlocking moves it back), so
+/// large values should be boxed.
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use pasts::sync::Mutex;
+///
+/// let executor = pasts::Executor::default();
+/// let count = Arc::new(Mutex::new(0));
+///
+/// for _ in 0..3 {
+///     let count = count.clone();
+///
+///     executor.spawn(async move {
+///         let mut count = count.lock_owned().await;
+///
+///         *count += 1;
+///     });
+/// }
+///
+/// executor.block_on(async {});
+/// assert_eq!(*count.try_lock().unwrap(), 3);
+/// ```
+pub struct Mutex<T> {
+    state: sync::Mutex<State<T>>,
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mutex")
+            .field("locked", &self.state().value.is_none())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Mutex<T> {
+    /// Create an unlocked mutex holding `value`.
+    pub const fn new(value: T) -> Self {
+        Self {
+            state: sync::Mutex::new(State {
+                value: Some(value),
+                waiters: Vec::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> sync::MutexGuard<'_, State<T>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Take the value, or register to wake the task once it's put back.
+    fn poll_take(&self, t: &mut Task<'_>) -> Poll<T> {
+        let mut state = self.state();
+
+        if let Some(value) = state.value.take() {
+            return Ready(value);
+        }
+
+        if !state.waiters.iter().any(|w| w.will_wake(t.waker())) {
+            state.waiters.push(t.waker().clone());
+        }
+
+        Pending
+    }
+
+    // Put the value back, waking the tasks waiting to take it.
+    fn put(&self, value: T) {
+        let waiters = {
+            let mut state = self.state();
+
+            state.value = Some(value);
+            core::mem::take(&mut state.waiters)
+        };
+
+        // Every waiter gets a chance, in case one was dropped before locking
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+
+    // Lock the mutex, or register to wake the task once it's unlocked.
+    pub(crate) fn poll_lock(
+        &self,
+        t: &mut Task<'_>,
+    ) -> Poll<MutexGuard<'_, T>> {
+        self.poll_take(t).map(|value| MutexGuard {
+            mutex: self,
+            value: Some(value),
+        })
+    }
+
+    // Like `poll_lock()`, with an owned guard.
+    pub(crate) fn poll_lock_owned(
+        self: &Arc<Self>,
+        t: &mut Task<'_>,
+    ) -> Poll<OwnedMutexGuard<T>> {
+        self.poll_take(t).map(|value| OwnedMutexGuard {
+            mutex: self.clone(),
+            value: Some(value),
+        })
+    }
+
+    /// Wait to lock the mutex.
+    pub fn lock(&self) -> Lock<'_, T> {
+        Lock(self)
+    }
+
+    /// Lock the mutex if it's unlocked, without waiting.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        let value = self.state().value.take()?;
+
+        Some(MutexGuard {
+            mutex: self,
+            value: Some(value),
+        })
+    }
+
+    /// Wait to lock the mutex, with a guard that keeps the mutex alive.
+    ///
+    /// Unlike [`MutexGuard`], the guard isn't borrowed, so it can be held
+    /// across `.await`s in `'static` tasks.
+    pub fn lock_owned(self: Arc<Self>) -> LockOwned<T> {
+        LockOwned(Some(self))
+    }
+
+    /// Lock the mutex if it's unlocked, without waiting, with a guard that
+    /// keeps the mutex alive.
+    ///
+    /// Returns the mutex back if it's locked.
+    pub fn try_lock_owned(
+        self: Arc<Self>,
+    ) -> Result<OwnedMutexGuard<T>, Arc<Self>> {
+        let Some(value) = self.state().value.take() else {
+            return Err(self);
+        };
+
+        Ok(OwnedMutexGuard {
+            mutex: self,
+            value: Some(value),
+        })
+    }
+
+    /// Get the value, without locking since the mutex is borrowed mutably.
+    ///
+    /// # Panics
+    /// If a guard was leaked with [`core::mem::forget()`], taking the value
+    /// with it.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.state
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .value
+            .as_mut()
+            .expect("mutex guard was leaked")
+    }
+
+    /// Get the value, consuming the mutex.
+    ///
+    /// # Panics
+    /// If a guard was leaked with [`core::mem::forget()`], taking the value
+    /// with it.
+    pub fn into_inner(self) -> T {
+        self.state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .value
+            .expect("mutex guard was leaked")
+    }
+}
+
+/// Future returned from [`Mutex::lock()`].
+#[derive(Debug)]
+pub struct Lock<'a, T>(&'a Mutex<T>);
+
+impl<'a, T> Future for Lock<'a, T> {
+    type Output = MutexGuard<'a, T>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        self.0.poll_lock(t)
+    }
+}
+
+/// Future returned from [`Mutex::lock_owned()`].
+#[derive(Debug)]
+pub struct LockOwned<T>(Option<Arc<Mutex<T>>>);
+
+impl<T> Unpin for LockOwned<T> {}
+
+impl<T> Future for LockOwned<T> {
+    type Output = OwnedMutexGuard<T>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mutex = this.0.as_ref().expect("polled after completion");
+        let guard = mutex.poll_lock_owned(t);
+
+        if guard.is_ready() {
+            this.0 = None;
+        }
+
+        guard
+    }
+}
+
+/// Guard that unlocks its [`Mutex`] when dropped.
+///
+/// Created with [`Mutex::lock()`] or [`Mutex::try_lock()`].
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+    // Only `None` while dropping
+    value: Option<T>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().expect("guard is locked")
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().expect("guard is locked")
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.mutex.put(value);
+        }
+    }
+}
+
+impl<'a, T> MutexGuard<'a, T> {
+    // Unlock the mutex, returning it so it can be locked again.
+    pub(crate) fn unlock(this: Self) -> &'a Mutex<T> {
+        let mutex = this.mutex;
+
+        drop(this);
+        mutex
+    }
+
+    /// Narrow the guard to a part of the value, such as a field.
+    ///
+    /// Takes separate shared and mutable accessors, since the mapped guard
+    /// can't hold a reference into the value it owns.
+    ///
+    /// ```rust
+    /// use pasts::sync::{Mutex, MutexGuard};
+    ///
+    /// let mutex = Mutex::new((1, 'a'));
+    /// let mut letter =
+    ///     MutexGuard::map(mutex.try_lock().unwrap(), |v| &v.1, |v| &mut v.1);
+    ///
+    /// *letter = 'b';
+    /// drop(letter);
+    /// assert_eq!(mutex.into_inner(), (1, 'b'));
+    /// ```
+    pub fn map<U: ?Sized>(
+        this: Self,
+        get: fn(&T) -> &U,
+        get_mut: fn(&mut T) -> &mut U,
+    ) -> MappedMutexGuard<Self, U> {
+        MappedMutexGuard {
+            guard: this,
+            get,
+            get_mut,
+        }
+    }
+}
+
+/// Guard that unlocks its [`Mutex`] when dropped, and keeps it alive.
+///
+/// Created with [`Mutex::lock_owned()`].
+pub struct OwnedMutexGuard<T> {
+    mutex: Arc<Mutex<T>>,
+    // Only `None` while dropping
+    value: Option<T>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for OwnedMutexGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> Deref for OwnedMutexGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().expect("guard is locked")
+    }
+}
+
+impl<T> DerefMut for OwnedMutexGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().expect("guard is locked")
+    }
+}
+
+impl<T> Drop for OwnedMutexGuard<T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.mutex.put(value);
+        }
+    }
+}
+
+impl<T> OwnedMutexGuard<T> {
+    // Unlock the mutex, returning it so it can be locked again.
+    pub(crate) fn unlock(this: Self) -> Arc<Mutex<T>> {
+        let mutex = this.mutex.clone();
+
+        drop(this);
+        mutex
+    }
+
+    /// Narrow the guard to a part of the value, like [`MutexGuard::map()`].
+    pub fn map<U: ?Sized>(
+        this: Self,
+        get: fn(&T) -> &U,
+        get_mut: fn(&mut T) -> &mut U,
+    ) -> MappedMutexGuard<Self, U> {
+        MappedMutexGuard {
+            guard: this,
+            get,
+            get_mut,
+        }
+    }
+
+    /// Get the [`Mutex`] that this guard locks.
+    pub fn mutex(this: &Self) -> &Arc<Mutex<T>> {
+        &this.mutex
+    }
+}
+
+/// Guard for part of a [`Mutex`]'s value, which unlocks the mutex when
+/// dropped.
+///
+/// Created with [`MutexGuard::map()`] or [`OwnedMutexGuard::map()`].
+pub struct MappedMutexGuard<G: Deref, U: ?Sized> {
+    guard: G,
+    get: fn(&G::Target) -> &U,
+    get_mut: fn(&mut G::Target) -> &mut U,
+}
+
+impl<G: Deref, U: ?Sized + fmt::Debug> fmt::Debug for MappedMutexGuard<G, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<G: Deref, U: ?Sized> Deref for MappedMutexGuard<G, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        (self.get)(&self.guard)
+    }
+}
+
+impl<G: DerefMut, U: ?Sized> DerefMut for MappedMutexGuard<G, U> {
+    fn deref_mut(&mut self) -> &mut U {
+        (self.get_mut)(&mut self.guard)
+    }
+}
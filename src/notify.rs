@@ -14,10 +14,22 @@
 //! your code at each call to `.next()`.  They also lend themselves nicely for
 //! creating clean and simple multimedia APIs.
 
-use core::fmt;
+use alloc::{collections::VecDeque, rc::Rc, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    cell::RefCell,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    task::Waker,
+};
+
+#[cfg(feature = "macros")]
+pub use pasts_macros::Notify;
 
 use crate::prelude::*;
 
+#[cfg(feature = "test_util")]
+pub mod conformance;
+
 /// An owned dynamically typed [`Notify`] for use in cases where you can’t
 /// statically type your result or need to add some indirection.
 pub type BoxNotify<'a, T = ()> = Pin<Box<dyn Notify<Event = T> + Send + 'a>>;
@@ -55,6 +67,16 @@ pub trait Notify {
     ///  - `Poll::Pending` - Not ready yet
     ///  - `Poll::Ready(val)` - Ready with next value
     fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event>;
+
+    /// Shortcut for calling [`Notify::poll_next()`] on [`Unpin`] notifys,
+    /// without pinning them first.
+    #[inline]
+    fn poll_next_unpin(&mut self, t: &mut Task<'_>) -> Poll<Self::Event>
+    where
+        Self: Unpin,
+    {
+        Pin::new(self).poll_next(t)
+    }
 }
 
 impl<N> Notify for Box<N>
@@ -65,7 +87,7 @@ where
 
     #[inline]
     fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<N::Event> {
-        Pin::new(self.get_mut().as_mut()).poll_next(t)
+        self.get_mut().as_mut().poll_next_unpin(t)
     }
 }
 
@@ -89,7 +111,7 @@ where
 
     #[inline]
     fn poll_next(mut self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<N::Event> {
-        Pin::new(&mut **self).poll_next(t)
+        (**self).poll_next_unpin(t)
     }
 }
 
@@ -102,7 +124,7 @@ where
     #[inline]
     fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
         for (i, this) in self.get_mut().iter_mut().enumerate() {
-            if let Poll::Ready(value) = Pin::new(this).poll_next(t) {
+            if let Poll::Ready(value) = this.poll_next_unpin(t) {
                 return Poll::Ready((i, value));
             }
         }
@@ -113,7 +135,7 @@ where
 
 /// An extension trait for [`Notify`]s that provides a variety of convenient
 /// adapters.
-pub trait NotifyExt: Notify + Sized + Unpin {
+pub trait NotifyExt: Notify + Sized {
     /// Get the next [`Notify::Event`]
     ///
     /// # Usage
@@ -147,7 +169,33 @@ pub trait NotifyExt: Notify + Sized + Unpin {
     /// }
     /// ```
     #[inline(always)]
-    fn next(&mut self) -> Next<'_, Self> {
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Unpin,
+    {
+        Next(Pin::new(self))
+    }
+
+    /// Get the next [`Notify::Event`] from a pinned notify, which doesn't
+    /// need to be [`Unpin`].
+    ///
+    /// # Usage
+    /// ```rust
+    /// use pasts::prelude::*;
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     // `async` blocks aren't `Unpin`
+    ///     let mut boxed = Box::pin(async { 1 }.fuse());
+    ///     let stacked = async { 2 }.fuse();
+    ///
+    ///     pasts::pin!(stacked);
+    ///
+    ///     assert_eq!(boxed.as_mut().next_pinned().await, 1);
+    ///     assert_eq!(stacked.as_mut().next_pinned().await, 2);
+    /// });
+    /// ```
+    #[inline(always)]
+    fn next_pinned(self: Pin<&mut Self>) -> Next<'_, Self> {
         Next(self)
     }
 
@@ -158,25 +206,348 @@ pub trait NotifyExt: Notify + Sized + Unpin {
 
         Map { noti, f }
     }
+
+    /// Convert produced [`Notify::Event`]s with [`Into`].
+    ///
+    /// Useful for normalizing events from several sources into one
+    /// application event type.
+    ///
+    /// ```rust
+    /// use pasts::{notify, prelude::*};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Event {
+    ///     Key(char),
+    /// }
+    ///
+    /// impl From<char> for Event {
+    ///     fn from(key: char) -> Self {
+    ///         Self::Key(key)
+    ///     }
+    /// }
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut keys = notify::ready('a').map_into::<Event>();
+    ///
+    ///     assert_eq!(keys.next().await, Event::Key('a'));
+    /// });
+    /// ```
+    #[inline(always)]
+    fn map_into<T>(self) -> Map<Self, fn(Self::Event) -> T>
+    where
+        Self::Event: Into<T>,
+    {
+        self.map(Into::into)
+    }
+
+    /// Stop after the first event that matches `predicate`, such as a
+    /// connection's "closed" event.
+    ///
+    /// The matching event is still produced, and then this notify is
+    /// dropped, so it's never polled again.  After that, this is never ready.
+    ///
+    /// ```rust
+    /// use pasts::{notify, prelude::*};
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut count = 0;
+    ///     let mut counter = notify::poll_fn(|_| {
+    ///         count += 1;
+    ///         Ready(count)
+    ///     })
+    ///     .fuse_on(|count| *count == 2);
+    ///
+    ///     assert_eq!(counter.next().await, 1);
+    ///     assert_eq!(counter.next().await, 2);
+    ///     assert!(counter.is_done());
+    /// });
+    /// ```
+    #[inline(always)]
+    fn fuse_on<P>(self, predicate: P) -> FuseOn<Self, P>
+    where
+        P: FnMut(&Self::Event) -> bool,
+    {
+        FuseOn {
+            noti: Some(self),
+            predicate,
+        }
+    }
+
+    /// Stop once `token` is cancelled, producing [`None`] as a final event.
+    ///
+    /// Other events are wrapped in [`Some`].  Cancellation is checked first,
+    /// so no more events are produced once the token is cancelled, and after
+    /// the final [`None`] this is never ready.
+    ///
+    /// ```rust
+    /// use pasts::{notify, prelude::*, CancelToken};
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let token = CancelToken::new();
+    ///     let mut ticks = notify::poll_fn(|_| Ready(())).until(token.clone());
+    ///
+    ///     assert_eq!(ticks.next().await, Some(()));
+    ///     token.cancel();
+    ///     assert_eq!(ticks.next().await, None);
+    /// });
+    /// ```
+    #[inline(always)]
+    fn until(self, token: crate::CancelToken) -> Until<Self> {
+        Until {
+            noti: Some(self),
+            token,
+        }
+    }
+
+    /// Wait for an event from both this and `other`, producing them
+    /// together.
+    ///
+    /// Events that arrive early are held until the other notify catches up,
+    /// so each pair is made of the next event from each side.
+    ///
+    /// ```rust
+    /// use pasts::{notify, prelude::*};
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut both = notify::ready(1).zip(notify::ready("one"));
+    ///
+    ///     assert_eq!(both.next().await, (1, "one"));
+    /// });
+    /// ```
+    #[inline(always)]
+    fn zip<N: Notify>(self, other: N) -> Zip<Self, N> {
+        Zip {
+            a: (self, None),
+            b: (other, None),
+        }
+    }
+
+    /// Tag each [`Notify::Event`] with the [`Instant`](crate::time::Instant)
+    /// it was received at.
+    ///
+    /// Useful for measuring latency, and ordering events from different
+    /// notifys, without changing the handlers that produce them.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use pasts::{notify, prelude::*, time::Instant};
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let start = Instant::now();
+    ///     let (received, event) = notify::ready(1).timestamped().next().await;
+    ///
+    ///     assert_eq!(event, 1);
+    ///     assert!(received >= start);
+    /// });
+    /// # }
+    /// ```
+    #[inline(always)]
+    fn timestamped(self) -> Timestamped<Self> {
+        Timestamped(self)
+    }
+
+    /// Produce [`Beat::Stalled`] whenever this notify hasn't produced an
+    /// event for `window`, so that stalled drivers can be restarted by
+    /// supervision logic in the same [`Loop`](crate::Loop).
+    ///
+    /// Events are wrapped in [`Beat::Event`].  The window starts over after
+    /// each event, and after each stall.
+    ///
+    /// ```rust
+    /// use pasts::{
+    ///     notify::{self, Beat},
+    ///     prelude::*,
+    ///     time::Duration,
+    /// };
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut driver = notify::ready(1).heartbeat(Duration::from_millis(5));
+    ///
+    ///     assert_eq!(driver.next().await, Beat::Event(1));
+    ///     assert_eq!(driver.next().await, Beat::Stalled);
+    /// });
+    /// ```
+    #[cfg(any(feature = "std", feature = "web_lite"))]
+    #[inline(always)]
+    fn heartbeat(self, window: crate::time::Duration) -> Heartbeat<Self> {
+        Heartbeat {
+            noti: self,
+            window,
+            sleep: crate::time::sleep(window),
+        }
+    }
+
+    /// Only poll this notify when `gate` is [`Ready`], to apply backpressure
+    /// to the producer of its events.
+    ///
+    /// While `gate` returns [`Pending`] (registering a wakeup for when there
+    /// is capacity again), this notify isn't polled, so it stops producing.
+    /// For example, a network reader can be paused while a decode queue is
+    /// full.
+    ///
+    /// ```rust
+    /// use std::{cell::RefCell, collections::VecDeque};
+    ///
+    /// use pasts::{notify, prelude::*};
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let queue = RefCell::new(VecDeque::new());
+    ///     let mut frames = notify::poll_fn(|_| Ready(7))
+    ///         .ready_when(|_task| {
+    ///             // A real gate would register the waker for when the queue
+    ///             // drains
+    ///             if queue.borrow().len() < 2 {
+    ///                 Ready(())
+    ///             } else {
+    ///                 Pending
+    ///             }
+    ///         });
+    ///
+    ///     for _ in 0..2 {
+    ///         let frame = frames.next().await;
+    ///
+    ///         queue.borrow_mut().push_back(frame);
+    ///     }
+    ///
+    ///     // The queue is full, so no more frames are read
+    ///     let mut polled = false;
+    ///
+    ///     core::future::poll_fn(|task| {
+    ///         polled = true;
+    ///         assert!(Pin::new(&mut frames).poll_next(task).is_pending());
+    ///         Ready(())
+    ///     })
+    ///     .await;
+    ///
+    ///     assert!(polled);
+    /// });
+    /// ```
+    #[inline(always)]
+    fn ready_when<G>(self, gate: G) -> ReadyWhen<Self, G>
+    where
+        G: FnMut(&mut Task<'_>) -> Poll<()>,
+    {
+        ReadyWhen { noti: self, gate }
+    }
+
+    /// Merge events that have the same key, for events of `(key, value)`
+    /// pairs, so that a storm of events produces one per key.
+    ///
+    /// Each poll collects the events that are ready from this notify (up to
+    /// a fixed number per poll, so one that's always ready can't block the
+    /// task), and `fold`s the value of each event into the value of an
+    /// earlier event with the same key that hasn't been produced yet.  Keys
+    /// are produced in the order they were first received.
+    ///
+    /// ```rust
+    /// use pasts::{notify, prelude::*};
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut storm = vec![("resize", 3), ("redraw", 1), ("resize", 4)];
+    ///     let mut events = notify::poll_fn(|_| match storm.pop() {
+    ///         Some(event) => Ready(event),
+    ///         None => Pending,
+    ///     })
+    ///     // Keep the latest value
+    ///     .coalesce(|old, new| *old = new);
+    ///
+    ///     assert_eq!(events.next().await, ("resize", 3));
+    ///     assert_eq!(events.next().await, ("redraw", 1));
+    /// });
+    /// ```
+    #[inline(always)]
+    fn coalesce<K, V, F>(self, fold: F) -> Coalesce<Self, K, V, F>
+    where
+        Self: Notify<Event = (K, V)>,
+        K: PartialEq,
+        F: FnMut(&mut V, V),
+    {
+        Coalesce {
+            noti: self,
+            pending: VecDeque::new(),
+            fold,
+        }
+    }
+
+    /// Keep the last `capacity` events, so that they can be replayed with
+    /// [`Replay::attach()`] before continuing with live events.
+    ///
+    /// ```rust
+    /// use pasts::{notify, prelude::*};
+    ///
+    /// pasts::Executor::default().block_on(async {
+    ///     let mut count = 0;
+    ///     let mut counter = notify::poll_fn(|_| {
+    ///         count += 1;
+    ///         Ready(count)
+    ///     })
+    ///     .replay(2);
+    ///
+    ///     for _ in 0..3 {
+    ///         counter.next().await;
+    ///     }
+    ///
+    ///     // A late consumer sees the last two events first
+    ///     counter.attach();
+    ///
+    ///     assert_eq!(counter.next().await, 2);
+    ///     assert_eq!(counter.next().await, 3);
+    ///     assert_eq!(counter.next().await, 4);
+    /// });
+    /// ```
+    #[inline(always)]
+    fn replay(self, capacity: usize) -> Replay<Self>
+    where
+        Self::Event: Clone,
+    {
+        Replay {
+            noti: self,
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+            replaying: 0,
+        }
+    }
 }
 
-impl<N: Notify + Sized + Unpin> NotifyExt for N {}
+impl<N: Notify + Sized> NotifyExt for N {}
 
-/// The [`Future`] returned from [`NotifyExt::next()`]
+/// The [`Future`] returned from [`NotifyExt::next()`] and
+/// [`NotifyExt::next_pinned()`]
 #[derive(Debug)]
-pub struct Next<'a, N>(&'a mut N)
+pub struct Next<'a, N>(Pin<&'a mut N>)
 where
-    N: Notify + Unpin;
+    N: Notify;
 
 impl<N> Future for Next<'_, N>
 where
-    N: Notify + Unpin,
+    N: Notify,
+{
+    type Output = N::Event;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        self.get_mut().0.as_mut().poll_next(t)
+    }
+}
+
+/// The [`Future`] returned from the function created by [`next_fn()`]
+pub struct NextFn<'a, N: ?Sized>(Rc<RefCell<&'a mut N>>);
+
+impl<N: ?Sized> fmt::Debug for NextFn<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NextFn")
+    }
+}
+
+impl<N> Future for NextFn<'_, N>
+where
+    N: Notify + Unpin + ?Sized,
 {
     type Output = N::Event;
 
     #[inline]
     fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
-        Pin::new(&mut self.get_mut().0).poll_next(t)
+        self.0.borrow_mut().poll_next_unpin(t)
     }
 }
 
@@ -225,139 +596,1436 @@ where
 
     #[inline]
     fn poll_next(mut self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<E> {
-        Pin::new(&mut self.noti).poll_next(t).map(&mut self.f)
+        self.noti.poll_next_unpin(t).map(&mut self.f)
     }
 }
 
-/// A [`Notify`] that wraps a function returning a [`Future`]
-///
-/// This struct is created by [`future_fn()`].  See its documentation for more.
+/// The [`Notify`] returned from [`NotifyExt::fuse_on()`]
 #[derive(Debug)]
-pub struct FutureFn<T, F>(T, F);
+pub struct FuseOn<N, P> {
+    noti: Option<N>,
+    predicate: P,
+}
 
-impl<T, F> Notify for FutureFn<T, F>
+impl<N, P> FuseOn<N, P> {
+    /// Check if an event has matched the predicate, so this will never be
+    /// ready again.
+    pub fn is_done(&self) -> bool {
+        self.noti.is_none()
+    }
+}
+
+impl<N, P> Notify for FuseOn<N, P>
 where
-    T: Future + Unpin,
-    F: FnMut() -> T + Unpin,
+    N: Notify + Unpin,
+    P: FnMut(&N::Event) -> bool + Unpin,
 {
-    type Event = T::Output;
+    type Event = N::Event;
 
     #[inline]
-    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T::Output> {
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<N::Event> {
         let this = self.get_mut();
-        let poll = Pin::new(&mut this.0).poll(t);
+        let Some(ref mut noti) = this.noti else {
+            return Poll::Pending;
+        };
+        let event = noti.poll_next_unpin(t);
 
-        if poll.is_ready() {
-            Pin::new(&mut this.0).set(this.1());
+        if let Poll::Ready(ref event) = event {
+            if (this.predicate)(event) {
+                this.noti = None;
+            }
         }
 
-        poll
+        event
     }
 }
 
-/// A [`Notify`] created from a function returning [`Poll`]
+/// The [`Notify`] returned from [`NotifyExt::until()`]
 #[derive(Debug)]
-pub struct PollFn<F>(F);
+pub struct Until<N> {
+    noti: Option<N>,
+    token: crate::CancelToken,
+}
 
-impl<T, F> Notify for PollFn<F>
+impl<N> Notify for Until<N>
 where
-    F: FnMut(&mut Task<'_>) -> Poll<T> + Unpin,
+    N: Notify + Unpin,
 {
-    type Event = T;
+    type Event = Option<N::Event>;
 
     #[inline]
-    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
-        self.get_mut().0(t)
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        let this = self.get_mut();
+        let Some(ref mut noti) = this.noti else {
+            return Poll::Pending;
+        };
+
+        if this.token.poll(t).is_ready() {
+            this.noti = None;
+            return Poll::Ready(None);
+        }
+
+        noti.poll_next_unpin(t).map(Some)
     }
 }
 
-/// A [`Notify`] that never produces an event.
-///
-/// This struct is created by [`pending()`].  See its documentation for more.
+/// The [`Notify`] returned from [`NotifyExt::timestamped()`]
 #[derive(Debug)]
-pub struct Pending<T>(core::marker::PhantomData<fn() -> T>);
+pub struct Timestamped<N>(N);
 
-impl<T> Notify for Pending<T> {
-    type Event = T;
+impl<N> Notify for Timestamped<N>
+where
+    N: Notify + Unpin,
+{
+    type Event = (crate::time::Instant, N::Event);
 
-    fn poll_next(self: Pin<&mut Self>, _task: &mut Task<'_>) -> Poll<T> {
-        Poll::Pending
+    #[inline]
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        t: &mut Task<'_>,
+    ) -> Poll<Self::Event> {
+        self.0
+            .poll_next_unpin(t)
+            .map(|event| (crate::time::Instant::now(), event))
     }
 }
 
-/// A [`Notify`] that immediately produces a single event.
-///
-/// This struct is created by [`ready()`].  See its documentation for more.
+/// Event produced by [`Heartbeat`].
+#[cfg(any(feature = "std", feature = "web_lite"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Beat<E> {
+    /// Event from the inner notify
+    Event(E),
+    /// The inner notify hasn't produced an event within the window
+    Stalled,
+}
+
+/// The [`Notify`] returned from [`NotifyExt::heartbeat()`]
+#[cfg(any(feature = "std", feature = "web_lite"))]
 #[derive(Debug)]
-pub struct Ready<T: Unpin>(Option<T>);
+pub struct Heartbeat<N> {
+    noti: N,
+    window: crate::time::Duration,
+    sleep: crate::time::Sleep,
+}
 
-impl<T: Unpin> Notify for Ready<T> {
-    type Event = T;
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<N> Notify for Heartbeat<N>
+where
+    N: Notify + Unpin,
+{
+    type Event = Beat<N::Event>;
 
-    fn poll_next(self: Pin<&mut Self>, _task: &mut Task<'_>) -> Poll<T> {
-        let Some(event) = self.get_mut().0.take() else {
-            return Poll::Pending;
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        let this = self.get_mut();
+        let beat = match this.noti.poll_next_unpin(t) {
+            Poll::Ready(event) => Beat::Event(event),
+            Poll::Pending => {
+                if Pin::new(&mut this.sleep).poll(t).is_pending() {
+                    return Poll::Pending;
+                }
+
+                Beat::Stalled
+            }
         };
 
-        Poll::Ready(event)
+        this.sleep.reset(crate::time::Instant::now() + this.window);
+        Poll::Ready(beat)
     }
 }
 
-/// A [`Notify`] that selects over a list of [`Notify`]s
-///
-/// This struct is created by [`select()`].  See its documentation for more.
-pub struct Select<'a, E, const N: usize>(
-    [&'a mut (dyn Notify<Event = E> + Unpin); N],
-    usize,
-);
-
-impl<E, const N: usize> fmt::Debug for Select<'_, E, N> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("Select")
-    }
+/// The [`Notify`] returned from [`NotifyExt::ready_when()`]
+#[derive(Debug)]
+pub struct ReadyWhen<N, G> {
+    noti: N,
+    gate: G,
 }
 
-impl<E: Unpin, const N: usize> Notify for Select<'_, E, N> {
-    type Event = E;
-
-    fn poll_next(self: Pin<&mut Self>, task: &mut Task<'_>) -> Poll<E> {
-        let s = self.get_mut();
-        let start = s.1;
+impl<N, G> Notify for ReadyWhen<N, G>
+where
+    N: Notify + Unpin,
+    G: FnMut(&mut Task<'_>) -> Poll<()> + Unpin,
+{
+    type Event = N::Event;
 
-        // Early exit for if there is nothing that can be ready.
-        if N == 0 {
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<N::Event> {
+        if (self.gate)(t).is_pending() {
             return Poll::Pending;
         }
 
-        for i in (start..N).chain(0..start) {
-            if let Poll::Ready(event) = Pin::new(&mut s.0[i]).poll_next(task) {
-                return Poll::Ready(event);
-            }
-        }
-
-        Poll::Pending
+        self.noti.poll_next_unpin(t)
     }
 }
 
-/// Create a [`Notify`] that wraps a function returning a [`Future`].
-///
-/// Polling the notify delegates to future returned by the wrapped function.
-/// The wrapped function is called immediately, and is only called again once
-/// the future is polled and returns `Ready`.
-pub fn future_fn<T, F>(mut f: F) -> FutureFn<T, F>
-where
-    T: Future + Unpin,
-    F: FnMut() -> T + Unpin,
-{
-    FutureFn(f(), f)
+/// The [`Notify`] returned from [`NotifyExt::coalesce()`]
+#[derive(Debug)]
+pub struct Coalesce<N, K, V, F> {
+    noti: N,
+    pending: VecDeque<(K, V)>,
+    fold: F,
 }
 
-/// Create a [`Notify`] that wraps a function returning [`Poll`].
-///
-/// Polling the future delegates to the wrapped function.
-pub fn poll_fn<T, F>(f: F) -> PollFn<F>
+impl<N: Unpin, K, V, F: Unpin> Unpin for Coalesce<N, K, V, F> {}
+
+// Events collected per poll, so a notify that's always ready can't block
+const COALESCE_EVENTS: usize = 64;
+
+impl<N, K, V, F> Notify for Coalesce<N, K, V, F>
 where
-    F: FnMut(&mut Task<'_>) -> Poll<T> + Unpin,
+    N: Notify<Event = (K, V)> + Unpin,
+    K: PartialEq,
+    F: FnMut(&mut V, V) + Unpin,
+{
+    type Event = (K, V);
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<(K, V)> {
+        let this = self.get_mut();
+
+        for _ in 0..COALESCE_EVENTS {
+            let Poll::Ready((key, value)) = this.noti.poll_next_unpin(t) else {
+                break;
+            };
+            let earlier = this.pending.iter_mut().find(|(k, _)| *k == key);
+
+            match earlier {
+                Some((_, old)) => (this.fold)(old, value),
+                None => this.pending.push_back((key, value)),
+            }
+        }
+
+        this.pending.pop_front().map_or(Poll::Pending, Poll::Ready)
+    }
+}
+
+/// The [`Notify`] returned from [`NotifyExt::replay()`]
+#[derive(Debug)]
+pub struct Replay<N: Notify> {
+    noti: N,
+    history: VecDeque<N::Event>,
+    capacity: usize,
+    replaying: usize,
+}
+
+impl<N: Notify + Unpin> Unpin for Replay<N> {}
+
+impl<N: Notify> Replay<N> {
+    /// Start over from the oldest kept event, for a newly attached consumer.
+    ///
+    /// The kept events are produced immediately, before any live ones.
+    pub fn attach(&mut self) {
+        self.replaying = self.history.len();
+    }
+
+    /// Get the kept events, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &N::Event> {
+        self.history.iter()
+    }
+}
+
+impl<N> Notify for Replay<N>
+where
+    N: Notify + Unpin,
+    N::Event: Clone,
+{
+    type Event = N::Event;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<N::Event> {
+        let this = self.get_mut();
+
+        if this.replaying != 0 {
+            let index = this.history.len() - this.replaying;
+
+            this.replaying -= 1;
+            return Poll::Ready(this.history[index].clone());
+        }
+
+        let event = this.noti.poll_next_unpin(t);
+
+        if let Poll::Ready(ref event) = event {
+            if this.history.len() == this.capacity {
+                this.history.pop_front();
+            }
+
+            if this.capacity != 0 {
+                this.history.push_back(event.clone());
+            }
+        }
+
+        event
+    }
+}
+
+/// The [`Notify`] returned from [`NotifyExt::zip()`]
+#[derive(Debug)]
+pub struct Zip<A: Notify, B: Notify> {
+    a: (A, Option<A::Event>),
+    b: (B, Option<B::Event>),
+}
+
+// Pending events are never pinned
+impl<A: Notify + Unpin, B: Notify + Unpin> Unpin for Zip<A, B> {}
+
+impl<A, B> Notify for Zip<A, B>
+where
+    A: Notify + Unpin,
+    B: Notify + Unpin,
+{
+    type Event = (A::Event, B::Event);
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        let Self { a, b } = self.get_mut();
+
+        if a.1.is_none() {
+            if let Poll::Ready(event) = a.0.poll_next_unpin(t) {
+                a.1 = Some(event);
+            }
+        }
+
+        if b.1.is_none() {
+            if let Poll::Ready(event) = b.0.poll_next_unpin(t) {
+                b.1 = Some(event);
+            }
+        }
+
+        match (a.1.take(), b.1.take()) {
+            (Some(a), Some(b)) => Poll::Ready((a, b)),
+            (early_a, early_b) => {
+                (a.1, b.1) = (early_a, early_b);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A [`Future`] that waits for an event from every [`Notify`] in a list
+///
+/// This struct is created by [`all()`].  See its documentation for more.
+#[derive(Debug)]
+pub struct All<'a, N: Notify>(&'a mut [N], Vec<Option<N::Event>>);
+
+impl<N: Notify> Unpin for All<'_, N> {}
+
+impl<N: Notify + Unpin> Future for All<'_, N> {
+    type Output = Vec<N::Event>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let Self(notifys, events) = self.get_mut();
+        let mut done = true;
+
+        for (notify, event) in notifys.iter_mut().zip(events.iter_mut()) {
+            if event.is_none() {
+                if let Poll::Ready(next) = notify.poll_next_unpin(t) {
+                    *event = Some(next);
+                }
+            }
+
+            done &= event.is_some();
+        }
+
+        if !done {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(events.drain(..).flatten().collect())
+    }
+}
+
+/// A [`Notify`] that wraps a function returning a [`Future`]
+///
+/// This struct is created by [`future_fn()`].  See its documentation for more.
+#[derive(Debug)]
+pub struct FutureFn<T, F>(T, F);
+
+impl<T, F> Notify for FutureFn<T, F>
+where
+    T: Future + Unpin,
+    F: FnMut() -> T + Unpin,
+{
+    type Event = T::Output;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T::Output> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.0).poll(t);
+
+        if poll.is_ready() {
+            Pin::new(&mut this.0).set(this.1());
+        }
+
+        poll
+    }
+}
+
+/// A [`Notify`] created from a function returning [`Poll`]
+#[derive(Debug)]
+pub struct PollFn<F>(F);
+
+impl<T, F> Notify for PollFn<F>
+where
+    F: FnMut(&mut Task<'_>) -> Poll<T> + Unpin,
+{
+    type Event = T;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
+        self.get_mut().0(t)
+    }
+}
+
+/// A [`Notify`] that never produces an event.
+///
+/// This struct is created by [`pending()`].  See its documentation for more.
+#[derive(Debug)]
+pub struct Pending<T>(core::marker::PhantomData<fn() -> T>);
+
+impl<T> Notify for Pending<T> {
+    type Event = T;
+
+    fn poll_next(self: Pin<&mut Self>, _task: &mut Task<'_>) -> Poll<T> {
+        Poll::Pending
+    }
+}
+
+/// A [`Notify`] and [`Future`] that never produces anything.
+///
+/// This struct is created by [`never()`].  See its documentation for more.
+pub struct Never<T = core::convert::Infallible>(
+    core::marker::PhantomData<fn() -> T>,
+);
+
+impl<T> fmt::Debug for Never<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Never<{}>", core::any::type_name::<T>())
+    }
+}
+
+impl<T> Clone for Never<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Never<T> {}
+
+impl<T> Default for Never<T> {
+    fn default() -> Self {
+        never()
+    }
+}
+
+impl<T> Notify for Never<T> {
+    type Event = T;
+
+    fn poll_next(self: Pin<&mut Self>, _task: &mut Task<'_>) -> Poll<T> {
+        Poll::Pending
+    }
+}
+
+impl<T> Future for Never<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, _task: &mut Task<'_>) -> Poll<T> {
+        Poll::Pending
+    }
+}
+
+/// A [`Notify`] that immediately produces a single event.
+///
+/// This struct is created by [`ready()`].  See its documentation for more.
+#[derive(Debug)]
+pub struct Ready<T: Unpin>(Option<T>);
+
+impl<T: Unpin> Notify for Ready<T> {
+    type Event = T;
+
+    fn poll_next(self: Pin<&mut Self>, _task: &mut Task<'_>) -> Poll<T> {
+        let Some(event) = self.get_mut().0.take() else {
+            return Poll::Pending;
+        };
+
+        Poll::Ready(event)
+    }
+}
+
+/// A [`Notify`] that merges two [`Notify`]s, preferring the first
+///
+/// This struct is created by [`merge_prefer()`].  See its documentation for
+/// more.
+#[derive(Debug)]
+pub struct MergePrefer<A, B>(A, B);
+
+impl<A, B> Notify for MergePrefer<A, B>
+where
+    A: Notify + Unpin,
+    B: Notify<Event = A::Event> + Unpin,
+{
+    type Event = A::Event;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<A::Event> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(event) = this.0.poll_next_unpin(t) {
+            return Poll::Ready(event);
+        }
+
+        this.1.poll_next_unpin(t)
+    }
+}
+
+/// A [`Notify`] that selects over a list of [`Notify`]s
+///
+/// This struct is created by [`select()`].  See its documentation for more.
+pub struct Select<'a, E, const N: usize>(
+    [&'a mut (dyn Notify<Event = E> + Unpin); N],
+    usize,
+);
+
+impl<E, const N: usize> fmt::Debug for Select<'_, E, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Select")
+    }
+}
+
+impl<E, const N: usize> Select<'_, E, N> {
+    /// Start polling at a random [`Notify`], rather than the first.
+    #[cfg(feature = "std")]
+    pub fn random_start(mut self) -> Self {
+        self.1 = random() % N.max(1);
+        self
+    }
+}
+
+impl<E: Unpin, const N: usize> Notify for Select<'_, E, N> {
+    type Event = E;
+
+    fn poll_next(self: Pin<&mut Self>, task: &mut Task<'_>) -> Poll<E> {
+        let s = self.get_mut();
+
+        rotate(&mut s.0, &mut s.1, task)
+    }
+}
+
+/// A [`Notify`] that selects over a runtime-sized list of [`Notify`]s
+///
+/// This struct is created by [`select_dyn()`].  See its documentation for
+/// more.
+pub struct SelectDyn<'a, E>(
+    Vec<&'a mut (dyn Notify<Event = E> + Unpin)>,
+    usize,
+);
+
+impl<E> fmt::Debug for SelectDyn<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SelectDyn").field(&self.0.len()).finish()
+    }
+}
+
+impl<'a, E> SelectDyn<'a, E> {
+    /// Start polling at a random [`Notify`], rather than the first.
+    #[cfg(feature = "std")]
+    pub fn random_start(mut self) -> Self {
+        self.1 = random() % self.0.len().max(1);
+        self
+    }
+
+    /// Add a [`Notify`] to select over.
+    pub fn push(&mut self, notify: &'a mut (dyn Notify<Event = E> + Unpin)) {
+        self.0.push(notify);
+    }
+
+    /// Stop selecting over the [`Notify`] at `index`, returning it.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn remove(
+        &mut self,
+        index: usize,
+    ) -> &'a mut (dyn Notify<Event = E> + Unpin) {
+        let notify = self.0.remove(index);
+
+        if index < self.1 {
+            self.1 -= 1;
+        }
+
+        notify
+    }
+
+    /// Get the number of [`Notify`]s being selected over.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no [`Notify`]s to select over.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<E: Unpin> Notify for SelectDyn<'_, E> {
+    type Event = E;
+
+    fn poll_next(self: Pin<&mut Self>, task: &mut Task<'_>) -> Poll<E> {
+        let s = self.get_mut();
+
+        rotate(&mut s.0, &mut s.1, task)
+    }
+}
+
+/// A [`Notify`] that merges an owned, runtime-sized list of boxed
+/// [`Notify`]s
+///
+/// This struct is created by [`merge_all()`].  See its documentation for
+/// more.
+pub struct MergeAll<'a, T>(Vec<BoxNotify<'a, T>>, usize);
+
+impl<T> fmt::Debug for MergeAll<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MergeAll").field(&self.0.len()).finish()
+    }
+}
+
+impl<'a, T> MergeAll<'a, T> {
+    /// Add a [`Notify`] to merge.
+    pub fn push(&mut self, notify: BoxNotify<'a, T>) {
+        self.0.push(notify);
+    }
+
+    /// Stop merging the [`Notify`] at `index`, returning it.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> BoxNotify<'a, T> {
+        let notify = self.0.remove(index);
+
+        if index < self.1 {
+            self.1 -= 1;
+        }
+
+        notify
+    }
+
+    /// Get the number of [`Notify`]s being merged.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no [`Notify`]s to merge.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<T> Notify for MergeAll<'_, T> {
+    type Event = T;
+
+    fn poll_next(self: Pin<&mut Self>, task: &mut Task<'_>) -> Poll<T> {
+        let s = self.get_mut();
+
+        rotate(&mut s.0, &mut s.1, task)
+    }
+}
+
+/// A [`Notify`] that shares turns between a runtime-sized list of
+/// [`Notify`]s by weight
+///
+/// This struct is created by [`weighted_select()`].  See its documentation
+/// for more.
+pub struct WeightedSelect<'a, E> {
+    notifys: Vec<(&'a mut (dyn Notify<Event = E> + Unpin), u32)>,
+    current: usize,
+    credit: u32,
+}
+
+impl<E> fmt::Debug for WeightedSelect<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.notifys.iter().map(|(_, weight)| weight))
+            .finish()
+    }
+}
+
+impl<'a, E> WeightedSelect<'a, E> {
+    /// Add a [`Notify`] to select over, with a `weight`.
+    pub fn push(
+        &mut self,
+        notify: &'a mut (dyn Notify<Event = E> + Unpin),
+        weight: u32,
+    ) {
+        if self.notifys.is_empty() {
+            self.current = 0;
+            self.credit = weight;
+        }
+
+        self.notifys.push((notify, weight));
+    }
+
+    /// Stop selecting over the [`Notify`] at `index`, returning it.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn remove(
+        &mut self,
+        index: usize,
+    ) -> &'a mut (dyn Notify<Event = E> + Unpin) {
+        let (notify, _) = self.notifys.remove(index);
+
+        if index == self.current {
+            // The next notify takes over the turn, wrapping to the first
+            if self.current >= self.notifys.len() {
+                self.current = 0;
+            }
+
+            self.credit = self.notifys.get(self.current).map_or(0, |n| n.1);
+        } else if index < self.current {
+            self.current -= 1;
+        }
+
+        notify
+    }
+
+    /// Get the weight of the [`Notify`] at `index`.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn weight(&self, index: usize) -> u32 {
+        self.notifys[index].1
+    }
+
+    /// Change the weight of the [`Notify`] at `index`, starting from its
+    /// next turn.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn set_weight(&mut self, index: usize, weight: u32) {
+        self.notifys[index].1 = weight;
+    }
+
+    /// Get the number of [`Notify`]s being selected over.
+    pub fn len(&self) -> usize {
+        self.notifys.len()
+    }
+
+    /// Check if there are no [`Notify`]s to select over.
+    pub fn is_empty(&self) -> bool {
+        self.notifys.is_empty()
+    }
+}
+
+impl<E: Unpin> Notify for WeightedSelect<'_, E> {
+    type Event = E;
+
+    fn poll_next(self: Pin<&mut Self>, task: &mut Task<'_>) -> Poll<E> {
+        let s = self.get_mut();
+        let len = s.notifys.len();
+
+        if len == 0 {
+            return Poll::Pending;
+        }
+
+        // Visit each notify once, plus the current one again if its turn
+        // started before this poll
+        for _ in 0..=len {
+            if s.credit > 0 {
+                let (ref mut notify, _) = s.notifys[s.current];
+
+                if let Poll::Ready(event) = notify.poll_next_unpin(task) {
+                    s.credit -= 1;
+                    return Poll::Ready(event);
+                }
+            }
+
+            // Give the next notify its turn, with credit for its weight
+            s.current = (s.current + 1) % len;
+            s.credit = s.notifys[s.current].1;
+        }
+
+        Poll::Pending
+    }
+}
+
+/// A [`Notify`] over a list of fallible [`Notify`]s, which collects their
+/// errors
+///
+/// This struct is created by [`results()`].  See its documentation for more.
+#[derive(Debug)]
+pub struct Results<N, E> {
+    notifys: Vec<N>,
+    failed: Vec<bool>,
+    errors: Vec<(usize, E)>,
+}
+
+impl<N, E> Results<N, E> {
+    /// Get the errors that haven't been taken yet, with the index of the
+    /// [`Notify`] that produced each.
+    pub fn errors(&self) -> &[(usize, E)] {
+        &self.errors
+    }
+
+    /// Take the errors collected so far.
+    ///
+    /// The [`Notify`]s that failed still won't be polled again.
+    pub fn take_errors(&mut self) -> Vec<(usize, E)> {
+        core::mem::take(&mut self.errors)
+    }
+
+    /// Get the number of [`Notify`]s that haven't failed.
+    pub fn active(&self) -> usize {
+        self.failed.iter().filter(|failed| !**failed).count()
+    }
+}
+
+impl<N: Unpin, E> Unpin for Results<N, E> {}
+
+impl<N, T, E> Notify for Results<N, E>
+where
+    N: Notify<Event = Result<T, E>> + Unpin,
+{
+    type Event = (usize, T);
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<(usize, T)> {
+        let this = self.get_mut();
+        let notifys = this.notifys.iter_mut().zip(this.failed.iter_mut());
+
+        for (i, (noti, failed)) in notifys.enumerate() {
+            if *failed {
+                continue;
+            }
+
+            match noti.poll_next_unpin(t) {
+                Poll::Ready(Ok(value)) => return Poll::Ready((i, value)),
+                Poll::Ready(Err(error)) => {
+                    *failed = true;
+                    this.errors.push((i, error));
+                }
+                Poll::Pending => {}
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+// Poll each notify once beginning at `start`, then move `start` past the one
+// that was ready, so that others get the first turn next time.
+fn rotate<E>(
+    notifys: &mut [impl Notify<Event = E> + Unpin],
+    start: &mut usize,
+    task: &mut Task<'_>,
+) -> Poll<E> {
+    let len = notifys.len();
+    let first = (*start).min(len);
+
+    for i in (first..len).chain(0..first) {
+        if let Poll::Ready(event) = notifys[i].poll_next_unpin(task) {
+            *start = (i + 1) % len;
+            return Poll::Ready(event);
+        }
+    }
+
+    Poll::Pending
+}
+
+// Waker for one entry of a `NotifyUnordered`, which marks the entry to be
+// polled again before waking the task that polls the set.
+struct EntryWaker {
+    woken: AtomicBool,
+    parent: Waker,
+}
+
+impl Wake for EntryWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+        self.parent.wake_by_ref();
+    }
+}
+
+struct Entry<'a, T> {
+    notify: LocalBoxNotify<'a, T>,
+    flag: Arc<EntryWaker>,
+    waker: Waker,
+    // Remove the entry after its first event (for futures).
+    once: bool,
+}
+
+impl<T> Entry<'_, T> {
+    // Give the entry a new waker for `parent`, marked as woken.
+    fn rewake(&mut self, parent: &Waker) {
+        self.flag = Arc::new(EntryWaker {
+            woken: AtomicBool::new(true),
+            parent: parent.clone(),
+        });
+        self.waker = Waker::from(self.flag.clone());
+    }
+}
+
+/// A growable set of [`Notify`]s, which only polls the ones that have been
+/// woken
+///
+/// Each entry gets its own waker, so unlike the [`Notify`] implementation for
+/// slices, entries that haven't been woken aren't polled again.  Events are
+/// produced along with the key returned when the entry was pushed.  Keys of
+/// removed entries are reused.
+///
+/// ```rust
+/// use pasts::{notify::{self, NotifyUnordered}, prelude::*};
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut set = NotifyUnordered::new();
+///     let ticks = set.push(notify::ready(()).map(|()| "tick"));
+///     let once = set.push_future(async { "once" });
+///
+///     assert_eq!(set.next().await, (ticks, "tick"));
+///     assert_eq!(set.next().await, (once, "once"));
+///     assert_eq!(set.len(), 1);
+/// });
+/// ```
+pub struct NotifyUnordered<'a, T> {
+    entries: Vec<Option<Entry<'a, T>>>,
+    free: Vec<usize>,
+    len: usize,
+    // Where to start looking for woken entries, for fairness.
+    next: usize,
+    parent: Option<Waker>,
+}
+
+impl<T> fmt::Debug for NotifyUnordered<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotifyUnordered")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<T> Default for NotifyUnordered<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> NotifyUnordered<'a, T> {
+    /// Create an empty set.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            next: 0,
+            parent: None,
+        }
+    }
+
+    /// Add a [`Notify`] to the set, returning its key.
+    pub fn push(&mut self, notify: impl Notify<Event = T> + 'a) -> usize {
+        self.insert(Box::pin(notify), false)
+    }
+
+    /// Add a [`Future`] to the set, returning its key.
+    ///
+    /// The future is removed from the set once it completes.
+    pub fn push_future(
+        &mut self,
+        future: impl Future<Output = T> + 'a,
+    ) -> usize {
+        self.insert(Box::pin(future.fuse()), true)
+    }
+
+    fn insert(&mut self, notify: LocalBoxNotify<'a, T>, once: bool) -> usize {
+        let parent = self.parent.as_ref().unwrap_or(Waker::noop());
+        let flag = Arc::new(EntryWaker {
+            woken: AtomicBool::new(true),
+            parent: parent.clone(),
+        });
+        let entry = Entry {
+            notify,
+            waker: Waker::from(flag.clone()),
+            flag,
+            once,
+        };
+        let key = match self.free.pop() {
+            Some(key) => {
+                self.entries[key] = Some(entry);
+                key
+            }
+            None => {
+                self.entries.push(Some(entry));
+                self.entries.len() - 1
+            }
+        };
+
+        self.len += 1;
+        // Let the task polling the set know that there's a new entry
+        parent.wake_by_ref();
+        key
+    }
+
+    /// Remove the entry for `key` from the set, returning true if it was
+    /// in the set.
+    pub fn remove(&mut self, key: usize) -> bool {
+        let Some(entry) = self.entries.get_mut(key) else {
+            return false;
+        };
+
+        if entry.take().is_none() {
+            return false;
+        }
+
+        self.free.push(key);
+        self.len -= 1;
+        true
+    }
+
+    /// Check if the entry for `key` is in the set.
+    pub fn contains(&self, key: usize) -> bool {
+        self.entries.get(key).is_some_and(Option::is_some)
+    }
+
+    /// Get the number of entries in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T> Notify for NotifyUnordered<'_, T> {
+    type Event = (usize, T);
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        let this = self.get_mut();
+
+        // Entry wakers wake the task they were made for, so replace them if
+        // the set moved to a different task, polling every entry again
+        if !this.parent.as_ref().is_some_and(|p| p.will_wake(t.waker())) {
+            for entry in this.entries.iter_mut().flatten() {
+                entry.rewake(t.waker());
+            }
+
+            this.parent = Some(t.waker().clone());
+        }
+
+        let count = this.entries.len();
+
+        for offset in 0..count {
+            let key = (this.next + offset) % count;
+            let Some(ref mut entry) = this.entries[key] else {
+                continue;
+            };
+
+            if !entry.flag.woken.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+
+            let mut task = Task::from_waker(&entry.waker);
+
+            if let Poll::Ready(event) =
+                entry.notify.as_mut().poll_next(&mut task)
+            {
+                // There may be more events, so poll it again next time
+                entry.flag.woken.store(true, Ordering::SeqCst);
+                this.next = key + 1;
+
+                if entry.once {
+                    this.remove(key);
+                }
+
+                return Poll::Ready((key, event));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+// Get a random number, for picking where to start selecting.
+#[cfg(feature = "std")]
+fn random() -> usize {
+    use std::hash::{BuildHasher, RandomState};
+
+    RandomState::new().hash_one(()) as usize
+}
+
+/// A [`Notify`] that wraps a [`Stream`](futures_core::Stream)
+///
+/// This struct is created by [`stream()`].  See its documentation for more.
+#[cfg(feature = "stream")]
+#[derive(Debug)]
+pub struct StreamNotify<S>(Option<S>);
+
+#[cfg(feature = "stream")]
+impl<S> Notify for StreamNotify<S>
+where
+    S: futures_core::Stream + Unpin,
+{
+    type Event = Option<S::Item>;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Event> {
+        let this = self.get_mut();
+        let Some(stream) = &mut this.0 else {
+            return Poll::Pending;
+        };
+        let poll = Pin::new(stream).poll_next(t);
+
+        // Don't poll the stream again once it's finished
+        if let Poll::Ready(None) = poll {
+            this.0 = None;
+        }
+
+        poll
+    }
+}
+
+/// Create a [`Notify`] that selects over a runtime-sized list of
+/// [`Notify`]s.
+///
+/// Unlike [`select()`], notifys can be added and removed while selecting.
+/// Polling starts after the last notify that was ready, so that no notify can
+/// starve the others.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut clients = [notify::ready(1), notify::ready(2)];
+///     let mut late = notify::ready(3);
+///     let mut select = notify::select_dyn(
+///         clients
+///             .iter_mut()
+///             .map(|c| c as &mut (dyn Notify<Event = i32> + Unpin)),
+///     );
+///
+///     select.push(&mut late);
+///
+///     let mut sum = 0;
+///
+///     for _ in 0..3 {
+///         sum += select.next().await;
+///     }
+///
+///     assert_eq!(sum, 6);
+/// });
+/// ```
+pub fn select_dyn<'a, E>(
+    notifys: impl IntoIterator<Item = &'a mut (dyn Notify<Event = E> + Unpin)>,
+) -> SelectDyn<'a, E> {
+    SelectDyn(notifys.into_iter().collect(), 0)
+}
+
+/// Create a [`Notify`] that merges an owned list of boxed [`Notify`]s.
+///
+/// Like [`select_dyn()`], polling starts after the last notify that was
+/// ready, and notifys can be added and removed while merging.  Since the
+/// notifys are owned, the merge can be stored without borrowing them from
+/// somewhere else.  Use [`NotifyUnordered`] instead for large numbers of
+/// notifys, where polling every one of them on each wake would be slow.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut merged = notify::merge_all(vec![
+///         Box::pin(notify::ready(1)) as BoxNotify<'_, i32>,
+///         Box::pin(notify::ready(2)),
+///     ]);
+///
+///     merged.push(Box::pin(notify::ready(3)));
+///
+///     let mut sum = 0;
+///
+///     for _ in 0..3 {
+///         sum += merged.next().await;
+///     }
+///
+///     assert_eq!(sum, 6);
+/// });
+/// ```
+pub fn merge_all<T>(notifys: Vec<BoxNotify<'_, T>>) -> MergeAll<'_, T> {
+    MergeAll(notifys, 0)
+}
+
+/// Create a [`Notify`] that shares turns between a runtime-sized list of
+/// weighted [`Notify`]s, with deficit round-robin.
+///
+/// Each notify takes turns in order, and may produce up to its weight in
+/// events before the next one gets a turn.  A notify that has nothing ready
+/// loses the rest of its turn, so idle notifys don't build up credit.
+/// Notifys with a weight of zero are never polled.  Weights can be changed
+/// with [`WeightedSelect::set_weight()`], for example to give a client more
+/// bandwidth.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut premium = notify::poll_fn(|_| Ready("premium"));
+///     let mut basic = notify::poll_fn(|_| Ready("basic"));
+///     let mut select =
+///         notify::weighted_select([(&mut premium as _, 2), (&mut basic as _, 1)]);
+///     let mut events = Vec::new();
+///
+///     for _ in 0..6 {
+///         events.push(select.next().await);
+///     }
+///
+///     assert_eq!(events.iter().filter(|e| **e == "premium").count(), 4);
+/// });
+/// ```
+pub fn weighted_select<'a, E>(
+    notifys: impl IntoIterator<
+        Item = (&'a mut (dyn Notify<Event = E> + Unpin), u32),
+    >,
+) -> WeightedSelect<'a, E> {
+    let mut select = WeightedSelect {
+        notifys: Vec::new(),
+        current: 0,
+        credit: 0,
+    };
+
+    for (notify, weight) in notifys {
+        select.push(notify, weight);
+    }
+
+    select
+}
+
+/// Create a [`Notify`] over a list of [`Notify`]s that produce [`Result`]s,
+/// which produces each success along with the index of its notify.
+///
+/// Errors are collected into [`Results::errors()`] instead of being produced,
+/// and the notify that failed isn't polled again, so that the rest can carry
+/// on.  Once every notify has failed, this is never ready.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut connections = notify::results([
+///         notify::ready(Err("refused")),
+///         notify::ready(Ok(42)),
+///     ]);
+///
+///     assert_eq!(connections.next().await, (1, 42));
+///     assert_eq!(connections.errors(), [(0, "refused")]);
+///     assert_eq!(connections.active(), 1);
+/// });
+/// ```
+pub fn results<N, T, E>(notifys: impl IntoIterator<Item = N>) -> Results<N, E>
+where
+    N: Notify<Event = Result<T, E>>,
+{
+    let notifys: Vec<N> = notifys.into_iter().collect();
+    let failed = alloc::vec![false; notifys.len()];
+
+    Results {
+        notifys,
+        failed,
+        errors: Vec::new(),
+    }
+}
+
+/// Turn a [`Notify`] into a function that creates a [`Future`] for its next
+/// event each time it's called.
+///
+/// This bridges a notify onto APIs that expect a future-producing closure.
+/// The futures share the notify, so they may be created before the previous
+/// one completes, but only one may be polled at a time.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// async fn sum<F: Future<Output = u32>>(mut next: impl FnMut() -> F) -> u32 {
+///     next().await + next().await
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut count = 0;
+///     let mut counter = notify::poll_fn(|_| {
+///         count += 1;
+///         Ready(count)
+///     });
+///
+///     assert_eq!(sum(notify::next_fn(&mut counter)).await, 3);
+/// });
+/// ```
+pub fn next_fn<'a, N>(notify: &'a mut N) -> impl FnMut() -> NextFn<'a, N>
+where
+    N: Notify + Unpin + ?Sized,
+{
+    let notify = Rc::new(RefCell::new(notify));
+
+    move || NextFn(notify.clone())
+}
+
+/// Create a [`Future`] that waits until every [`Notify`] in a list has
+/// produced an event, returning the events in the same order as the list.
+///
+/// This is the counterpart to [`select()`]; rather than racing the notifys,
+/// it waits for all of them, without counting how many are done by hand.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut notifys = [notify::ready(1), notify::ready(2), notify::ready(3)];
+///
+///     assert_eq!(notify::all(&mut notifys).await, [1, 2, 3]);
+/// });
+/// ```
+pub fn all<N: Notify + Unpin>(notifys: &mut [N]) -> All<'_, N> {
+    let events = notifys.iter().map(|_| None).collect();
+
+    All(notifys, events)
+}
+
+/// Create a [`Notify`] that wraps a [`Stream`](futures_core::Stream).
+///
+/// Each item becomes `Some(item)`.  Once the stream ends, the notify produces
+/// a single `None`, and never becomes ready again.  Streams that aren't
+/// [`Unpin`] can be wrapped with [`Box::pin()`] first.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// struct Countdown(u32);
+///
+/// impl futures_core::Stream for Countdown {
+///     type Item = u32;
+///
+///     fn poll_next(
+///         self: Pin<&mut Self>,
+///         _: &mut Task<'_>,
+///     ) -> Poll<Option<u32>> {
+///         let this = self.get_mut();
+///         let Some(count) = this.0.checked_sub(1) else {
+///             return Ready(None);
+///         };
+///
+///         this.0 = count;
+///         Ready(Some(count))
+///     }
+/// }
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut countdown = notify::stream(Countdown(2));
+///
+///     assert_eq!(countdown.next().await, Some(1));
+///     assert_eq!(countdown.next().await, Some(0));
+///     assert_eq!(countdown.next().await, None);
+/// });
+/// ```
+#[cfg(feature = "stream")]
+pub fn stream<S>(stream: S) -> StreamNotify<S>
+where
+    S: futures_core::Stream + Unpin,
+{
+    StreamNotify(Some(stream))
+}
+
+/// Storage for a single [`Waker`], for implementing [`Notify`]s.
+///
+/// Registering the same waker on every `Pending` poll is common, so
+/// [`WakerSlot::register()`] skips the clone when the stored waker would
+/// already wake the same task (see [`Waker::will_wake()`]).
+///
+/// ```rust
+/// use pasts::{notify::WakerSlot, prelude::*};
+///
+/// #[derive(Default)]
+/// struct Flag(bool, WakerSlot);
+///
+/// impl Flag {
+///     fn set(&mut self) {
+///         self.0 = true;
+///         self.1.wake();
+///     }
+/// }
+///
+/// impl Notify for Flag {
+///     type Event = ();
+///
+///     fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+///         let this = self.get_mut();
+///
+///         if core::mem::take(&mut this.0) {
+///             return Ready(());
+///         }
+///
+///         this.1.register(t.waker());
+///         Pending
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct WakerSlot(Option<Waker>);
+
+impl WakerSlot {
+    /// Create an empty slot.
+    pub const fn new() -> Self {
+        Self(None)
+    }
+
+    /// Store `waker`, unless the stored waker already wakes the same task.
+    #[inline]
+    pub fn register(&mut self, waker: &Waker) {
+        match &mut self.0 {
+            Some(old) if old.will_wake(waker) => {}
+            slot => *slot = Some(waker.clone()),
+        }
+    }
+
+    /// Wake and remove the stored waker, if any.
+    #[inline]
+    pub fn wake(&mut self) {
+        if let Some(waker) = self.0.take() {
+            waker.wake();
+        }
+    }
+
+    /// Remove the stored waker without waking it.
+    #[inline]
+    pub fn take(&mut self) -> Option<Waker> {
+        self.0.take()
+    }
+
+    /// Check if a waker is stored.
+    #[inline]
+    pub fn is_registered(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// Create a [`Notify`] that wraps a function returning a [`Future`].
+///
+/// Polling the notify delegates to future returned by the wrapped function.
+/// The wrapped function is called immediately, and is only called again once
+/// the future is polled and returns `Ready`.
+pub fn future_fn<T, F>(mut f: F) -> FutureFn<T, F>
+where
+    T: Future + Unpin,
+    F: FnMut() -> T + Unpin,
+{
+    FutureFn(f(), f)
+}
+
+/// Create a [`Notify`] that wraps a function returning [`Poll`].
+///
+/// Polling the future delegates to the wrapped function.
+pub fn poll_fn<T, F>(f: F) -> PollFn<F>
+where
+    F: FnMut(&mut Task<'_>) -> Poll<T> + Unpin,
 {
     PollFn(f)
 }
@@ -367,12 +2035,69 @@ pub fn pending<T>() -> Pending<T> {
     Pending(core::marker::PhantomData)
 }
 
+/// Create a [`Notify`] and [`Future`] which is never ready, for disabled
+/// branches of a [`select()`] or [`Loop`](crate::Loop).
+///
+/// Unlike [`Pending`], the [`Never`] type's event type defaults to
+/// [`Infallible`](core::convert::Infallible), so it can be named without one
+/// (for example, in a struct field).  Functions can't have defaults, so the
+/// event type is still inferred or given here.  Events of type `Infallible`
+/// can be mapped to any other event type.
+///
+/// ```rust
+/// use core::convert::Infallible;
+///
+/// use pasts::{notify, prelude::*};
+///
+/// let mut disabled = notify::never::<Infallible>().map(|never| match never {});
+/// let mut ready = notify::ready(7);
+///
+/// pasts::Executor::default().block_on(async move {
+///     let event = notify::select([&mut disabled, &mut ready]).next().await;
+///
+///     assert_eq!(event, 7);
+/// });
+/// ```
+pub fn never<T>() -> Never<T> {
+    Never(core::marker::PhantomData)
+}
+
 /// Create a [`Notify`] which is immediately ready with an event.
 pub fn ready<T: Unpin>(t: T) -> Ready<T> {
     Ready(t.into())
 }
 
+/// Create a [`Notify`] that merges the events of two [`Notify`]s, with strict
+/// priority for `a`.
+///
+/// Unlike [`select()`], `a` is always checked first, so `b` only gets to
+/// produce an event once `a` has nothing ready.  This lets control messages
+/// preempt data, but a notify that's always ready as `a` starves `b`.
+///
+/// ```rust
+/// use pasts::{notify, prelude::*};
+///
+/// pasts::Executor::default().block_on(async {
+///     let control = notify::ready("stop");
+///     let data = notify::poll_fn(|_| Ready("data"));
+///     let mut merged = notify::merge_prefer(control, data);
+///
+///     assert_eq!(merged.next().await, "stop");
+///     assert_eq!(merged.next().await, "data");
+/// });
+/// ```
+pub fn merge_prefer<A, B>(a: A, b: B) -> MergePrefer<A, B>
+where
+    A: Notify,
+    B: Notify<Event = A::Event>,
+{
+    MergePrefer(a, b)
+}
+
 /// Create a [`Notify`] that selects over a list of [`Notify`]s.
+///
+/// Polling starts after the last notify that was ready, so that a notify
+/// that is always ready can't starve the others.
 pub fn select<E, const N: usize>(
     notifys: [&mut (dyn Notify<Event = E> + Unpin); N],
 ) -> Select<'_, E, N> {
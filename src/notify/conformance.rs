@@ -0,0 +1,118 @@
+//! Check that a [`Notify`] upholds the contract executors rely on.
+//!
+//! [`check()`] polls a notify until it's waiting on something outside of the
+//! test, panicking if it:
+//!
+//!  - panics when polled again after producing an event
+//!  - returns [`Pending`] without registering the task's waker
+//!  - keeps waking itself without ever producing an event
+//!
+//! ```rust
+//! use pasts::{notify::conformance, prelude::*};
+//! use std::task::Waker;
+//!
+//! /// Counts down, then waits for an event that never comes.
+//! struct Countdown(u32, Option<Waker>);
+//!
+//! impl Notify for Countdown {
+//!     type Event = u32;
+//!
+//!     fn poll_next(mut self: Pin<&mut Self>, task: &mut Task<'_>) -> Poll<u32> {
+//!         if self.0 == 0 {
+//!             self.1 = Some(task.waker().clone());
+//!             return Pending;
+//!         }
+//!
+//!         self.0 -= 1;
+//!         Ready(self.0)
+//!     }
+//! }
+//!
+//! assert_eq!(conformance::check(Countdown(3, None)), [2, 1, 0]);
+//! ```
+
+use alloc::{sync::Arc, task::Wake, vec::Vec};
+use core::{
+    sync::atomic::{AtomicUsize, Ordering},
+    task::Waker,
+};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::prelude::*;
+
+// Polls before giving up on a notify that's always ready
+const POLLS: usize = 256;
+// Consecutive self-wakes before calling it a busy loop
+const SPINS: usize = 64;
+
+/// Poll `notify` until it's waiting on an outside event, checking each poll,
+/// and return the events it produced.
+///
+/// Notifys that are always ready are polled a fixed number of times.  One-shot
+/// notifys that stop registering wakers after their last event, like
+/// [`ready()`](super::ready()), don't pass.
+///
+/// # Panics
+/// If `notify` breaks the contract, with a message describing how.
+pub fn check<N: Notify + Unpin>(mut notify: N) -> Vec<N::Event> {
+    let mut events = Vec::new();
+    let mut spins = 0;
+
+    for _ in 0..POLLS {
+        let counter = Arc::new(Counter::default());
+        let waker = Waker::from(counter.clone());
+        let mut task = Task::from_waker(&waker);
+        let poll = panic::catch_unwind(AssertUnwindSafe(|| {
+            Pin::new(&mut notify).poll_next(&mut task)
+        }));
+        let poll = match poll {
+            Ok(poll) => poll,
+            Err(payload) if events.is_empty() => panic::resume_unwind(payload),
+            Err(_) => panic!(
+                "notify panicked when polled after producing {} event(s)",
+                events.len(),
+            ),
+        };
+
+        drop(waker);
+
+        if let Ready(event) = poll {
+            events.push(event);
+            spins = 0;
+            continue;
+        }
+
+        if counter.wakes.load(Ordering::Relaxed) != 0 {
+            spins += 1;
+            assert!(
+                spins < SPINS,
+                "notify woke itself {SPINS} times in a row without producing \
+                 an event",
+            );
+            continue;
+        }
+
+        assert!(
+            Arc::strong_count(&counter) > 1,
+            "notify returned `Pending` without registering the waker",
+        );
+        break;
+    }
+
+    events
+}
+
+#[derive(Default)]
+struct Counter {
+    wakes: AtomicUsize,
+}
+
+impl Wake for Counter {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.wakes.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,192 @@
+use alloc::{rc::Rc, sync::Arc, task::Wake};
+use core::{fmt, marker::PhantomData, task::Waker, time::Duration};
+
+use crate::{
+    spawn::DefaultPark,
+    sync::{AtomicBool, Ordering},
+    Park,
+};
+
+/// Blocks the thread that created it until woken by an [`Unparker`].
+///
+/// This is the same parking the executor does between passes over its tasks,
+/// for custom executors, bridges from blocking code, and tests.  Unparking
+/// before parking isn't lost; the next park returns right away.  Parking may
+/// also return spuriously, so check the condition being waited for in a
+/// loop.
+///
+/// ```rust
+/// use std::{
+///     sync::{
+///         atomic::{AtomicBool, Ordering},
+///         Arc,
+///     },
+///     thread,
+/// };
+///
+/// use pasts::Parker;
+///
+/// let parker: Parker = Parker::new();
+/// let unparker = parker.unparker();
+/// let done = Arc::new(AtomicBool::new(false));
+/// let flag = done.clone();
+///
+/// thread::spawn(move || {
+///     flag.store(true, Ordering::SeqCst);
+///     unparker.unpark();
+/// });
+///
+/// while !done.load(Ordering::SeqCst) {
+///     parker.park();
+/// }
+/// ```
+pub struct Parker<P: Park = DefaultPark> {
+    unparker: Unparker<P>,
+    // Parks the thread it was created on, so it can't be sent
+    _local: PhantomData<Rc<()>>,
+}
+
+impl<P: Park> fmt::Debug for Parker<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parker")
+            .field("unparked", &self.unparker.unparked())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: Park> Default for Parker<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Park> Parker<P> {
+    /// Create a parker for the current thread.
+    pub fn new() -> Self {
+        Self::with_level(false)
+    }
+
+    // Create a parker, which starts out unparked if `level` is set.
+    pub(crate) fn with_level(level: bool) -> Self {
+        Self {
+            unparker: Unparker {
+                park: Arc::new(P::default()),
+                level: Arc::new(AtomicBool::new(level)),
+            },
+            _local: PhantomData,
+        }
+    }
+
+    /// Block until unparked, or return right away if unparked since the last
+    /// time this returned.
+    pub fn park(&self) {
+        self.unparker.park.park();
+        self.reset();
+    }
+
+    /// Like [`Parker::park()`], but may return early after `timeout` has
+    /// elapsed.
+    pub fn park_timeout(&self, timeout: Duration) {
+        self.unparker.park.park_timeout(timeout);
+        self.reset();
+    }
+
+    /// Get an [`Unparker`] for waking this parker from any thread.
+    pub fn unparker(&self) -> Unparker<P> {
+        self.unparker.clone()
+    }
+
+    /// Get a waker that unparks this parker.
+    pub fn waker(&self) -> Waker {
+        Arc::new(self.unparker()).into()
+    }
+
+    // Start accepting unparks again.
+    //
+    // Swap rather than store, so that this synchronizes with the last
+    // unpark, and the caller sees whatever the unparking thread did before it
+    pub(crate) fn reset(&self) {
+        self.unparker.level.swap(false, Ordering::SeqCst);
+    }
+
+    // Check if unparked since the last reset, without resetting.
+    #[cfg(not(feature = "web_lite"))]
+    pub(crate) fn unparked(&self) -> bool {
+        self.unparker.unparked()
+    }
+
+    // The flag set on unpark, and cleared on reset.
+    #[cfg(all(feature = "std", not(feature = "web_lite")))]
+    pub(crate) fn level(&self) -> &Arc<AtomicBool> {
+        &self.unparker.level
+    }
+
+    // The underlying park, for parking without resetting.
+    #[cfg(not(feature = "web_lite"))]
+    pub(crate) fn inner(&self) -> &P {
+        &self.unparker.park
+    }
+}
+
+/// Wakes a [`Parker`], from any thread.
+///
+/// Only the first unpark since the parker last returned reaches the
+/// underlying [`Park`]; the rest are already covered by it.
+pub struct Unparker<P: Park = DefaultPark> {
+    park: Arc<P>,
+    level: Arc<AtomicBool>,
+}
+
+impl<P: Park> fmt::Debug for Unparker<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Unparker")
+            .field("unparked", &self.unparked())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: Park> Clone for Unparker<P> {
+    fn clone(&self) -> Self {
+        Self {
+            park: self.park.clone(),
+            level: self.level.clone(),
+        }
+    }
+}
+
+impl<P: Park> Unparker<P> {
+    /// Wake the parker, or make its next park return right away.
+    #[inline(always)]
+    pub fn unpark(&self) {
+        self.try_unpark();
+    }
+
+    // Unpark, returning true if this was the first unpark since the parker
+    // last returned.
+    #[inline(always)]
+    pub(crate) fn try_unpark(&self) -> bool {
+        let first = !self.level.swap(true, Ordering::SeqCst);
+
+        if first {
+            self.park.unpark();
+        }
+
+        first
+    }
+
+    fn unparked(&self) -> bool {
+        self.level.load(Ordering::SeqCst)
+    }
+}
+
+impl<P: Park> Wake for Unparker<P> {
+    #[inline(always)]
+    fn wake(self: Arc<Self>) {
+        self.unpark();
+    }
+
+    #[inline(always)]
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.unpark();
+    }
+}
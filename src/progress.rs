@@ -0,0 +1,199 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::{fmt, task::Waker};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::prelude::*;
+
+struct State {
+    done: u64,
+    total: u64,
+    // Incremented on every update, so watchers can tell what they've seen
+    version: u64,
+    watchers: Vec<Waker>,
+}
+
+struct Shared(Mutex<State>);
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut State)) {
+        let watchers = {
+            let mut state = self.lock();
+
+            f(&mut state);
+            state.version += 1;
+            core::mem::take(&mut state.watchers)
+        };
+
+        for waker in watchers {
+            waker.wake();
+        }
+    }
+}
+
+/// Progress of a long-running operation, which can be watched from other
+/// tasks.
+///
+/// The operation updates how much of the work is done, from any thread, and
+/// frontends (such as a progress bar) receive [`ProgressSnapshot`]s from
+/// [`Progress::watch()`].  Updates made between polls of a watcher are
+/// coalesced into one snapshot, so watching never slows the operation down.
+/// Clones update the same progress.
+///
+/// ```rust
+/// use pasts::{prelude::*, sync::Progress, Executor};
+///
+/// let executor = Executor::default();
+/// let progress = Progress::new(3);
+/// let mut watch = progress.watch();
+///
+/// executor.spawn(async move {
+///     for _ in 0..3 {
+///         progress.advance(1);
+///     }
+/// });
+/// executor.block_on(async move {
+///     loop {
+///         let snapshot = watch.next().await;
+///
+///         println!("{:.0}%", snapshot.ratio() * 100.0);
+///
+///         if snapshot.is_complete() {
+///             break;
+///         }
+///     }
+/// });
+/// ```
+#[derive(Clone)]
+pub struct Progress(Arc<Shared>);
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Progress").field(&self.snapshot()).finish()
+    }
+}
+
+impl Progress {
+    /// Start tracking progress towards `total` units of work.
+    pub fn new(total: u64) -> Self {
+        Self(Arc::new(Shared(Mutex::new(State {
+            done: 0,
+            total,
+            version: 0,
+            watchers: Vec::new(),
+        }))))
+    }
+
+    /// Add `amount` to the units of work that are done.
+    pub fn advance(&self, amount: u64) {
+        self.0
+            .update(|state| state.done = state.done.saturating_add(amount));
+    }
+
+    /// Set the units of work that are done.
+    pub fn set(&self, done: u64) {
+        self.0.update(|state| state.done = done);
+    }
+
+    /// Change the total units of work, for when it's discovered as the
+    /// operation goes.
+    pub fn set_total(&self, total: u64) {
+        self.0.update(|state| state.total = total);
+    }
+
+    /// Get the current progress.
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let state = self.0.lock();
+
+        ProgressSnapshot {
+            done: state.done,
+            total: state.total,
+        }
+    }
+
+    /// Get a [`Notify`] of snapshots, starting with the current progress.
+    pub fn watch(&self) -> ProgressWatch {
+        ProgressWatch {
+            shared: self.0.clone(),
+            seen: None,
+        }
+    }
+}
+
+/// Progress at one point in time, from [`Progress`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ProgressSnapshot {
+    /// Units of work that are done
+    pub done: u64,
+    /// Units of work in total
+    pub total: u64,
+}
+
+impl ProgressSnapshot {
+    /// Get the fraction of the work that's done, from `0.0` to `1.0`.
+    ///
+    /// With no work in total, this is `1.0`.
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let ratio = self.done as f64 / self.total as f64;
+
+        ratio.min(1.0)
+    }
+
+    /// Check if all of the work is done.
+    pub fn is_complete(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+/// [`Notify`] returned from [`Progress::watch()`].
+///
+/// Produces the current snapshot first, and then the latest one each time
+/// the progress has been updated since the last.
+#[derive(Clone)]
+pub struct ProgressWatch {
+    shared: Arc<Shared>,
+    seen: Option<u64>,
+}
+
+impl fmt::Debug for ProgressWatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressWatch").finish_non_exhaustive()
+    }
+}
+
+impl Notify for ProgressWatch {
+    type Event = ProgressSnapshot;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        t: &mut Task<'_>,
+    ) -> Poll<ProgressSnapshot> {
+        let this = self.get_mut();
+        let mut state = this.shared.lock();
+
+        if this.seen != Some(state.version) {
+            this.seen = Some(state.version);
+
+            return Ready(ProgressSnapshot {
+                done: state.done,
+                total: state.total,
+            });
+        }
+
+        if !state.watchers.iter().any(|w| w.will_wake(t.waker())) {
+            state.watchers.push(t.waker().clone());
+        }
+
+        Pending
+    }
+}
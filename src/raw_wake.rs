@@ -0,0 +1,135 @@
+use core::fmt;
+
+use atomic_waker::AtomicWaker;
+
+use crate::{
+    prelude::*,
+    sync::{AtomicBool, Ordering},
+};
+
+/// Wake source for signal handlers and interrupts, which can't safely lock,
+/// allocate, or call arbitrary wakers.
+///
+/// A [`RawWakeHandle`] only stores to an atomic, so its
+/// [`RawWakeHandle::wake()`] is safe to call from any context.  Since the
+/// handle borrows a `'static` `RawWake`, there's no reference count to drop
+/// there either.  The task waiting on [`RawWake::notify()`] is woken later,
+/// from a normal context, by [`RawWake::dispatch()`].  Custom [`Park`]s for
+/// hardware that wakes from sleep on interrupts can dispatch each time they
+/// return, so the task is woken before the executor parks again.
+///
+/// The waker is stored without locking, so this works without the _`std`_
+/// feature.
+///
+/// [`Park`]: crate::Park
+///
+/// ```rust
+/// use pasts::{prelude::*, RawWake};
+///
+/// static BUTTON: RawWake = RawWake::new();
+///
+/// // Called from the interrupt handler
+/// fn on_interrupt() {
+///     BUTTON.handle().wake();
+/// }
+///
+/// on_interrupt();
+/// BUTTON.dispatch();
+///
+/// pasts::Executor::default().block_on(async {
+///     BUTTON.notify().next().await;
+/// });
+/// ```
+pub struct RawWake {
+    pending: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl fmt::Debug for RawWake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawWake")
+            .field("pending", &self.pending.load(Ordering::SeqCst))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RawWake {
+    fn default() -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+        }
+    }
+}
+
+impl RawWake {
+    /// Create a wake source, for placing in a `static`.
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    /// Get a handle for waking from a signal handler or interrupt.
+    pub fn handle(&'static self) -> RawWakeHandle {
+        RawWakeHandle(self)
+    }
+
+    /// Get a [`Notify`] for raw wakes, which are combined into one event
+    /// until received.
+    pub fn notify(&'static self) -> RawWakeNotify {
+        RawWakeNotify(self)
+    }
+
+    /// Wake the task waiting on [`RawWake::notify()`], if there's been a raw
+    /// wake since it was last received.
+    ///
+    /// This must not be called from a signal handler or interrupt.  Returns
+    /// true if a task was woken.
+    pub fn dispatch(&self) -> bool {
+        if !self.pending.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let waker = self.waker.take();
+
+        waker.map(|waker| waker.wake()).is_some()
+    }
+}
+
+/// Handle for waking a [`RawWake`] from a signal handler or interrupt.
+#[derive(Copy, Clone, Debug)]
+pub struct RawWakeHandle(&'static RawWake);
+
+impl RawWakeHandle {
+    /// Mark the [`RawWake`] as woken, only touching an atomic.
+    #[inline(always)]
+    pub fn wake(self) {
+        self.0.pending.store(true, Ordering::SeqCst);
+    }
+}
+
+/// [`Notify`] returned from [`RawWake::notify()`].
+#[derive(Copy, Clone, Debug)]
+pub struct RawWakeNotify(&'static RawWake);
+
+impl Notify for RawWakeNotify {
+    type Event = ();
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        if self.0.pending.swap(false, Ordering::SeqCst) {
+            return Ready(());
+        }
+
+        self.0.waker.register(t.waker());
+
+        // A raw wake may have come in before the waker was stored
+        if self.0.pending.swap(false, Ordering::SeqCst) {
+            return Ready(());
+        }
+
+        Pending
+    }
+}
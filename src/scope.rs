@@ -0,0 +1,107 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::{cell::RefCell, fmt};
+
+use crate::prelude::*;
+
+type Tasks<'a, E> = Vec<LocalBoxNotify<'a, Result<(), E>>>;
+
+/// Structured concurrency scope, which fails fast.
+///
+/// A `Scope` is a [`Future`] that runs all of its tasks to completion,
+/// within the task that awaits it.  As soon as one of the tasks returns an
+/// error, the remaining tasks are cancelled (dropped) and the error is
+/// returned from the scope.  Panics in tasks unwind through the scope, also
+/// dropping the remaining tasks.
+///
+/// ```rust
+#[doc = include_str!("../examples/scope.rs")]
+/// ```
+pub struct Scope<'a, E = ()>(Tasks<'a, E>, ScopeHandle<'a, E>);
+
+impl<E> fmt::Debug for Scope<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Scope").field(&self.0).finish()
+    }
+}
+
+impl<E> Default for Scope<'_, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, E> Scope<'a, E> {
+    /// Create a new empty scope.
+    pub fn new() -> Self {
+        Self(Vec::new(), ScopeHandle(Rc::default()))
+    }
+
+    /// Get a handle for spawning tasks on this scope from within its tasks.
+    pub fn handle(&self) -> ScopeHandle<'a, E> {
+        self.1.clone()
+    }
+
+    /// Spawn a task on this scope.
+    pub fn spawn(&self, f: impl Future<Output = Result<(), E>> + 'a) {
+        self.1.spawn(f);
+    }
+}
+
+impl<E> Future for Scope<'_, E> {
+    type Output = Result<(), E>;
+
+    fn poll(mut self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let Self(ref mut tasks, ref handle) = *self;
+
+        loop {
+            tasks.append(&mut handle.0.borrow_mut());
+
+            if tasks.is_empty() {
+                return Ready(Ok(()));
+            }
+
+            let Ready((index, result)) =
+                tasks.as_mut_slice().poll_next_unpin(t)
+            else {
+                if handle.0.borrow().is_empty() {
+                    return Pending;
+                }
+                continue;
+            };
+
+            tasks.swap_remove(index);
+
+            if let Err(error) = result {
+                // Cancel siblings, including any that haven't started yet
+                tasks.clear();
+                handle.0.borrow_mut().clear();
+
+                return Ready(Err(error));
+            }
+        }
+    }
+}
+
+/// A handle for spawning tasks on a [`Scope`].
+pub struct ScopeHandle<'a, E = ()>(Rc<RefCell<Tasks<'a, E>>>);
+
+impl<E> Clone for ScopeHandle<'_, E> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<E> fmt::Debug for ScopeHandle<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ScopeHandle")
+    }
+}
+
+impl<'a, E> ScopeHandle<'a, E> {
+    /// Spawn a task on the scope.
+    ///
+    /// Tasks spawned after the scope has completed are never run.
+    pub fn spawn(&self, f: impl Future<Output = Result<(), E>> + 'a) {
+        self.0.borrow_mut().push(Box::pin(f.fuse()));
+    }
+}
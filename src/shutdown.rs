@@ -0,0 +1,101 @@
+use alloc::{sync::Arc, vec::Vec};
+#[cfg(not(feature = "std"))]
+use core::cell::RefCell;
+use core::{fmt, ops::DerefMut, task::Waker};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
+use crate::{
+    prelude::*,
+    sync::{AtomicBool, Ordering},
+};
+
+// Flag set once an executor starts shutting down, which can be waited on from
+// any thread.
+#[derive(Default)]
+pub(crate) struct Shutdown {
+    fired: AtomicBool,
+    #[cfg(feature = "std")]
+    wakers: Mutex<Vec<Waker>>,
+    #[cfg(not(feature = "std"))]
+    wakers: RefCell<Vec<Waker>>,
+}
+
+impl Shutdown {
+    // Unset the flag, so it can be waited on again.
+    pub(crate) fn reset(&self) {
+        self.fired.store(false, Ordering::SeqCst);
+    }
+
+    // Set the flag, and wake the tasks waiting for it.
+    pub(crate) fn fire(&self) {
+        self.fired.store(true, Ordering::SeqCst);
+
+        let wakers = core::mem::take(&mut *self.wakers());
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn is_fired(&self) -> bool {
+        self.fired.load(Ordering::SeqCst)
+    }
+
+    // Check if the flag is set, or register to wake the task once it is.
+    fn poll(&self, t: &mut Task<'_>) -> Poll {
+        if self.is_fired() {
+            return Ready(());
+        }
+
+        let mut wakers = self.wakers();
+
+        if !wakers.iter().any(|w| w.will_wake(t.waker())) {
+            wakers.push(t.waker().clone());
+        }
+
+        drop(wakers);
+
+        // May have fired before the waker was stored
+        if self.is_fired() {
+            return Ready(());
+        }
+
+        Pending
+    }
+
+    fn wakers(&self) -> impl DerefMut<Target = Vec<Waker>> + '_ {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        #[cfg(feature = "std")]
+        {
+            self.wakers.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        #[cfg(not(feature = "std"))]
+        self.wakers.borrow_mut()
+    }
+}
+
+/// Future that completes once an [`Executor`](crate::Executor) starts
+/// shutting down.
+///
+/// Created with
+/// [`Executor::on_shutdown()`](crate::Executor::on_shutdown()).  Use
+/// [`Fuse::fuse()`] to handle it in a [`Loop`](crate::Loop).
+pub struct OnShutdown(pub(crate) Arc<Shutdown>);
+
+impl fmt::Debug for OnShutdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnShutdown")
+            .field(&self.0.is_fired())
+            .finish()
+    }
+}
+
+impl Future for OnShutdown {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        self.0.poll(t)
+    }
+}
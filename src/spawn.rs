@@ -1,7 +1,43 @@
-use alloc::{sync::Arc, task::Wake, vec::Vec};
-use core::{cell::Cell, fmt, future::Future};
+use alloc::{collections::TryReserveError, sync::Arc, task::Wake, vec::Vec};
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+use core::cell::Cell;
+use core::{cell::RefCell, fmt, future::Future, task::Waker, time::Duration};
+#[cfg(feature = "std")]
+use std::{any::Any, time::Instant};
+
+use atomic_waker::AtomicWaker;
+
+#[cfg(all(
+    feature = "std",
+    any(
+        not(feature = "web_lite"),
+        all(feature = "web", target_feature = "atomics")
+    )
+))]
+use crate::blocking::{self, BlockingHandle, BlockingQueue, Threads};
+#[cfg(feature = "std")]
+use crate::join::{catch_unwind, Panicked};
+#[cfg(feature = "web_lite")]
+use crate::web;
+use crate::{
+    join::{joinable, on_cancel, JoinHandle},
+    park::Unparker,
+    prelude::*,
+    shutdown::{OnShutdown, Shutdown},
+    stats::{Counters, ExecutorStats},
+    sync::{AtomicBool, Ordering},
+};
+#[cfg(not(feature = "web_lite"))]
+use crate::{list::List, park::Parker};
 
-use crate::prelude::*;
+// Log a debug-level record of the executor's lifecycle, when building with
+// feature _`log`_.
+macro_rules! lifecycle {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::debug!(target: "pasts", $($arg)*);
+    };
+}
 
 /// Pasts' executor.
 ///
@@ -30,7 +66,18 @@ use crate::prelude::*;
 /// ```rust
 #[doc = include_str!("../examples/resume.rs")]
 /// ```
-pub struct Executor<P: Pool = DefaultPool>(Arc<P>);
+/// 
+/// Tasks of the enclosing executor can't run until the nested `block_on()`
+/// returns, so if the nested one waits on them it will never return.  This
+/// is reported as [`Diagnostic::Reentrant`] instead of silently hanging, which
+/// panics unless a hook is set with [`Executor::watchdog()`].
+pub struct Executor<P: Pool = DefaultPool>(
+    Arc<P>,
+    Arc<Config>,
+    Arc<Shutdown>,
+    // Wakes the executor to start tasks spawned from other threads
+    Arc<AtomicWaker>,
+);
 
 impl Default for Executor {
     fn default() -> Self {
@@ -40,7 +87,12 @@ impl Default for Executor {
 
 impl<P: Pool> Clone for Executor<P> {
     fn clone(&self) -> Self {
-        Self(Arc::clone(&self.0))
+        Self(
+            Arc::clone(&self.0),
+            Arc::clone(&self.1),
+            Arc::clone(&self.2),
+            Arc::clone(&self.3),
+        )
     }
 }
 
@@ -51,227 +103,2317 @@ impl<P: Pool + fmt::Debug> fmt::Debug for Executor<P> {
 }
 
 impl<P: Pool> Executor<P> {
-    /// Create a new executor that can only spawn tasks from the current thread.
+    /// Create a new executor.
     ///
-    /// Custom executors can be built by implementing [`Pool`].
+    /// Custom executors can be built by implementing [`Pool`].  With a pool
+    /// that's [`Sync`], the executor can be sent to other threads, to spawn
+    /// [`Send`] tasks from them with [`Executor::spawn()`].
     #[inline(always)]
     pub fn new(pool: P) -> Self {
-        Self(Arc::new(pool))
+        Self(
+            Arc::new(pool),
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+        )
     }
 
-    /// Block on a future and return it's result.
+    /// Report tasks that block the executor, and the executor parking for
+    /// too long.
+    ///
+    /// `hook` is called with [`Diagnostic::SlowPoll`] whenever a single poll
+    /// of a task takes longer than `poll`, which usually means a blocking call
+    /// was made from async code.  It's called with [`Diagnostic::Stall`] each
+    /// time the executor stays parked for `park` while tasks are still
+    /// pending, but none have registered a waker (for example, with a timer or
+    /// a channel), which usually means a wakeup was lost.  It's also called
+    /// with any of the other [`Diagnostic`]s, such as
+    /// [`Diagnostic::Deadlock`].
+    ///
+    /// Settings are shared with clones made after calling this method.
     ///
     /// # Platform-Specific Behavior
-    /// When building with feature _`web`_, spawns task and returns
-    /// immediately instead of blocking.
-    #[inline(always)]
-    pub fn block_on(self, f: impl Future<Output = ()> + 'static) {
-        #[cfg(feature = "web")]
-        wasm_bindgen_futures::spawn_local(f);
+    /// When building with feature _`web`_, the hook is never called.
+    #[cfg(feature = "std")]
+    pub fn watchdog(
+        mut self,
+        poll: Duration,
+        park: Duration,
+        hook: impl Fn(Diagnostic) + Send + Sync + 'static,
+    ) -> Self {
+        let hook = Arc::new(hook);
 
-        #[cfg(not(feature = "web"))]
-        block_on(f, &self.0);
+        Arc::make_mut(&mut self.1).watchdog =
+            Some(Watchdog { poll, park, hook });
+        self
     }
-}
 
-impl<P: Pool> Executor<P> {
-    /// Spawn a [`LocalBoxNotify`] on this executor.
+    /// Call `before` immediately before and `after` immediately after each
+    /// time a task is polled.
     ///
-    /// Execution of the [`LocalBoxNotify`] will halt after the first poll that
-    /// returns [`Ready`].
-    #[inline(always)]
-    pub fn spawn_notify(&self, n: LocalBoxNotify<'static>) {
-        // Convert the notify into a future and spawn on wasm_bindgen_futures
-        #[cfg(feature = "web")]
-        wasm_bindgen_futures::spawn_local(async move {
-            let mut n = n;
-
-            n.next().await;
-        });
+    /// This can be used for profiling, or setting up thread-local state
+    /// (such as allocator scopes) for the duration of a poll.
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, the hooks are never called.
+    pub fn poll_hooks(
+        mut self,
+        before: impl Fn(TaskId) + Send + Sync + 'static,
+        after: impl Fn(TaskId) + Send + Sync + 'static,
+    ) -> Self {
+        let hooks = PollHooks(Arc::new(before), Arc::new(after));
 
-        // Push the notify onto the pool.
-        #[cfg(not(feature = "web"))]
-        self.0.push(n);
+        Arc::make_mut(&mut self.1).hooks = Some(hooks);
+        self
     }
 
-    /// Box and spawn a future on this executor.
-    #[inline(always)]
-    pub fn spawn_boxed(&self, f: impl Future<Output = ()> + 'static) {
-        // Spawn the future on wasm_bindgen_futures
-        #[cfg(feature = "web")]
-        wasm_bindgen_futures::spawn_local(f);
+    /// Call `hook` with the task's identifier each time a task is woken.
+    ///
+    /// This answers the question of which task keeps waking up the executor
+    /// (for example, when a loop is spinning).  The hook may be called from
+    /// any thread that holds a waker for the task.
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, the hook is never called.
+    pub fn wake_hook(
+        mut self,
+        hook: impl Fn(TaskId) + Send + Sync + 'static,
+    ) -> Self {
+        Arc::make_mut(&mut self.1).wake_hook = Some(Arc::new(hook));
+        self
+    }
 
-        // Fuse the future, box it, and push it onto the pool.
-        #[cfg(not(feature = "web"))]
-        self.spawn_notify(Box::pin(f.fuse()));
+    /// Call `hook` with the size in bytes of each task as it's spawned.
+    ///
+    /// Tasks are boxed when spawned, so futures that are accidentally huge
+    /// (for example, from holding a large array by value across an `.await`)
+    /// can use up a lot of memory when there's thousands of them.  The size
+    /// includes the few bytes used to fuse the future.
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// ```rust
+    /// let executor = pasts::Executor::default().task_size_hook(|size| {
+    ///     assert!(size < 4096, "task is {size} bytes");
+    /// });
+    ///
+    /// executor.block_on(async {});
+    /// ```
+    pub fn task_size_hook(
+        mut self,
+        hook: impl Fn(usize) + Send + Sync + 'static,
+    ) -> Self {
+        Arc::make_mut(&mut self.1).size_hook = Some(Arc::new(hook));
+        self
     }
-}
 
-/// Storage for a task pool.
-///
-/// # Implementing `Pool` For A Custom Executor
-/// This example shows how to create a custom single-threaded executor using
-/// [`Executor::new()`].
-///
-/// ```rust
-#[doc = include_str!("../examples/pool.rs")]
-/// ```
-pub trait Pool {
-    /// Type that handles the sleeping / waking of the executor.
-    type Park: Park;
+    // Call `hook` each time before the executor parks.
+    #[cfg(feature = "test_util")]
+    pub(crate) fn park_hook(
+        mut self,
+        hook: impl Fn() + Send + Sync + 'static,
+    ) -> Self {
+        Arc::make_mut(&mut self.1).park_hook = Some(Arc::new(hook));
+        self
+    }
 
-    /// Push a task into the thread pool queue.
-    fn push(&self, task: LocalBoxNotify<'static>);
+    /// Make tasks that keep waking themselves yield their turn.
+    ///
+    /// After a task has woken itself during `polls` polls in a row, it's
+    /// skipped for one pass over the tasks, so that a spin-polling task (such
+    /// as a busy driver) takes up at most `polls` out of every `polls + 1`
+    /// passes.  A limit of zero disables time-slicing (default).
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, tasks are never skipped.
+    pub fn time_slice(mut self, polls: u32) -> Self {
+        Arc::make_mut(&mut self.1).time_slice = polls;
+        self
+    }
 
-    /// Drain tasks from the thread pool queue.  Should returns true if drained
-    /// at least one task.
-    fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool;
-}
+    /// Accumulate how long each task spends being polled, for finding tasks
+    /// that hog the executor with [`ExecutorStats::cpu_times()`].
+    ///
+    /// This reads the clock around every poll, so it's off by default.
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// ```rust
+    /// let executor = pasts::Executor::default().cpu_time();
+    /// let stats = executor.stats();
+    ///
+    /// executor.block_on(async move {
+    ///     pasts::time::sleep(pasts::time::Duration::from_millis(1)).await;
+    ///
+    ///     for (task, time) in stats.cpu_times() {
+    ///         println!("{task:?} was polled for {time:?}");
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, nothing is recorded.
+    #[cfg(feature = "std")]
+    pub fn cpu_time(mut self) -> Self {
+        Arc::make_mut(&mut self.1).cpu_time = true;
+        self
+    }
 
-/// Trait for implementing the parking / unparking threads.
-pub trait Park: Default + Send + Sync + 'static {
-    /// The park routine; should put the processor or thread to sleep in order
-    /// to save CPU cycles and power, until the hardware tells it to wake up.
-    fn park(&self);
+    /// Demote tasks whose polls take longer than `threshold` for `strikes`
+    /// polls in a row, so that they're polled after the other tasks, and
+    /// only on every other pass.
+    ///
+    /// This keeps latency predictable for well-behaved tasks when another
+    /// task does too much work per poll.  A demoted task is promoted again as
+    /// soon as one of its polls is under `threshold`.  Each demotion is
+    /// reported as [`Diagnostic::Demoted`] (see [`Executor::watchdog()`]).
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, tasks are never demoted.
+    #[cfg(feature = "std")]
+    pub fn demote_slow(mut self, threshold: Duration, strikes: u32) -> Self {
+        Arc::make_mut(&mut self.1).demote = Some(Demote {
+            threshold,
+            strikes: strikes.max(1),
+        });
+        self
+    }
 
-    /// Wake the processor or thread.
-    fn unpark(&self);
-}
+    /// Limit the number of threads that run closures from
+    /// [`Executor::spawn_blocking()`] at once.
+    ///
+    /// Once every thread is busy, closures wait in a queue for the next one
+    /// that's free.  Defaults to the available parallelism.
+    ///
+    /// Settings are shared with clones made after calling this method.
+    #[cfg(all(
+        feature = "std",
+        any(
+            not(feature = "web_lite"),
+            all(feature = "web", target_feature = "atomics")
+        )
+    ))]
+    pub fn blocking_threads(mut self, limit: usize) -> Self {
+        Arc::make_mut(&mut self.1).blocking = Arc::new(Threads::new(limit));
+        self
+    }
 
-#[derive(Default)]
-pub struct DefaultPool {
-    spawning_queue: Cell<Vec<LocalBoxNotify<'static>>>,
-}
+    /// Run a blocking closure on another thread, without blocking the
+    /// executor.
+    ///
+    /// The number of threads is limited by [`Executor::blocking_threads()`],
+    /// so when they're all busy, the returned future also waits for a thread
+    /// to be free.  Applications can shed load by watching
+    /// [`Executor::blocking_queue()`].
+    ///
+    /// ```rust
+    /// let executor = pasts::Executor::default().blocking_threads(2);
+    ///
+    /// executor.clone().block_on(async move {
+    ///     let sum = executor.spawn_blocking(|| (1..=10).sum::<u32>());
+    ///
+    ///     assert_eq!(sum.await, 55);
+    /// });
+    /// ```
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, only available for wasm with
+    /// atomics, where threads are started with the spawner set with
+    /// `web::thread_spawner()`.
+    #[cfg(all(
+        feature = "std",
+        any(
+            not(feature = "web_lite"),
+            all(feature = "web", target_feature = "atomics")
+        )
+    ))]
+    pub fn spawn_blocking<T, F>(&self, f: F) -> BlockingHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        blocking::spawn(&self.1.blocking, f)
+    }
 
-impl fmt::Debug for DefaultPool {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let queue = self.spawning_queue.take();
+    /// Watch the number of closures from [`Executor::spawn_blocking()`] that
+    /// are waiting for a thread.
+    #[cfg(all(
+        feature = "std",
+        any(
+            not(feature = "web_lite"),
+            all(feature = "web", target_feature = "atomics")
+        )
+    ))]
+    pub fn blocking_queue(&self) -> BlockingQueue {
+        blocking::queue(&self.1.blocking)
+    }
 
-        f.debug_struct("DefaultPool")
-            .field("spawning_queue", &queue)
-            .finish()?;
-        self.spawning_queue.set(queue);
+    /// Get a handle to this executor's counters, for diagnosing imbalance
+    /// between executors (such as [`Workers`](crate::Workers)).
+    ///
+    /// Counters are shared with every clone of this executor.
+    pub fn stats(&self) -> ExecutorStats {
+        ExecutorStats(self.1.stats.clone())
+    }
 
-        Ok(())
+    /// Decide what happens when a task panics, by calling `hook` with the
+    /// task's identifier and the panic payload.
+    ///
+    /// Without a hook, panics propagate out of [`Executor::block_on()`].
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, the hook is never called.
+    #[cfg(feature = "std")]
+    pub fn panic_hook(
+        mut self,
+        hook: impl Fn(TaskId, &(dyn Any + Send)) -> PanicPolicy
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Arc::make_mut(&mut self.1).panic_hook = Some(Arc::new(hook));
+        self
     }
-}
 
-impl Pool for DefaultPool {
-    type Park = DefaultPark;
+    /// Decide what [`Executor::block_on()`] does once it has no tasks left,
+    /// by calling `hook` each time it runs out.
+    ///
+    /// Without a hook, `block_on()` returns.  Servers whose tasks are
+    /// spawned from other threads (with [`Executor::spawn()`] on clones of an
+    /// executor whose [`Pool`] is [`Sync`]) can keep waiting for them with
+    /// [`Idle::Park`], until the hook returns [`Idle::Return`]:
+    ///
+    /// ```rust
+    /// use std::sync::{
+    ///     atomic::{AtomicBool, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// use pasts::{Executor, Idle};
+    ///
+    /// let shutdown = Arc::new(AtomicBool::new(true));
+    /// let executor = Executor::default().idle_hook({
+    ///     let shutdown = shutdown.clone();
+    ///
+    ///     move || match shutdown.load(Ordering::SeqCst) {
+    ///         true => Idle::Return,
+    ///         false => Idle::Park,
+    ///     }
+    /// });
+    ///
+    /// executor.block_on(async {});
+    /// ```
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, the hook is never called.
+    pub fn idle_hook(
+        mut self,
+        hook: impl Fn() -> Idle + Send + Sync + 'static,
+    ) -> Self {
+        Arc::make_mut(&mut self.1).idle_hook = Some(Arc::new(hook));
+        self
+    }
 
-    // Push onto queue of tasks to spawn.
-    #[inline(always)]
-    fn push(&self, task: LocalBoxNotify<'static>) {
-        let mut queue = self.spawning_queue.take();
+    /// Decide what happens to the tasks that are still running once the
+    /// executor starts shutting down (see [`Executor::on_shutdown()`]).
+    ///
+    /// By default, [`Executor::block_on()`] waits for every task, which can
+    /// hang forever if one never completes.  Libraries that run an executor
+    /// during teardown can bound the wait, or drop the tasks right away.
+    ///
+    /// Settings are shared with clones made after calling this method.
+    ///
+    /// ```rust
+    /// use pasts::{time::Duration, DrainPolicy, Executor};
+    ///
+    /// let executor = Executor::default()
+    ///     .drain_policy(DrainPolicy::Timeout(Duration::from_millis(10)));
+    ///
+    /// executor.spawn_local(core::future::pending());
+    /// executor.block_on(async {});
+    /// ```
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, tasks are always left running.
+    #[cfg(feature = "std")]
+    pub fn drain_policy(mut self, policy: DrainPolicy) -> Self {
+        Arc::make_mut(&mut self.1).drain = policy;
+        self
+    }
 
-        queue.push(task);
-        self.spawning_queue.set(queue);
+    /// Set whether tasks spawned from this handle keep the executor running.
+    ///
+    /// Tasks spawned from a handle with `keep_alive(false)` are daemons:
+    /// once the main future completes, [`Executor::block_on()`] returns as
+    /// soon as only daemons are left, dropping them.  Handles are tagged
+    /// separately, so the rest of the clones still keep the executor alive.
+    ///
+    /// ```rust
+    /// use pasts::Executor;
+    ///
+    /// let executor = Executor::default();
+    /// let daemon = executor.clone().keep_alive(false);
+    ///
+    /// daemon.spawn_local(async {
+    ///     // Background work that never completes
+    ///     core::future::pending::<()>().await;
+    /// });
+    /// executor.block_on(async {});
+    /// ```
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, tasks are always left running.
+    #[cfg(feature = "std")]
+    pub fn keep_alive(mut self, keep_alive: bool) -> Self {
+        Arc::make_mut(&mut self.1).detached = !keep_alive;
+        self
     }
 
-    // Drain from queue of tasks to spawn.
+    /// Block on a future and return it's result.
+    ///
+    /// Once `f` completes, the executor starts shutting down (see
+    /// [`Executor::on_shutdown()`]), and keeps running until the rest of the
+    /// tasks complete, or as set by [`Executor::drain_policy()`].
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, spawns task and returns
+    /// immediately instead of blocking.
     #[inline(always)]
-    fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool {
-        let mut queue = self.spawning_queue.take();
-        let mut drained = queue.drain(..).peekable();
-        let has_drained = drained.peek().is_some();
+    pub fn block_on(self, f: impl Future<Output = ()> + 'static) {
+        let f = self.main(f);
 
-        tasks.extend(drained);
-        self.spawning_queue.set(queue);
+        #[cfg(feature = "web_lite")]
+        web::spawn_local(f);
 
-        has_drained
-    }
-}
+        #[cfg(all(not(feature = "web_lite"), feature = "std"))]
+        let _ = block_on(f, &self.0, &self.1, None, &self.2, &self.3);
 
-#[cfg(not(feature = "std"))]
-#[derive(Copy, Clone, Debug, Default)]
-pub struct DefaultPark;
+        #[cfg(all(not(feature = "web_lite"), not(feature = "std")))]
+        let _ = block_on(f, &self.0, &self.1, &self.3);
+    }
 
-#[cfg(feature = "std")]
-#[derive(Debug)]
-pub struct DefaultPark(std::sync::atomic::AtomicBool, std::thread::Thread);
+    /// Block on the future returned from `f`, which is passed a [`Spawner`]
+    /// for spawning sibling tasks on this executor.
+    ///
+    /// Like [`Executor::block_on()`], but without cloning the executor into
+    /// the future just to spawn tasks.
+    ///
+    /// ```rust
+    /// use std::{cell::Cell, rc::Rc};
+    ///
+    /// let ran = Rc::new(Cell::new(false));
+    ///
+    /// pasts::Executor::default().block_on_with({
+    ///     let ran = ran.clone();
+    ///
+    ///     |spawner| async move {
+    ///         spawner.spawn_local(async move { ran.set(true) });
+    ///     }
+    /// });
+    /// assert!(ran.get());
+    /// ```
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, spawns task and returns
+    /// immediately instead of blocking.
+    #[inline(always)]
+    pub fn block_on_with<F>(self, f: impl FnOnce(Spawner<P>) -> F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let f = f(Spawner(self.clone()));
 
-#[cfg(feature = "std")]
-impl Default for DefaultPark {
-    fn default() -> Self {
-        Self(
-            std::sync::atomic::AtomicBool::new(true),
-            std::thread::current(),
-        )
+        self.block_on(f);
     }
-}
 
-impl Park for DefaultPark {
-    // Park the current thread.
+    /// Block on a future, giving up once `timeout` has elapsed.
+    ///
+    /// Returns [`TimedOut`] with the number of tasks that haven't completed,
+    /// which are dropped.  Useful for bounding hangs in tests.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, spawns task and returns `Ok(())`
+    /// immediately instead of blocking.  Custom [`Park`] implementations
+    /// that don't override [`Park::park_timeout()`] may block past the
+    /// timeout.
+    #[cfg(feature = "std")]
     #[inline(always)]
-    fn park(&self) {
-        // Only park on std; There is no portable parking for no-std.
-        #[cfg(feature = "std")]
-        while self.0.swap(true, std::sync::atomic::Ordering::SeqCst) {
-            std::thread::park();
+    pub fn block_on_timeout(
+        self,
+        f: impl Future<Output = ()> + 'static,
+        timeout: Duration,
+    ) -> Result<(), TimedOut> {
+        let f = self.main(f);
+
+        #[cfg(feature = "web_lite")]
+        {
+            let _ = timeout;
+            web::spawn_local(f);
+            Ok(())
         }
 
-        // Hint at spin loop to possibly short sleep on no-std to save CPU time.
-        #[cfg(not(feature = "std"))]
-        core::hint::spin_loop();
+        #[cfg(not(feature = "web_lite"))]
+        block_on(
+            f,
+            &self.0,
+            &self.1,
+            Some(Instant::now() + timeout),
+            &self.2,
+            &self.3,
+        )
     }
 
-    // Unpark the parked thread
-    #[inline(always)]
-    fn unpark(&self) {
-        // Only unpark on std; Since no-std doesn't park, it's already unparked.
-        #[cfg(feature = "std")]
-        if self.0.swap(false, std::sync::atomic::Ordering::SeqCst) {
-            self.1.unpark();
-        }
+    /// Get a future that completes once the executor starts shutting down.
+    ///
+    /// Shutdown starts when the future passed to [`Executor::block_on()`]
+    /// completes, or when [`Executor::shutdown()`] is called.  Background
+    /// tasks can wait for this to wind down gracefully, rather than keeping
+    /// the executor running forever, without being handed a token.
+    ///
+    /// ```rust
+    /// let executor = pasts::Executor::default();
+    /// let shutdown = executor.on_shutdown();
+    ///
+    /// executor.spawn_local(async move {
+    ///     shutdown.await;
+    ///     println!("Closing connections");
+    /// });
+    /// executor.block_on(async {
+    ///     println!("Main task complete");
+    /// });
+    /// ```
+    pub fn on_shutdown(&self) -> OnShutdown {
+        OnShutdown(self.2.clone())
     }
-}
 
-struct Unpark<P: Park>(P);
+    /// Start shutting down, waking the futures from
+    /// [`Executor::on_shutdown()`].
+    ///
+    /// This is only needed when the executor isn't run with
+    /// [`Executor::block_on()`] (for example, with [`Ticker::tick()`]).
+    pub fn shutdown(&self) {
+        self.2.fire();
+    }
 
-impl<P: Park> Wake for Unpark<P> {
-    #[inline(always)]
-    fn wake(self: Arc<Self>) {
-        self.0.unpark();
+    // Wrap the main future, so that shutdown starts when it completes.
+    fn main(
+        &self,
+        f: impl Future<Output = ()> + 'static,
+    ) -> impl Future<Output = ()> + 'static {
+        let shutdown = self.2.clone();
+
+        shutdown.reset();
+
+        async move {
+            f.await;
+            shutdown.fire();
+        }
     }
 
-    #[inline(always)]
-    fn wake_by_ref(self: &Arc<Self>) {
-        self.0.unpark();
+    /// Get a [`Ticker`] for running this executor's tasks inside of an
+    /// external event loop, without blocking.
+    ///
+    /// Tasks spawned on this executor are started by the next
+    /// [`Ticker::tick()`], and then stay on the ticker's thread.
+    pub fn ticker(&self) -> Ticker<P> {
+        Ticker {
+            executor: self.clone(),
+            #[cfg(not(feature = "web_lite"))]
+            run: Run::new(),
+        }
     }
 }
 
-#[cfg(not(feature = "web"))]
-fn block_on<P: Pool>(f: impl Future<Output = ()> + 'static, pool: &Arc<P>) {
-    // Fuse main task
-    let f: LocalBoxNotify<'_> = Box::pin(f.fuse());
+/// Runs an [`Executor`]'s tasks inside of an external event loop.
+///
+/// Created with [`Executor::ticker()`].  Tasks started by a ticker are kept by
+/// it between ticks, so unlike the executor, it can't be sent to other
+/// threads.  Dropping the ticker drops its tasks.
+pub struct Ticker<P: Pool = DefaultPool> {
+    #[cfg_attr(feature = "web_lite", allow(dead_code))]
+    executor: Executor<P>,
+    #[cfg(not(feature = "web_lite"))]
+    run: Run<P::Park>,
+}
+
+impl<P: Pool> fmt::Debug for Ticker<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ticker").finish_non_exhaustive()
+    }
+}
 
-    // Set up the notify
-    let tasks = &mut Vec::new();
+impl<P: Pool> Ticker<P> {
+    /// Run tasks that have been woken, and start spawned tasks, without
+    /// blocking.
+    ///
+    /// This is for running pasts inside of an external event loop (such as
+    /// a GUI toolkit's run loop), which calls `tick()` whenever pasts has
+    /// work to do.  Tasks spawned from outside of the executor's tasks are
+    /// started on the next tick.  Use [`Executor::wake_hook()`] to find out
+    /// when a task is woken, so that the event loop can schedule another
+    /// tick, and [`Ticker::waker()`] to schedule one from an outside
+    /// callback.
+    ///
+    /// Returns [`Ready`] once there are no tasks left to run.
+    ///
+    /// ```rust
+    #[doc = include_str!("../examples/tick.rs")]
+    /// ```
+    /// 
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, tasks are run by the browser's
+    /// event loop, so this does nothing and returns [`Ready`].
+    pub fn tick(&mut self) -> Poll {
+        #[cfg(feature = "web_lite")]
+        {
+            Ready(())
+        }
 
-    // Set up the park, waker, and context.
-    let parky = Arc::new(Unpark(<P as Pool>::Park::default()));
-    let waker = parky.clone().into();
-    let tasky = &mut Task::from_waker(&waker);
+        #[cfg(not(feature = "web_lite"))]
+        {
+            let Executor(ref pool, ref config, ..) = self.executor;
+            let run = &mut self.run;
+            let mut woken = run.parker.unparked();
 
-    // Spawn main task
-    tasks.push(f);
+            // Keep going until spawned tasks have had their first poll
+            while run.start(&**pool, config) || woken {
+                run.poll(config);
+                woken = false;
+            }
 
-    // Run the set of futures to completion.
-    while !tasks.is_empty() {
-        // Poll the set of futures
-        let poll = Pin::new(tasks.as_mut_slice()).poll_next(tasky);
-        // If no tasks have completed, then park
-        let Ready((task_index, ())) = poll else {
-            // Initiate execution of any spawned tasks - if no new tasks, park
-            if !pool.drain(tasks) {
-                parky.0.park();
+            if run.tasks.is_empty() {
+                Ready(())
+            } else {
+                Pending
             }
-            continue;
-        };
+        }
+    }
+
+    /// Get a waker that schedules running this ticker's tasks on the next
+    /// [`Ticker::tick()`].
+    ///
+    /// The waker can be sent to other threads, and woken from callbacks of
+    /// an external event loop.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, waking does nothing.
+    pub fn waker(&self) -> Waker {
+        #[cfg(feature = "web_lite")]
+        {
+            Waker::noop().clone()
+        }
+
+        #[cfg(not(feature = "web_lite"))]
+        self.run.parker.waker()
+    }
+}
 
-        // Task has completed
-        tasks.swap_remove(task_index);
-        // Drain any spawned tasks into the pool
-        pool.drain(tasks);
+/// Error returned from [`Executor::block_on_timeout()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimedOut {
+    /// How many tasks hadn't completed
+    pub tasks: usize,
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out with {} pending tasks", self.tasks)
     }
 }
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimedOut {}
+
+impl<P: Pool> Executor<P> {
+    /// Spawn a [`LocalBoxNotify`] on this executor.
+    ///
+    /// Execution of the [`LocalBoxNotify`] will halt after the first poll that
+    /// returns [`Ready`].
+    #[inline(always)]
+    pub fn spawn_notify(&self, n: LocalBoxNotify<'static>) {
+        if let Some(ref hook) = self.1.size_hook {
+            hook(size_of_val(&*n));
+        }
+
+        // Convert the notify into a future and spawn on the browser's event
+        // loop
+        #[cfg(feature = "web_lite")]
+        web::spawn_local(async move {
+            let mut n = n;
+
+            n.next().await;
+        });
+
+        // Tag tasks that don't keep the executor alive
+        #[cfg(all(not(feature = "web_lite"), feature = "std"))]
+        let n = if self.1.detached {
+            Box::pin(Detached(n))
+        } else {
+            n
+        };
+
+        // Push the notify onto the pool.
+        #[cfg(not(feature = "web_lite"))]
+        self.0.push(n);
+    }
+
+    /// Spawn a [`Send`] future on this executor.
+    ///
+    /// The future is fused and boxed as needed, so the same code works with
+    /// and without the _`std`_ and _`web`_ features.  It's pushed onto the
+    /// pool with [`Pool::push_send()`], and the executor is woken to start
+    /// it, so with a pool that's [`Sync`], this can be called from clones of
+    /// the executor on other threads.  Use [`Executor::spawn_local()`] for
+    /// futures that aren't [`Send`]:
+    ///
+    /// ```rust,compile_fail
+    /// let executor = pasts::Executor::default();
+    /// let shared = std::rc::Rc::new(());
+    ///
+    /// executor.spawn(async move {
+    ///     let _shared = shared;
+    /// });
+    /// ```
+    #[inline(always)]
+    pub fn spawn(&self, f: impl Future<Output = ()> + Send + 'static) {
+        // Spawn the future on the browser's event loop
+        #[cfg(feature = "web_lite")]
+        self.spawn_local(f);
+
+        #[cfg(not(feature = "web_lite"))]
+        self.spawn_send_notify(Box::pin(f.fuse()));
+    }
+
+    // Push a `Send` task onto the pool, and wake the executor to start it.
+    fn spawn_send_notify(&self, n: BoxNotify<'static>) {
+        if let Some(ref hook) = self.1.size_hook {
+            hook(size_of_val(&*n));
+        }
+
+        #[cfg(feature = "web_lite")]
+        web::spawn_local(async move {
+            let mut n = n;
+
+            n.next().await;
+        });
+
+        // Tag tasks that don't keep the executor alive
+        #[cfg(all(not(feature = "web_lite"), feature = "std"))]
+        let n = if self.1.detached {
+            Box::pin(Detached(n))
+        } else {
+            n
+        };
+
+        #[cfg(not(feature = "web_lite"))]
+        {
+            self.0.push_send(n);
+            self.3.wake();
+        }
+    }
+
+    /// Spawn a future that isn't [`Send`] on this executor.
+    ///
+    /// With the default pool, the executor is neither [`Send`] nor [`Sync`],
+    /// so this can only be called on the thread that owns the executor.
+    #[inline(always)]
+    pub fn spawn_local(&self, f: impl Future<Output = ()> + 'static) {
+        // Spawn the future on the browser's event loop
+        #[cfg(feature = "web_lite")]
+        {
+            if let Some(ref hook) = self.1.size_hook {
+                hook(size_of_val(&f));
+            }
+
+            web::spawn_local(f);
+        }
+
+        // Fuse the future, box it, and push it onto the pool.
+        #[cfg(not(feature = "web_lite"))]
+        self.spawn_notify(Box::pin(f.fuse()));
+    }
+
+    /// Spawn a future that isn't [`Send`] on this executor, unless the pool is
+    /// full.
+    ///
+    /// If [`Pool::is_full()`] returns true, the future is handed back without
+    /// allocating, so that it can be retried later or dropped.
+    ///
+    /// ```rust
+    /// let executor = pasts::Executor::default();
+    ///
+    /// assert!(executor.try_spawn(async {}).is_ok());
+    /// ```
+    pub fn try_spawn<F>(&self, f: F) -> Result<(), F>
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        if self.0.is_full() {
+            return Err(f);
+        }
+
+        self.spawn_local(f);
+        Ok(())
+    }
+
+    /// Poll a future once on the caller's stack, and only spawn it on this
+    /// executor if it didn't complete.
+    ///
+    /// Cheap tasks that complete right away never reach the pool, and tasks
+    /// that start I/O do so immediately, rather than the next time the
+    /// executor gets around to its spawned tasks.  The first poll doesn't
+    /// call any of the executor's hooks, and the task is polled again once
+    /// started, so that it can register a waker from the executor.
+    ///
+    /// ```rust
+    /// use std::{cell::Cell, rc::Rc};
+    ///
+    /// let executor = pasts::Executor::default();
+    /// let done = Rc::new(Cell::new(false));
+    ///
+    /// executor.spawn_eager({
+    ///     let done = done.clone();
+    ///
+    ///     async move { done.set(true) }
+    /// });
+    /// assert!(done.get());
+    /// ```
+    pub fn spawn_eager(&self, f: impl Future<Output = ()> + 'static) {
+        let mut task: LocalBoxNotify<'static> = Box::pin(f.fuse());
+        let mut t = Task::from_waker(Waker::noop());
+
+        if task.as_mut().poll_next(&mut t).is_pending() {
+            self.spawn_notify(task);
+        }
+    }
+
+    /// Box and spawn a future on this executor.
+    ///
+    /// Same as [`Executor::spawn_local()`].
+    #[inline(always)]
+    pub fn spawn_boxed(&self, f: impl Future<Output = ()> + 'static) {
+        self.spawn_local(f);
+    }
+
+    /// Box and spawn a future on this executor, returning a [`JoinHandle`]
+    /// to its output.
+    ///
+    /// Like [`Executor::spawn_local()`], the future doesn't need to be
+    /// [`Send`].
+    #[inline(always)]
+    pub fn spawn_with_handle<F>(&self, f: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        let (task, handle) = joinable(f);
+
+        self.spawn_local(task);
+        handle
+    }
+
+    /// Spawn a future on this executor like
+    /// [`Executor::spawn_with_handle()`], calling `cancel` if the task is
+    /// dropped before it completes.
+    ///
+    /// This happens when the task is cancelled through its [`JoinHandle`],
+    /// or when the executor shuts down with the task still pending (for
+    /// example, after [`Executor::block_on_timeout()`] times out), so that
+    /// resources owned by the task can be released deterministically.
+    ///
+    /// ```rust
+    /// use std::{cell::Cell, rc::Rc};
+    ///
+    /// let executor = pasts::Executor::default();
+    /// let closed = Rc::new(Cell::new(false));
+    /// let handle = executor.spawn_with_cleanup(core::future::pending::<()>(), {
+    ///     let closed = closed.clone();
+    ///
+    ///     move || closed.set(true)
+    /// });
+    ///
+    /// handle.cancel();
+    /// executor.block_on(async {});
+    /// assert!(closed.get());
+    /// ```
+    #[inline(always)]
+    pub fn spawn_with_cleanup<F>(
+        &self,
+        f: F,
+        cancel: impl FnOnce() + 'static,
+    ) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        self.spawn_with_handle(on_cancel(f, cancel))
+    }
+
+    /// Spawn a future on this executor like
+    /// [`Executor::spawn_with_handle()`], returning a panic while polling it
+    /// as an error from the [`JoinHandle`].
+    ///
+    /// The panic is caught where the executor polls the task, so the future
+    /// doesn't need to be wrapped in [`AssertUnwindSafe`].  Other tasks keep
+    /// running, regardless of the [`Executor::panic_hook()`].
+    ///
+    /// [`AssertUnwindSafe`]: std::panic::AssertUnwindSafe
+    ///
+    /// ```rust
+    /// let executor = pasts::Executor::default();
+    /// let handle = executor.spawn_catching(async { panic!("oops") });
+    ///
+    /// executor.block_on(async move {
+    ///     let error = handle.await.unwrap().unwrap_err();
+    ///
+    ///     assert_eq!(error.message(), Some("oops"));
+    /// });
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn spawn_catching<F>(
+        &self,
+        f: F,
+    ) -> JoinHandle<Result<F::Output, Panicked>>
+    where
+        F: Future + 'static,
+    {
+        self.spawn_with_handle(catch_unwind(f))
+    }
+
+    /// Spawn a notify that lives for the rest of the program on this
+    /// executor, without moving it.
+    ///
+    /// The notify's state isn't allocated by the executor, so it can be kept
+    /// in static storage (for example, from a crate like `static_cell` on
+    /// targets without an allocator to spare).  The pool still stores a small
+    /// globally-allocated handle to the task.  Execution halts after the
+    /// first poll that returns [`Ready`].
+    ///
+    /// ```rust
+    /// use pasts::{prelude::*, Executor};
+    ///
+    /// let task = Box::leak(Box::new(async {}.fuse()));
+    /// let executor = Executor::default();
+    ///
+    /// executor.spawn_static(Pin::static_mut(task));
+    /// executor.block_on(async {});
+    /// ```
+    #[inline(always)]
+    pub fn spawn_static(&self, n: Pin<&'static mut dyn Notify<Event = ()>>) {
+        self.spawn_notify(Box::pin(n));
+    }
+
+    /// Spawn a future on this executor, allocating it with `alloc`.
+    ///
+    /// Useful for allocating tasks from an arena, to avoid global allocator
+    /// spikes in soft real-time loops.  The pool still stores a small
+    /// globally-allocated handle to each task.
+    #[cfg(feature = "allocator_api")]
+    #[inline(always)]
+    pub fn spawn_in<A>(&self, f: impl Future<Output = ()> + 'static, alloc: A)
+    where
+        A: core::alloc::Allocator + 'static,
+    {
+        self.spawn_local(Box::pin_in(f, alloc));
+    }
+
+    /// Spawn a future on this executor, returning an error rather than
+    /// panicking if the task can't be allocated.
+    ///
+    /// Both the task's box and its slot in the pool's queue (see
+    /// [`Pool::try_reserve()`]) are allocated fallibly.  Bookkeeping once the
+    /// executor starts the task may still allocate infallibly.
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// let executor = pasts::Executor::default();
+    ///
+    /// executor.try_spawn_alloc(async {}).unwrap();
+    /// executor.block_on(async {});
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    pub fn try_spawn_alloc(
+        &self,
+        f: impl Future<Output = ()> + 'static,
+    ) -> Result<(), core::alloc::AllocError> {
+        self.0.try_reserve().map_err(|_| core::alloc::AllocError)?;
+        self.spawn_notify(Box::into_pin(Box::try_new(f.fuse())?));
+        Ok(())
+    }
+
+    /// Spawn a task on this executor, which starts over with a new future
+    /// from `f` when polled again after panicking.
+    ///
+    /// Use with [`PanicPolicy::RestartTask`] to keep a task alive.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, the task is never restarted.
+    #[inline(always)]
+    pub fn spawn_restartable<F>(&self, mut f: impl FnMut() -> F + 'static)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        #[cfg(feature = "web_lite")]
+        web::spawn_local(f());
+
+        #[cfg(not(feature = "web_lite"))]
+        self.spawn_notify(Box::pin(Restartable {
+            task: Box::pin(f()),
+            make: f,
+            polling: false,
+        }));
+    }
+}
+
+impl<P: Pool + 'static> Executor<P> {
+    /// Get a type-erased [`Spawn`] handle to this executor, for passing to
+    /// code that shouldn't be generic over the [`Pool`].
+    pub fn spawner(&self) -> Arc<dyn Spawn> {
+        Arc::new(self.clone())
+    }
+}
+
+/// Object-safe interface for spawning tasks, implemented by [`Executor`].
+///
+/// Libraries can take a `&dyn Spawn` or an `Arc<dyn Spawn>` to accept any
+/// executor without being generic over its [`Pool`].
+pub trait Spawn {
+    /// Spawn a boxed task that isn't [`Send`].
+    fn spawn_local(&self, task: LocalBoxNotify<'static>);
+
+    /// Spawn a boxed task that is [`Send`].
+    ///
+    /// By default, this spawns the task with [`Spawn::spawn_local()`].
+    fn spawn_send(&self, task: BoxNotify<'static>) {
+        self.spawn_local(task);
+    }
+}
+
+impl<P: Pool> Spawn for Executor<P> {
+    fn spawn_local(&self, task: LocalBoxNotify<'static>) {
+        self.spawn_notify(task);
+    }
+
+    fn spawn_send(&self, task: BoxNotify<'static>) {
+        self.spawn_send_notify(task);
+    }
+}
+
+impl<S: Spawn + ?Sized> Spawn for &S {
+    fn spawn_local(&self, task: LocalBoxNotify<'static>) {
+        (**self).spawn_local(task);
+    }
+
+    fn spawn_send(&self, task: BoxNotify<'static>) {
+        (**self).spawn_send(task);
+    }
+}
+
+impl<S: Spawn + ?Sized> Spawn for Arc<S> {
+    fn spawn_local(&self, task: LocalBoxNotify<'static>) {
+        (**self).spawn_local(task);
+    }
+
+    fn spawn_send(&self, task: BoxNotify<'static>) {
+        (**self).spawn_send(task);
+    }
+}
+
+/// Handle for spawning tasks on an [`Executor`].
+///
+/// Passed to the future started with [`Executor::block_on_with()`].  Unlike
+/// the executor itself, a spawner can't block on futures.
+pub struct Spawner<P: Pool = DefaultPool>(Executor<P>);
+
+impl<P: Pool> Clone for Spawner<P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<P: Pool + fmt::Debug> fmt::Debug for Spawner<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Spawner").field(&self.0 .0).finish()
+    }
+}
+
+impl<P: Pool> Spawner<P> {
+    /// Spawn a [`Send`] future on the executor.
+    ///
+    /// See [`Executor::spawn()`].
+    #[inline(always)]
+    pub fn spawn(&self, f: impl Future<Output = ()> + Send + 'static) {
+        self.0.spawn(f);
+    }
+
+    /// Spawn a future that isn't [`Send`] on the executor.
+    ///
+    /// See [`Executor::spawn_local()`].
+    #[inline(always)]
+    pub fn spawn_local(&self, f: impl Future<Output = ()> + 'static) {
+        self.0.spawn_local(f);
+    }
+
+    /// Spawn a future on the executor, returning a [`JoinHandle`] to its
+    /// output.
+    ///
+    /// See [`Executor::spawn_with_handle()`].
+    #[inline(always)]
+    pub fn spawn_with_handle<F>(&self, f: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        self.0.spawn_with_handle(f)
+    }
+}
+
+impl<P: Pool> Spawn for Spawner<P> {
+    fn spawn_local(&self, task: LocalBoxNotify<'static>) {
+        self.0.spawn_notify(task);
+    }
+
+    fn spawn_send(&self, task: BoxNotify<'static>) {
+        self.0.spawn_send_notify(task);
+    }
+}
+
+#[cfg(feature = "async_main")]
+impl<P: Pool> async_main::Spawn for Executor<P> {
+    #[inline(always)]
+    fn spawn_local(&self, f: impl Future<Output = ()> + 'static) {
+        self.spawn_local(f);
+    }
+}
+
+// A task that starts over when polled again after panicking.
+#[cfg(not(feature = "web_lite"))]
+struct Restartable<F, M> {
+    task: Pin<Box<F>>,
+    make: M,
+    polling: bool,
+}
+
+#[cfg(not(feature = "web_lite"))]
+impl<F, M> Unpin for Restartable<F, M> {}
+
+#[cfg(not(feature = "web_lite"))]
+impl<F, M> Notify for Restartable<F, M>
+where
+    F: Future<Output = ()>,
+    M: FnMut() -> F,
+{
+    type Event = ();
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        let this = self.get_mut();
+
+        // Still marked as polling, so the last poll didn't return
+        if core::mem::replace(&mut this.polling, true) {
+            this.task = Box::pin((this.make)());
+        }
+
+        #[cfg(feature = "std")]
+        let outer = RESTARTABLE.replace(true);
+        let poll = this.task.as_mut().poll(t);
+
+        #[cfg(feature = "std")]
+        RESTARTABLE.set(outer);
+        this.polling = false;
+        poll
+    }
+}
+
+/// Storage for a task pool.
+///
+/// # Implementing `Pool` For A Custom Executor
+/// This example shows how to create a custom single-threaded executor using
+/// [`Executor::new()`].
+///
+/// ```rust
+#[doc = include_str!("../examples/pool.rs")]
+/// ```
+pub trait Pool {
+    /// Type that handles the sleeping / waking of the executor.
+    type Park: Park;
+
+    /// Push a task into the thread pool queue.
+    fn push(&self, task: LocalBoxNotify<'static>);
+
+    /// Push a [`Send`] task into the thread pool queue, from any thread that
+    /// has a clone of the executor.
+    ///
+    /// Used by [`Executor::spawn()`], which wakes the executor afterwards.
+    /// The default implementation calls [`Pool::push()`].
+    fn push_send(&self, task: BoxNotify<'static>) {
+        self.push(task);
+    }
+
+    /// Drain tasks from the thread pool queue.  Should returns true if drained
+    /// at least one task.
+    fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool;
+
+    /// Return true if the queue is full, so that [`Executor::try_spawn()`]
+    /// should reject new tasks.
+    ///
+    /// The default implementation never fills up.  [`Pool::push()`] is still
+    /// called when full by the other spawn methods.
+    fn is_full(&self) -> bool {
+        false
+    }
+
+    /// Try to reserve room in the queue for one more task, without panicking
+    /// if the allocation fails.
+    ///
+    /// Used by [`Executor::try_spawn_alloc()`].  The default implementation
+    /// doesn't reserve anything.
+    fn try_reserve(&self) -> Result<(), TryReserveError> {
+        Ok(())
+    }
+}
+
+/// Trait for implementing the parking / unparking threads.
+pub trait Park: Default + Send + Sync + 'static {
+    /// The park routine; should put the processor or thread to sleep in order
+    /// to save CPU cycles and power, until the hardware tells it to wake up.
+    fn park(&self);
+
+    /// Wake the processor or thread.
+    fn unpark(&self);
+
+    /// Like [`Park::park()`], but may return early after `timeout` has
+    /// elapsed.
+    ///
+    /// The default implementation ignores the timeout.
+    fn park_timeout(&self, _timeout: Duration) {
+        self.park();
+    }
+}
+
+/// What the executor does when a task panics.
+///
+/// Returned from the hook set with [`Executor::panic_hook()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PanicPolicy {
+    /// Continue the panic out of [`Executor::block_on()`], dropping all tasks
+    Abort,
+    /// Drop the task that panicked, and keep running the others
+    CancelTask,
+    /// Keep the task that panicked, and poll it again
+    ///
+    /// Tasks spawned with [`Executor::spawn_restartable()`] start over from
+    /// the beginning; other tasks can't, so they're cancelled instead.
+    RestartTask,
+}
+
+/// What the executor does with tasks that are still running once it starts
+/// shutting down.
+///
+/// Set with [`Executor::drain_policy()`].
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DrainPolicy {
+    /// Keep running until every task completes
+    #[default]
+    Wait,
+    /// Keep running until every task completes, or until the duration has
+    /// passed since shutdown started, and then drop the rest
+    Timeout(Duration),
+    /// Drop the rest of the tasks right away
+    Cancel,
+}
+
+/// What the executor does once it has no tasks left.
+///
+/// Returned from the hook set with [`Executor::idle_hook()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Idle {
+    /// Return from [`Executor::block_on()`]
+    Return,
+    /// Park until unparked (such as by a task spawned with
+    /// [`Executor::spawn()`] from another thread), then start any spawned
+    /// tasks, or call the hook again
+    Park,
+}
+
+/// Identifier for a task running on an [`Executor`].
+///
+/// Tasks are numbered in the order they start executing, so the future
+/// passed to the first [`Executor::block_on()`] of a new executor is task 0.
+/// Identifiers are unique across an executor and its clones, including
+/// nested calls to [`Executor::block_on()`] and [`Ticker::tick()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(u64);
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task {}", self.0)
+    }
+}
+
+/// Diagnostic reported by [`Executor::watchdog()`].
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub enum Diagnostic {
+    /// A single poll of a task blocked the executor for `elapsed`
+    SlowPoll {
+        /// The task that was polled
+        task: TaskId,
+        /// How long the poll took
+        elapsed: Duration,
+    },
+    /// The executor has been parked for `parked` with `tasks` tasks pending,
+    /// none of which have registered a waker
+    Stall {
+        /// How long the executor was parked for
+        parked: Duration,
+        /// How many tasks haven't completed yet
+        tasks: usize,
+    },
+    /// A task returned [`Pending`] twice in a row without holding onto a
+    /// waker or waking itself, and may never be polled again (only detected
+    /// in debug builds)
+    LostWaker(TaskId),
+    /// A nested [`Executor::block_on()`] is parking while tasks of an
+    /// enclosing [`Executor::block_on()`] on the same thread have been woken;
+    /// those tasks can't run until the nested call returns, which may
+    /// deadlock (only detected with feature _`std`_).
+    ///
+    /// Without a hook set by [`Executor::watchdog()`], this panics.
+    Reentrant {
+        /// How many calls to [`Executor::block_on()`] are on the stack
+        depth: usize,
+    },
+    /// The executor is about to park while no task has a registered waker, so
+    /// nothing may ever wake it up again (only detected with feature _`std`_).
+    ///
+    /// Wakes that don't go through a task's waker (such as a [`RawWake`]
+    /// dispatched from a custom [`Park`], or a task spawned from another
+    /// thread) can't be seen, so this may be reported when the executor will
+    /// be woken after all.  A hook set by [`Executor::watchdog()`] can panic
+    /// on it to fail fast.
+    ///
+    /// [`RawWake`]: crate::RawWake
+    Deadlock {
+        /// How many tasks haven't completed yet
+        tasks: usize,
+    },
+    /// A task was demoted for polling slowly too many times in a row (see
+    /// [`Executor::demote_slow()`])
+    Demoted(TaskId),
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SlowPoll { task, elapsed } => {
+                write!(f, "{task} blocked the executor for {elapsed:?}")
+            }
+            Self::Stall { parked, tasks } => write!(
+                f,
+                "executor parked for {parked:?} with {tasks} pending tasks",
+            ),
+            Self::LostWaker(task) => write!(
+                f,
+                "{task} returned `Pending` without registering a waker",
+            ),
+            Self::Reentrant { depth } => write!(
+                f,
+                "nested `block_on()` (depth {depth}) parked while an \
+                 enclosing executor has woken tasks",
+            ),
+            Self::Deadlock { tasks } => write!(
+                f,
+                "executor parked with {tasks} pending tasks and no wakers",
+            ),
+            Self::Demoted(task) => {
+                write!(f, "{task} was demoted for polling slowly")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Config {
+    #[cfg(feature = "std")]
+    watchdog: Option<Watchdog>,
+    hooks: Option<PollHooks>,
+    wake_hook: Option<Arc<dyn Fn(TaskId) + Send + Sync>>,
+    size_hook: Option<Arc<dyn Fn(usize) + Send + Sync>>,
+    #[cfg_attr(feature = "web_lite", allow(dead_code))]
+    park_hook: Option<Arc<dyn Fn() + Send + Sync>>,
+    #[cfg_attr(feature = "web_lite", allow(dead_code))]
+    idle_hook: Option<Arc<dyn Fn() -> Idle + Send + Sync>>,
+    #[cfg(feature = "std")]
+    panic_hook: Option<PanicHook>,
+    time_slice: u32,
+    #[cfg(feature = "std")]
+    cpu_time: bool,
+    #[cfg(feature = "std")]
+    demote: Option<Demote>,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "web_lite", allow(dead_code))]
+    drain: DrainPolicy,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "web_lite", allow(dead_code))]
+    detached: bool,
+    stats: Arc<Counters>,
+    #[cfg(all(
+        feature = "std",
+        any(
+            not(feature = "web_lite"),
+            all(feature = "web", target_feature = "atomics")
+        )
+    ))]
+    blocking: Arc<Threads>,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Config")
+    }
+}
+
+#[cfg(not(feature = "web_lite"))]
+impl Config {
+    // Poll a task, calling hooks and timing it if anything needs the time,
+    // which is returned along with the poll.
+    #[inline(always)]
+    fn poll(
+        &self,
+        id: TaskId,
+        task: &mut LocalBoxNotify<'static>,
+        t: &mut Task<'_>,
+    ) -> (Poll, Option<Duration>) {
+        // Calls the after hook once done, even if the poll panics
+        let _after = self.hooks.as_ref().map(|hooks| {
+            (hooks.0)(id);
+            AfterPoll(hooks, id)
+        });
+
+        #[cfg(feature = "std")]
+        let timed = self.watchdog.is_some() || self.cpu_time;
+        #[cfg(feature = "std")]
+        let (poll, elapsed) = if timed || self.demote.is_some() {
+            let start = Instant::now();
+            let poll = task.as_mut().poll_next(t);
+            let elapsed = start.elapsed();
+
+            if let Some(ref watchdog) = self.watchdog {
+                if elapsed > watchdog.poll {
+                    self.report(Diagnostic::SlowPoll { task: id, elapsed });
+                }
+            }
+
+            if self.cpu_time {
+                self.stats.busy(id, elapsed);
+            }
+
+            (poll, Some(elapsed))
+        } else {
+            (task.as_mut().poll_next(t), None)
+        };
+
+        #[cfg(not(feature = "std"))]
+        let (poll, elapsed) = (task.as_mut().poll_next(t), None);
+
+        (poll, elapsed)
+    }
+
+    // Park the executor, timing it if the watchdog is enabled.
+    #[inline(always)]
+    fn park(
+        &self,
+        park: &impl Park,
+        tasks: usize,
+        #[cfg(feature = "std")] registered: bool,
+        #[cfg(feature = "std")] deadline: Option<Instant>,
+    ) {
+        lifecycle!("parking with {tasks} pending tasks");
+
+        if let Some(ref hook) = self.park_hook {
+            hook();
+        }
+
+        #[cfg(feature = "std")]
+        {
+            let limit = self.watchdog.as_ref().map(|watchdog| watchdog.park);
+            let remaining = deadline.map(|deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            let timeout = match (limit, remaining) {
+                (Some(limit), Some(remaining)) => Some(limit.min(remaining)),
+                (limit, remaining) => limit.or(remaining),
+            };
+
+            if let Some(timeout) = timeout {
+                let start = Instant::now();
+
+                park.park_timeout(timeout);
+
+                let parked = start.elapsed();
+
+                // Parking while idle, or while tasks wait on something that
+                // holds their waker, isn't a stall
+                if tasks != 0
+                    && !registered
+                    && limit.is_some_and(|limit| parked >= limit)
+                {
+                    self.report(Diagnostic::Stall { parked, tasks });
+                }
+
+                return;
+            }
+        }
+
+        let _ = tasks;
+        park.park();
+    }
+
+    // Ask the idle hook what to do, now that there are no tasks left.
+    #[cfg(not(feature = "web_lite"))]
+    fn idle(&self) -> Idle {
+        self.idle_hook.as_ref().map_or(Idle::Return, |hook| hook())
+    }
+
+    // Send a diagnostic to the watchdog hook, or print it in debug builds.
+    #[cfg(any(feature = "std", debug_assertions))]
+    fn report(&self, diagnostic: Diagnostic) {
+        #[cfg(feature = "std")]
+        match (&self.watchdog, diagnostic) {
+            (Some(watchdog), _) => (watchdog.hook)(diagnostic),
+            // Would likely hang forever, so fail loudly in release builds too
+            (None, Diagnostic::Reentrant { .. }) => {
+                panic!("pasts: {diagnostic}")
+            }
+            (None, _) if cfg!(debug_assertions) => {
+                eprintln!("pasts: {diagnostic}");
+            }
+            (None, _) => {}
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = diagnostic;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "web_lite", allow(dead_code))]
+#[derive(Clone)]
+struct Watchdog {
+    poll: Duration,
+    park: Duration,
+    hook: Arc<dyn Fn(Diagnostic) + Send + Sync>,
+}
+
+// Settings for demoting slow tasks.
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "web_lite", allow(dead_code))]
+#[derive(Copy, Clone)]
+struct Demote {
+    threshold: Duration,
+    strikes: u32,
+}
+
+#[cfg(feature = "std")]
+type PanicHook =
+    Arc<dyn Fn(TaskId, &(dyn Any + Send)) -> PanicPolicy + Send + Sync>;
+
+#[cfg_attr(feature = "web_lite", allow(dead_code))]
+#[derive(Clone)]
+struct PollHooks(
+    Arc<dyn Fn(TaskId) + Send + Sync>,
+    Arc<dyn Fn(TaskId) + Send + Sync>,
+);
+
+// Calls the after poll hook when dropped.
+#[cfg(not(feature = "web_lite"))]
+struct AfterPoll<'a>(&'a PollHooks, TaskId);
+
+#[cfg(not(feature = "web_lite"))]
+impl Drop for AfterPoll<'_> {
+    fn drop(&mut self) {
+        (self.0 .1)(self.1);
+    }
+}
+
+#[derive(Default)]
+pub struct DefaultPool {
+    spawning_queue: RefCell<Vec<LocalBoxNotify<'static>>>,
+}
+
+impl fmt::Debug for DefaultPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultPool")
+            .field("spawning_queue", &self.spawning_queue.borrow().len())
+            .finish()
+    }
+}
+
+impl Pool for DefaultPool {
+    type Park = DefaultPark;
+
+    // Push onto queue of tasks to spawn.
+    #[inline(always)]
+    fn push(&self, task: LocalBoxNotify<'static>) {
+        self.spawning_queue.borrow_mut().push(task);
+    }
+
+    // Grow the queue of tasks to spawn ahead of a push.
+    #[inline(always)]
+    fn try_reserve(&self) -> Result<(), TryReserveError> {
+        self.spawning_queue.borrow_mut().try_reserve(1)
+    }
+
+    // Drain from queue of tasks to spawn, keeping the queue's allocation.
+    #[inline(always)]
+    fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool {
+        let mut queue = self.spawning_queue.borrow_mut();
+        let has_drained = !queue.is_empty();
+
+        tasks.append(&mut queue);
+
+        has_drained
+    }
+}
+
+#[cfg(not(feature = "std"))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DefaultPark;
+
+#[cfg(all(
+    feature = "std",
+    not(all(feature = "io", not(feature = "web_lite")))
+))]
+#[derive(Debug)]
+pub struct DefaultPark(AtomicBool, crate::sync::thread::Thread);
+
+#[cfg(all(
+    feature = "std",
+    not(all(feature = "io", not(feature = "web_lite")))
+))]
+impl Default for DefaultPark {
+    fn default() -> Self {
+        Self(AtomicBool::new(true), crate::sync::thread::current())
+    }
+}
+
+// With feature `io`, park on the reactor, so timers and I/O are driven from
+// the executor's thread rather than the reactor's fallback thread.
+#[cfg(all(feature = "io", not(feature = "web_lite")))]
+#[derive(Debug)]
+pub struct DefaultPark(AtomicBool, std::sync::Mutex<Option<Waker>>);
+
+#[cfg(all(feature = "io", not(feature = "web_lite")))]
+impl Default for DefaultPark {
+    fn default() -> Self {
+        Self(AtomicBool::new(true), std::sync::Mutex::new(None))
+    }
+}
+
+#[cfg(all(feature = "io", not(feature = "web_lite")))]
+impl DefaultPark {
+    // Drive the reactor until unparked, or the timeout elapses.
+    fn react(&self, timeout: Option<Duration>) {
+        let mut timer = timeout.map(async_io::Timer::after);
+
+        async_io::block_on(core::future::poll_fn(|t| {
+            if !self.0.swap(true, Ordering::SeqCst) {
+                return Ready(());
+            }
+
+            *self.waker() = Some(t.waker().clone());
+
+            // An unpark may have come in before the waker was stored
+            if !self.0.swap(true, Ordering::SeqCst) {
+                return Ready(());
+            }
+
+            match timer.as_mut() {
+                Some(timer) => Pin::new(timer).poll(t).map(|_| ()),
+                None => Pending,
+            }
+        }));
+    }
+
+    fn waker(&self) -> std::sync::MutexGuard<'_, Option<Waker>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.1
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Park for DefaultPark {
+    // Park the current thread.
+    #[inline(always)]
+    fn park(&self) {
+        // Only park on std; There is no portable parking for no-std.
+        #[cfg(all(
+            feature = "std",
+            not(all(feature = "io", not(feature = "web_lite")))
+        ))]
+        while self.0.swap(true, Ordering::SeqCst) {
+            crate::sync::thread::park();
+        }
+
+        #[cfg(all(feature = "io", not(feature = "web_lite")))]
+        self.react(None);
+
+        // Hint at spin loop to possibly short sleep on no-std to save CPU time.
+        #[cfg(not(feature = "std"))]
+        core::hint::spin_loop();
+    }
+
+    // Park the current thread, giving up after the timeout.
+    #[inline(always)]
+    fn park_timeout(&self, timeout: Duration) {
+        #[cfg(all(feature = "io", not(feature = "web_lite")))]
+        self.react(Some(timeout));
+
+        #[cfg(all(
+            feature = "std",
+            not(loom),
+            not(all(feature = "io", not(feature = "web_lite")))
+        ))]
+        {
+            let deadline = Instant::now() + timeout;
+
+            while self.0.swap(true, Ordering::SeqCst) {
+                let Some(timeout) =
+                    deadline.checked_duration_since(Instant::now())
+                else {
+                    break;
+                };
+
+                std::thread::park_timeout(timeout);
+            }
+        }
+
+        // Loom can't time out, so park until woken.
+        #[cfg(all(
+            feature = "std",
+            loom,
+            not(all(feature = "io", not(feature = "web_lite")))
+        ))]
+        {
+            let _ = timeout;
+            self.park();
+        }
+
+        // There's nothing to time out on no-std, so just hint at spin loop.
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = timeout;
+            core::hint::spin_loop();
+        }
+    }
+
+    // Unpark the parked thread
+    #[inline(always)]
+    fn unpark(&self) {
+        // Only unpark on std; Since no-std doesn't park, it's already unparked.
+        #[cfg(all(
+            feature = "std",
+            not(all(feature = "io", not(feature = "web_lite")))
+        ))]
+        if self.0.swap(false, Ordering::SeqCst) {
+            self.1.unpark();
+        }
+
+        #[cfg(all(feature = "io", not(feature = "web_lite")))]
+        if self.0.swap(false, Ordering::SeqCst) {
+            let waker = self.waker().take();
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+// Per-task waker, which sets the woken flag before unparking the executor.
+struct Unpark<P: Park> {
+    unparker: Unparker<P>,
+    woken: AtomicBool,
+    id: TaskId,
+    hook: Option<Arc<dyn Fn(TaskId) + Send + Sync>>,
+    stats: Arc<Counters>,
+    #[cfg(feature = "std")]
+    thread: Option<usize>,
+}
+
+impl<P: Park> Wake for Unpark<P> {
+    #[inline(always)]
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    #[inline(always)]
+    fn wake_by_ref(self: &Arc<Self>) {
+        #[cfg(all(feature = "std", not(feature = "web_lite")))]
+        self.stats
+            .wake(self.thread.is_none() || thread_id() != self.thread);
+        #[cfg(not(any(feature = "std", feature = "web_lite")))]
+        self.stats.wake(false);
+
+        self.woken.store(true, Ordering::SeqCst);
+
+        // Only the first wake since the executor started polling unparks;
+        // the rest are picked up by the same pass over the tasks.
+        if self.unparker.try_unpark() {
+            lifecycle!("{} unparked the executor", self.id);
+        } else {
+            #[cfg(not(feature = "web_lite"))]
+            self.stats.coalesced();
+        }
+
+        // Called last, so that the task is already marked as woken
+        if let Some(ref hook) = self.hook {
+            hook(self.id);
+        }
+    }
+}
+
+// A task on the executor, along with its waker.
+#[cfg(not(feature = "web_lite"))]
+struct Entry<P: Park> {
+    id: TaskId,
+    task: LocalBoxNotify<'static>,
+    unpark: Arc<Unpark<P>>,
+    waker: Waker,
+    streak: u32,
+    // Slow polls in a row, and whether that's enough to be demoted
+    #[cfg(feature = "std")]
+    slow: u32,
+    #[cfg(feature = "std")]
+    demoted: bool,
+    // Demoted tasks skip every other pass
+    #[cfg(feature = "std")]
+    skipped: bool,
+    // Doesn't keep the executor alive, known once polled
+    #[cfg(feature = "std")]
+    detached: bool,
+    #[cfg(debug_assertions)]
+    lost: u8,
+}
+
+#[cfg(not(feature = "web_lite"))]
+impl<P: Park> Entry<P> {
+    fn new(
+        id: u64,
+        task: LocalBoxNotify<'static>,
+        parker: &Parker<P>,
+        config: &Config,
+    ) -> Self {
+        let id = TaskId(id);
+        let unpark = Arc::new(Unpark {
+            unparker: parker.unparker(),
+            woken: AtomicBool::new(true),
+            id,
+            hook: config.wake_hook.clone(),
+            stats: config.stats.clone(),
+            #[cfg(feature = "std")]
+            thread: thread_id(),
+        });
+        let waker = unpark.clone().into();
+
+        lifecycle!("{id} spawned");
+
+        Self {
+            id,
+            task,
+            unpark,
+            waker,
+            streak: 0,
+            #[cfg(feature = "std")]
+            slow: 0,
+            #[cfg(feature = "std")]
+            demoted: false,
+            #[cfg(feature = "std")]
+            skipped: false,
+            #[cfg(feature = "std")]
+            detached: false,
+            #[cfg(debug_assertions)]
+            lost: 0,
+        }
+    }
+
+    // Check if a waker for this task is held outside of the executor.
+    #[cfg(any(feature = "std", debug_assertions))]
+    #[inline(always)]
+    fn registered(&self) -> bool {
+        // The entry holds two references; any more are registered wakers
+        Arc::strong_count(&self.unpark) > 2
+    }
+
+    // Check if this task has been woken since it was last polled.
+    #[inline(always)]
+    fn woken(&self) -> bool {
+        self.unpark.woken.load(Ordering::SeqCst)
+    }
+
+    // Check if this task may ever be woken.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn alive(&self) -> bool {
+        self.registered() || self.woken()
+    }
+
+    #[inline(always)]
+    fn poll(&mut self, config: &Config) -> Poll {
+        self.unpark.woken.store(false, Ordering::SeqCst);
+
+        #[cfg(feature = "std")]
+        let outer = DETACHED.replace(false);
+        let (poll, elapsed) = config.poll(
+            self.id,
+            &mut self.task,
+            &mut Task::from_waker(&self.waker),
+        );
+
+        #[cfg(feature = "std")]
+        if DETACHED.replace(outer) {
+            self.detached = true;
+        }
+
+        #[cfg(feature = "std")]
+        if let (Some(demote), Some(elapsed)) = (config.demote, elapsed) {
+            self.demote(demote, elapsed, config);
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = elapsed;
+
+        // Count polls in a row where the task woke itself
+        if poll.is_pending() && self.woken() {
+            self.streak = self.streak.saturating_add(1);
+        } else {
+            self.streak = 0;
+        }
+
+        #[cfg(debug_assertions)]
+        if poll.is_pending() {
+            if self.registered() || self.woken() {
+                self.lost = 0;
+            } else {
+                self.lost = self.lost.saturating_add(1);
+
+                if self.lost == 2 {
+                    config.report(Diagnostic::LostWaker(self.id));
+                }
+            }
+        }
+
+        poll
+    }
+
+    // Demote the task after too many slow polls in a row, or promote it
+    // after a fast one.
+    #[cfg(feature = "std")]
+    fn demote(&mut self, demote: Demote, elapsed: Duration, config: &Config) {
+        if elapsed <= demote.threshold {
+            self.slow = 0;
+            self.demoted = false;
+            return;
+        }
+
+        self.slow = self.slow.saturating_add(1);
+
+        if !self.demoted && self.slow >= demote.strikes {
+            self.demoted = true;
+            config.report(Diagnostic::Demoted(self.id));
+        }
+    }
+
+    // Check if the task has been demoted for being slow.
+    #[inline(always)]
+    fn demoted(&self) -> bool {
+        #[cfg(feature = "std")]
+        {
+            self.demoted
+        }
+
+        #[cfg(not(feature = "std"))]
+        false
+    }
+
+    // Check if the task has used up its time slice, or is demoted, and should
+    // skip a turn.
+    #[inline(always)]
+    fn yields(&mut self, config: &Config) -> bool {
+        #[cfg(feature = "std")]
+        if self.demoted {
+            self.skipped = !self.skipped;
+
+            if self.skipped {
+                return true;
+            }
+        }
+
+        if config.time_slice == 0 || self.streak < config.time_slice {
+            return false;
+        }
+
+        self.streak = 0;
+        true
+    }
+
+    // Poll the task, applying the panic policy if it panics.
+    #[inline(always)]
+    fn run(&mut self, config: &Config) -> Poll {
+        #[cfg(feature = "std")]
+        if let Some(ref hook) = config.panic_hook {
+            let outer = RESTARTABLE.replace(false);
+            let poll =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    self.poll(config)
+                }));
+            let restartable = RESTARTABLE.replace(outer);
+
+            return poll.unwrap_or_else(|payload| {
+                match hook(self.id, &*payload) {
+                    PanicPolicy::Abort => std::panic::resume_unwind(payload),
+                    PanicPolicy::RestartTask if restartable => {
+                        self.waker.wake_by_ref();
+                        Pending
+                    }
+                    // Other tasks would panic again each time they're polled
+                    PanicPolicy::RestartTask | PanicPolicy::CancelTask => {
+                        Ready(())
+                    }
+                }
+            });
+        }
+
+        self.poll(config)
+    }
+}
+
+// Get an identifier for the current thread, which unlike
+// `std::thread::current()` doesn't clone a handle on every wake.  Returns
+// `None` while the thread's locals are being destroyed.
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+fn thread_id() -> Option<usize> {
+    use core::sync::atomic;
+
+    static NEXT: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+
+    std::thread_local! {
+        static ID: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    ID.try_with(|id| *id).ok()
+}
+
+// Set while polling a task spawned with `Executor::spawn_restartable()`, so
+// the executor can tell if a panicking task can start over.
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+std::thread_local! {
+    static RESTARTABLE: Cell<bool> = const { Cell::new(false) };
+}
+
+// Set while polling a task spawned from a handle made with
+// `Executor::keep_alive(false)`, so its entry can be marked as detached.
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+std::thread_local! {
+    static DETACHED: Cell<bool> = const { Cell::new(false) };
+}
+
+// Woken flags for each `block_on()` running on this thread, innermost last.
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+std::thread_local! {
+    static NESTED: RefCell<Vec<Arc<AtomicBool>>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+// Registration of a `block_on()` call on this thread's stack.
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+struct Nested;
+
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+impl Nested {
+    fn enter(level: &Arc<AtomicBool>) -> Self {
+        NESTED.with(|levels| levels.borrow_mut().push(level.clone()));
+        Self
+    }
+
+    // Return the depth if an enclosing `block_on()` has woken tasks.
+    fn blocked(&self) -> Option<usize> {
+        NESTED.with(|levels| {
+            let levels = levels.borrow();
+            let (_, outer) = levels.split_last()?;
+
+            outer
+                .iter()
+                .any(|level| level.load(Ordering::SeqCst))
+                .then_some(levels.len())
+        })
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+impl Drop for Nested {
+    fn drop(&mut self) {
+        NESTED.with(|levels| levels.borrow_mut().pop());
+    }
+}
+
+// A task that doesn't keep the executor alive, which tells the entry polling
+// it so.
+#[cfg(all(not(feature = "web_lite"), feature = "std"))]
+struct Detached<N>(N);
+
+#[cfg(all(not(feature = "web_lite"), feature = "std"))]
+impl<N: Notify<Event = ()> + Unpin> Notify for Detached<N> {
+    type Event = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        DETACHED.set(true);
+        Pin::new(&mut self.0).poll_next(t)
+    }
+}
+
+// Tasks running on an executor, and the flag set when any are woken.
+#[cfg(not(feature = "web_lite"))]
+struct Run<K: Park> {
+    tasks: List<Entry<K>>,
+    spawned: Vec<LocalBoxNotify<'static>>,
+    parker: Parker<K>,
+    // Unparks the parker, for tasks spawned from other threads
+    waker: Waker,
+}
+
+#[cfg(not(feature = "web_lite"))]
+impl<K: Park> Run<K> {
+    fn new() -> Self {
+        // Start out unparked, so the first pass happens without a wake
+        let parker = Parker::with_level(true);
+        let waker = parker.waker();
+
+        Self {
+            tasks: List::new(),
+            spawned: Vec::new(),
+            parker,
+            waker,
+        }
+    }
+
+    // Add a task, with the next identifier.
+    fn push(&mut self, task: LocalBoxNotify<'static>, config: &Config) {
+        let id = config.stats.next_id();
+        let entry = Entry::new(id, task, &self.parker, config);
+
+        self.tasks.push_back(entry);
+    }
+
+    // Poll the set of futures, removing any that have completed.  Returns
+    // true if any tasks completed or were skipped.
+    fn poll(&mut self, config: &Config) -> bool {
+        let mut progress = false;
+
+        // Synchronizes with the last wake, so this pass sees whatever the
+        // waking thread did before it
+        self.parker.reset();
+
+        // Demoted tasks go after the rest
+        for demoted in [false, true] {
+            let mut cursor = self.tasks.first();
+
+            while let Some(key) = cursor {
+                cursor = self.tasks.next(key);
+
+                let Some(task) = self.tasks.get_mut(key) else {
+                    continue;
+                };
+
+                if task.demoted() != demoted {
+                    continue;
+                }
+
+                // Skipped tasks are still woken, so don't park after this pass
+                if task.yields(config) {
+                    progress = true;
+                } else if task.run(config).is_ready() {
+                    lifecycle!("{} completed", task.id);
+                    #[cfg(feature = "std")]
+                    if config.cpu_time {
+                        config.stats.completed(task.id);
+                    }
+                    self.tasks.remove(key);
+                    progress = true;
+                }
+            }
+        }
+
+        progress
+    }
+
+    // Forget the CPU time of tasks that are dropped without completing.
+    #[cfg(feature = "std")]
+    fn forget(&self, config: &Config) {
+        if !config.cpu_time {
+            return;
+        }
+
+        for task in self.tasks.iter() {
+            config.stats.completed(task.id);
+        }
+    }
+
+    // Initiate execution of any spawned tasks.  Returns true if any were
+    // drained from the pool.
+    fn start(&mut self, pool: &impl Pool<Park = K>, config: &Config) -> bool {
+        let drained = pool.drain(&mut self.spawned);
+
+        for task in self.spawned.drain(..) {
+            let id = config.stats.next_id();
+
+            self.tasks
+                .push_back(Entry::new(id, task, &self.parker, config));
+        }
+
+        config.stats.tasks(self.tasks.len());
+        drained
+    }
+}
+
+#[cfg(not(feature = "web_lite"))]
+fn block_on<P: Pool>(
+    f: impl Future<Output = ()> + 'static,
+    pool: &Arc<P>,
+    config: &Config,
+    #[cfg(feature = "std")] mut deadline: Option<Instant>,
+    #[cfg(feature = "std")] shutdown: &Shutdown,
+    spawned: &AtomicWaker,
+) -> Result<(), TimedOut> {
+    let run = &mut Run::<P::Park>::new();
+    #[cfg(feature = "std")]
+    let mut draining = false;
+
+    // Register on this thread's stack of executors
+    #[cfg(feature = "std")]
+    let nested = Nested::enter(run.parker.level());
+
+    // Spawn main task, fused
+    let main: LocalBoxNotify<'static> = Box::pin(f.fuse());
+
+    if let Some(ref hook) = config.size_hook {
+        hook(size_of_val(&*main));
+    }
+
+    run.push(main, config);
+
+    // Run the set of futures to completion, and then until the idle hook
+    // says to return.
+    loop {
+        // Before draining the pool, so spawns from other threads after it
+        // wake the executor
+        spawned.register(&run.waker);
+
+        // Apply the drain policy once, when shutdown starts
+        #[cfg(feature = "std")]
+        if !draining && shutdown.is_fired() {
+            draining = true;
+
+            match config.drain {
+                DrainPolicy::Wait => {}
+                DrainPolicy::Timeout(timeout) => {
+                    let drain = Instant::now() + timeout;
+
+                    deadline = Some(deadline.map_or(drain, |d| d.min(drain)));
+                }
+                DrainPolicy::Cancel => break,
+            }
+        }
+
+        if run.tasks.is_empty() {
+            if config.idle() == Idle::Return {
+                break;
+            }
+
+            // Wait for tasks to be spawned from outside of the executor
+            if !run.start(&**pool, config) {
+                #[cfg(feature = "std")]
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    break;
+                }
+
+                config.park(
+                    run.parker.inner(),
+                    0,
+                    #[cfg(feature = "std")]
+                    false,
+                    #[cfg(feature = "std")]
+                    deadline,
+                );
+            }
+
+            continue;
+        }
+
+        // Poll the tasks, and start spawned ones - if no progress, park
+        let progress = run.poll(config);
+        let drained = run.start(&**pool, config);
+
+        // Only tasks that don't keep the executor alive are left
+        #[cfg(feature = "std")]
+        if draining
+            && !run.tasks.is_empty()
+            && run.tasks.iter().all(|task| task.detached)
+        {
+            break;
+        }
+
+        // Give up if past the deadline
+        #[cfg(feature = "std")]
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            run.forget(config);
+
+            return match run.tasks.len() {
+                0 => Ok(()),
+                tasks => Err(TimedOut { tasks }),
+            };
+        }
+
+        if !drained && !progress {
+            #[cfg(feature = "std")]
+            if let Some(depth) = nested.blocked() {
+                config.report(Diagnostic::Reentrant { depth });
+            }
+
+            // Not a deadlock if the deadline will wake the executor
+            #[cfg(feature = "std")]
+            if deadline.is_none() && !run.tasks.iter().any(Entry::alive) {
+                config.report(Diagnostic::Deadlock {
+                    tasks: run.tasks.len(),
+                });
+            }
+
+            config.park(
+                run.parker.inner(),
+                run.tasks.len(),
+                #[cfg(feature = "std")]
+                run.tasks.iter().any(Entry::registered),
+                #[cfg(feature = "std")]
+                deadline,
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    run.forget(config);
+
+    Ok(())
+}
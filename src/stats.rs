@@ -0,0 +1,161 @@
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::sync::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use crate::TaskId;
+
+// Counters shared between an executor and its wakers.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    tasks: AtomicUsize,
+    wakes: AtomicUsize,
+    cross_thread_wakes: AtomicUsize,
+    coalesced_wakes: AtomicUsize,
+    // Identifier of the next task, shared so nested runs don't reuse them
+    #[cfg(not(feature = "web_lite"))]
+    next_id: AtomicUsize,
+    // Time spent polling each task that hasn't completed
+    #[cfg(feature = "std")]
+    busy: Mutex<BTreeMap<TaskId, Duration>>,
+}
+
+#[cfg(feature = "std")]
+impl Counters {
+    fn busy_times(&self) -> MutexGuard<'_, BTreeMap<TaskId, Duration>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.busy.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(not(feature = "web_lite"))]
+impl Counters {
+    // Record the number of tasks on the executor.
+    #[inline(always)]
+    pub(crate) fn tasks(&self, tasks: usize) {
+        self.tasks.store(tasks, Ordering::Relaxed);
+    }
+
+    // Allocate an identifier for a new task.
+    #[inline(always)]
+    pub(crate) fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) as u64
+    }
+
+    // Record a wake, and whether it came from another thread.
+    #[inline(always)]
+    pub(crate) fn wake(&self, cross_thread: bool) {
+        self.wakes.fetch_add(1, Ordering::Relaxed);
+
+        if cross_thread {
+            self.cross_thread_wakes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Record a wake that was batched into an earlier wake's unpark.
+    #[inline(always)]
+    pub(crate) fn coalesced(&self) {
+        self.coalesced_wakes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Add to the time spent polling a task.
+    #[cfg(feature = "std")]
+    pub(crate) fn busy(&self, task: TaskId, elapsed: Duration) {
+        *self.busy_times().entry(task).or_default() += elapsed;
+    }
+
+    // Forget the time spent polling a task, now that it's completed.
+    #[cfg(feature = "std")]
+    pub(crate) fn completed(&self, task: TaskId) {
+        self.busy_times().remove(&task);
+    }
+}
+
+/// Handle to the counters of an [`Executor`](crate::Executor), returned from
+/// [`Executor::stats()`](crate::Executor::stats()).
+///
+/// The handle can be sent to another thread (for example, a monitoring
+/// thread), and read with [`ExecutorStats::snapshot()`] while the executor is
+/// running.  Comparing snapshots from each of the [`Workers`](crate::Workers)
+/// shows whether work is balanced between them.
+///
+/// ```rust
+/// let executor = pasts::Executor::default();
+/// let stats = executor.stats();
+///
+/// executor.block_on(async {});
+///
+/// let snapshot = stats.snapshot();
+///
+/// assert_eq!(snapshot.tasks, 0);
+/// assert_eq!(snapshot.cross_thread_wakes, 0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExecutorStats(pub(crate) Arc<Counters>);
+
+impl ExecutorStats {
+    /// Read the current values of the counters.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            tasks: self.0.tasks.load(Ordering::Relaxed),
+            wakes: self.0.wakes.load(Ordering::Relaxed),
+            cross_thread_wakes: self
+                .0
+                .cross_thread_wakes
+                .load(Ordering::Relaxed),
+            coalesced_wakes: self.0.coalesced_wakes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Get how long each task that hasn't completed has spent being polled,
+    /// busiest first.
+    ///
+    /// Only recorded with [`Executor::cpu_time()`](crate::Executor::cpu_time),
+    /// and approximate, since it's measured with the wall clock around each
+    /// poll.
+    #[cfg(feature = "std")]
+    pub fn cpu_times(&self) -> Vec<(TaskId, Duration)> {
+        let mut times: Vec<_> = self
+            .0
+            .busy_times()
+            .iter()
+            .map(|(&task, &time)| (task, time))
+            .collect();
+
+        times.sort_by_key(|&(_, time)| core::cmp::Reverse(time));
+        times
+    }
+}
+
+/// Values of an executor's counters, from [`ExecutorStats::snapshot()`].
+///
+/// # Platform-Specific Behavior
+/// When building with feature _`web`_, all of the counters stay at zero.
+/// Without feature _`std`_, wakes can't be told apart by thread, so
+/// `cross_thread_wakes` stays at zero.
+///
+/// # Scope
+/// There are no steal counters, since tasks never move between
+/// [`Workers`](crate::Workers), and the counters aren't broken down by
+/// worker.  Each worker has its own executor, so get its [`ExecutorStats`]
+/// with [`Executor::stats()`](crate::Executor::stats()) in each worker's
+/// main function, and compare their snapshots.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct StatsSnapshot {
+    /// How many tasks are on the executor (its queue depth), as of the last
+    /// pass over the tasks
+    pub tasks: usize,
+    /// How many times tasks have been woken
+    pub wakes: usize,
+    /// How many of the wakes came from a thread other than the executor's
+    pub cross_thread_wakes: usize,
+    /// How many of the wakes were batched into an earlier wake, since the
+    /// executor was already unparked and will see them on the same pass
+    pub coalesced_wakes: usize,
+}
@@ -0,0 +1,222 @@
+//! Stress tests for [`Executor`]s, and the [`Pool`]s they run on.
+//!
+//! Each test runs to completion on the executor it's passed, and panics if
+//! the executor loses a task, loses a wakeup, or leaks a dropped task.  The
+//! tests avoid `unsafe` and timing, so they can also be run under Miri.
+//!
+//! Integrators with their own [`Pool`] can run the same battery against it:
+//!
+//! ```rust
+//! use pasts::{stress, Executor};
+//!
+//! stress::run_all(Executor::default);
+//! ```
+
+use alloc::{rc::Rc, sync::Arc, vec::Vec};
+use core::{cell::Cell, future::poll_fn, task::Waker};
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+use crate::{
+    prelude::*,
+    sync::{AtomicUsize, Ordering::SeqCst},
+    Executor, Pool,
+};
+
+/// Run every stress test, each on a fresh executor from `new`.
+pub fn run_all<P: Pool + 'static>(new: impl Fn() -> Executor<P>) {
+    spawn_storm(new(), 256);
+    wake_storm(new(), 4, 64);
+    drop_mid_poll(new(), 64);
+}
+
+/// Spawn `tasks` tasks at once, each of which spawns another task from
+/// within the executor, checking that all of them run.
+pub fn spawn_storm<P: Pool + 'static>(executor: Executor<P>, tasks: usize) {
+    let ran = Rc::new(Cell::new(0));
+
+    for _ in 0..tasks {
+        let spawner = executor.clone();
+        let ran = ran.clone();
+
+        executor.spawn_local(async move {
+            let child = ran.clone();
+
+            spawner.spawn_local(async move { child.set(child.get() + 1) });
+            ran.set(ran.get() + 1);
+        });
+    }
+
+    executor.block_on(async {});
+
+    assert_eq!(ran.get(), tasks * 2, "spawned tasks didn't all run");
+}
+
+/// Wake a handful of tasks `wakes` times each from each of `threads`
+/// threads, checking that each task sees every wake.
+pub fn wake_storm<P: Pool + 'static>(
+    executor: Executor<P>,
+    threads: usize,
+    wakes: usize,
+) {
+    const TASKS: usize = 8;
+
+    let signals: Vec<_> =
+        (0..TASKS).map(|_| Arc::new(Signal::default())).collect();
+    let total = threads * wakes;
+    let done = Rc::new(Cell::new(0));
+
+    for signal in &signals {
+        let signal = signal.clone();
+        let done = done.clone();
+
+        executor.spawn_local(async move {
+            poll_fn(|task| {
+                // Register before checking, so a wake in between isn't lost
+                signal.register(task.waker());
+
+                if signal.count.load(SeqCst) >= total {
+                    Ready(())
+                } else {
+                    Pending
+                }
+            })
+            .await;
+            done.set(done.get() + 1);
+        });
+    }
+
+    let wakers: Vec<_> = (0..threads)
+        .map(|_| {
+            let signals = signals.clone();
+
+            thread::spawn(move || {
+                for _ in 0..wakes {
+                    for signal in &signals {
+                        signal.count.fetch_add(1, SeqCst);
+                        signal.wake();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    executor.block_on(async {});
+
+    for waker in wakers {
+        waker.join().expect("waking thread panicked");
+    }
+
+    assert_eq!(done.get(), TASKS, "tasks missed wakes");
+}
+
+/// Cancel `tasks` tasks while they're pending, then wake them from another
+/// thread, checking that each is dropped exactly once and late wakes are
+/// harmless.
+pub fn drop_mid_poll<P: Pool + 'static>(executor: Executor<P>, tasks: usize) {
+    let signal = Arc::new(Signal::default());
+    let dropped = Rc::new(Cell::new(0));
+    let polled = Rc::new(Cell::new(0));
+    let mut handles = Vec::with_capacity(tasks);
+
+    for _ in 0..tasks {
+        let signal = signal.clone();
+        let polled = polled.clone();
+        let guard = DropCount(dropped.clone());
+
+        handles.push(executor.spawn_with_handle(async move {
+            let _guard = guard;
+
+            poll_fn::<(), _>(|task| {
+                signal.push(task.waker());
+                polled.set(polled.get() + 1);
+                Pending
+            })
+            .await;
+        }));
+    }
+
+    executor.block_on({
+        let polled = polled.clone();
+        let dropped = dropped.clone();
+
+        async move {
+            // Let every task get to its first `Pending`
+            poll_fn(|task| {
+                if polled.get() >= tasks {
+                    Ready(())
+                } else {
+                    task.waker().wake_by_ref();
+                    Pending
+                }
+            })
+            .await;
+
+            for handle in handles {
+                handle.cancel();
+            }
+
+            // Wake the dropped tasks from another thread
+            let waker = thread::spawn(move || signal.wake_all());
+
+            poll_fn(|task| {
+                if dropped.get() >= tasks {
+                    Ready(())
+                } else {
+                    task.waker().wake_by_ref();
+                    Pending
+                }
+            })
+            .await;
+            waker.join().expect("waking thread panicked");
+        }
+    });
+
+    assert_eq!(dropped.get(), tasks, "cancelled tasks weren't dropped once");
+}
+
+#[derive(Default)]
+struct Signal {
+    count: AtomicUsize,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Signal {
+    fn lock(&self) -> MutexGuard<'_, Vec<Waker>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.wakers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.lock();
+
+        wakers.clear();
+        wakers.push(waker.clone());
+    }
+
+    fn push(&self, waker: &Waker) {
+        self.lock().push(waker.clone());
+    }
+
+    fn wake(&self) {
+        for waker in self.lock().iter() {
+            waker.wake_by_ref();
+        }
+    }
+
+    fn wake_all(&self) {
+        for waker in core::mem::take(&mut *self.lock()) {
+            waker.wake();
+        }
+    }
+}
+
+struct DropCount(Rc<Cell<usize>>);
+
+impl Drop for DropCount {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
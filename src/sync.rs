@@ -0,0 +1,47 @@
+//! Synchronization primitives.
+//!
+//! With the _`std`_ feature, [`Mutex`] can be held across `.await`s, and
+//! [`Condvar`] waits for its value to change.  [`work_queue()`] hands each
+//! item to one of several competing receivers, and [`Progress`] reports how
+//! far along a long-running operation is.
+//!
+//! The atomics and threads are the ones used by the executor's wakers and
+//! parking.  Building with `RUSTFLAGS="--cfg loom"` swaps these for
+//! [loom](https://docs.rs/loom)'s, so that the park / unpark and cross-thread
+//! wake paths can be model checked.  Custom [`Park`](crate::Park) and
+//! [`Pool`](crate::Pool) implementations can use them too, so that they're
+//! checked along with the executor:
+//!
+//! ```shell
+//! RUSTFLAGS="--cfg loom" cargo test --test loom --release
+//! ```
+//!
+//! [`Arc`](alloc::sync::Arc) isn't swapped, since wakers are made from the
+//! standard library's.
+
+#[cfg(not(loom))]
+pub use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+/// Threads, for parking and unparking.
+///
+/// Under loom, there's no `park_timeout()`.
+#[cfg(all(feature = "std", not(loom)))]
+pub use std::thread;
+
+#[cfg(loom)]
+pub use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+/// Threads, for parking and unparking.
+///
+/// Under loom, there's no `park_timeout()`.
+#[cfg(all(feature = "std", loom))]
+pub use loom::thread;
+
+#[cfg(feature = "std")]
+pub use crate::condvar::{Condvar, Wait, WaitOwned};
+#[cfg(feature = "std")]
+pub use crate::mutex::{
+    Lock, LockOwned, MappedMutexGuard, Mutex, MutexGuard, OwnedMutexGuard,
+};
+#[cfg(feature = "std")]
+pub use crate::progress::{Progress, ProgressSnapshot, ProgressWatch};
+#[cfg(feature = "std")]
+pub use crate::work::{work_queue, WorkReceiver, WorkSender};
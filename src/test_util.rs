@@ -0,0 +1,146 @@
+//! Record the scheduling of an [`Executor`], for regression tests.
+//!
+//! [`RecordingExecutor`] logs each time a task is polled or woken, and each
+//! time the executor parks, into a [`Trace`].  Asserting on the trace catches
+//! changes in fairness or ordering that wouldn't change a test's output.
+//!
+//! ```rust
+//! use pasts::{test_util::RecordingExecutor, Executor};
+//!
+//! let executor = RecordingExecutor::new(Executor::default());
+//!
+//! executor.spawn_local(async {});
+//!
+//! let trace = executor.block_on(async {});
+//!
+//! assert_eq!(trace.to_string(), "poll task 0\npoll task 1\n");
+//! ```
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{fmt, ops::Deref};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::{prelude::*, spawn::DefaultPool, Executor, Pool, TaskId};
+
+/// Event recorded in a [`Trace`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TraceEvent {
+    /// A task was polled
+    Poll(TaskId),
+    /// A task was woken
+    Wake(TaskId),
+    /// The executor parked, waiting for a task to be woken
+    Park,
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Poll(task) => write!(f, "poll {task}"),
+            Self::Wake(task) => write!(f, "wake {task}"),
+            Self::Park => f.write_str("park"),
+        }
+    }
+}
+
+/// Sequence of [`TraceEvent`]s recorded by a [`RecordingExecutor`].
+///
+/// Clones share the same sequence.  Displays as one event per line.
+#[derive(Clone, Debug, Default)]
+pub struct Trace(Arc<Mutex<Vec<TraceEvent>>>);
+
+impl Trace {
+    fn lock(&self) -> MutexGuard<'_, Vec<TraceEvent>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, event: TraceEvent) {
+        self.lock().push(event);
+    }
+
+    /// Get the events recorded so far.
+    pub fn events(&self) -> Vec<TraceEvent> {
+        self.lock().clone()
+    }
+
+    /// Remove the events recorded so far, returning them.
+    pub fn take(&self) -> Vec<TraceEvent> {
+        core::mem::take(&mut *self.lock())
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in self.lock().iter() {
+            writeln!(f, "{event}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// [`Executor`] that records its scheduling into a [`Trace`].
+///
+/// Dereferences to the [`Executor`], for spawning tasks.  Recording replaces
+/// the executor's [`poll_hooks`](Executor::poll_hooks()) and
+/// [`wake_hook`](Executor::wake_hook()).
+#[derive(Debug)]
+pub struct RecordingExecutor<P: Pool = DefaultPool> {
+    executor: Executor<P>,
+    trace: Trace,
+}
+
+impl Default for RecordingExecutor {
+    fn default() -> Self {
+        Self::new(Executor::default())
+    }
+}
+
+impl<P: Pool> RecordingExecutor<P> {
+    /// Start recording the scheduling of `executor`.
+    pub fn new(executor: Executor<P>) -> Self {
+        let trace = Trace::default();
+        let executor = executor
+            .poll_hooks(
+                {
+                    let trace = trace.clone();
+
+                    move |task| trace.push(TraceEvent::Poll(task))
+                },
+                |_| {},
+            )
+            .wake_hook({
+                let trace = trace.clone();
+
+                move |task| trace.push(TraceEvent::Wake(task))
+            })
+            .park_hook({
+                let trace = trace.clone();
+
+                move || trace.push(TraceEvent::Park)
+            });
+
+        Self { executor, trace }
+    }
+
+    /// Get the trace that's being recorded.
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// Block on a future with [`Executor::block_on()`], returning the trace.
+    pub fn block_on(self, f: impl Future<Output = ()> + 'static) -> Trace {
+        self.executor.block_on(f);
+        self.trace
+    }
+}
+
+impl<P: Pool> Deref for RecordingExecutor<P> {
+    type Target = Executor<P>;
+
+    fn deref(&self) -> &Executor<P> {
+        &self.executor
+    }
+}
@@ -0,0 +1,1119 @@
+//! Portable time and timers, which work the same way on every platform.
+//!
+//! [`Instant`] is backed by [`std::time::Instant`], except when building with
+//! feature _`web`_, where it's backed by `performance.now()` (which
+//! [`std::time::Instant`] can't use in the browser).  Timers are run by a
+//! shared timer thread, by async-io's reactor with feature _`io`_ (which uses
+//! a timerfd on Linux, and is driven by the executor's thread while it
+//! waits), or by `setTimeout()` on the web.
+//!
+//! Without _`std`_ or _`web`_, there's no clock to read, so install one
+//! (such as a hardware timer's counter) with `time::set_clock()` before
+//! calling [`Instant::now()`].  Timers aren't available there.
+//!
+//! ```rust
+//! # #[cfg(feature = "std")] {
+//! use pasts::time::{Duration, Instant};
+//!
+//! let start = Instant::now();
+//! let later = start + Duration::from_millis(5);
+//!
+//! assert_eq!(later - start, Duration::from_millis(5));
+//! assert_eq!(start - later, Duration::ZERO);
+//! # }
+//! ```
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+use alloc::{collections::BTreeMap, rc::Rc};
+#[cfg(any(feature = "std", feature = "web_lite"))]
+use core::cell::{Cell, RefCell};
+#[doc(no_inline)]
+pub use core::time::Duration;
+use core::{
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+use crate::{notify::WakerSlot, prelude::*, timer::Timer};
+
+/// A measurement of a monotonically nondecreasing clock.
+///
+/// Like [`std::time::Instant`], instants are only meaningful compared to
+/// each other.  Unlike it, subtracting a later instant saturates to
+/// [`Duration::ZERO`] instead of panicking.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(Duration);
+
+impl Instant {
+    /// Get the instant corresponding to "now".
+    pub fn now() -> Self {
+        Self(now())
+    }
+
+    /// Get the amount of time elapsed from `earlier` to this instant, or
+    /// zero if `earlier` is later.
+    pub fn duration_since(&self, earlier: Self) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// Get the amount of time elapsed since this instant.
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    /// Get the instant `duration` after this one, if it can be represented.
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        self.0.checked_add(duration).map(Self)
+    }
+
+    /// Get the instant `duration` before this one, if it can be represented.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        self.0.checked_sub(duration).map(Self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Self;
+
+    /// # Panics
+    /// If the result can't be represented.
+    fn add(self, duration: Duration) -> Self {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Self;
+
+    /// # Panics
+    /// If the result can't be represented.
+    fn sub(self, duration: Duration) -> Self {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl Sub for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Self) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// Future that completes once a deadline has passed.
+///
+/// This struct is created by [`sleep()`].  See its documentation for more.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub struct Sleep {
+    deadline: Instant,
+    timer: Timer,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl fmt::Debug for Sleep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sleep")
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Sleep {
+    /// Get the instant that this future completes at.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Change the instant that this future completes at, even if it already
+    /// has.
+    ///
+    /// This allows one `Sleep` to be reused for a series of deadlines.
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = deadline;
+        self.timer.reset();
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        let this = self.get_mut();
+
+        this.timer.poll(this.deadline, t)
+    }
+}
+
+/// Error returned from [`Timeout`] when its deadline passes first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Elapsed {}
+
+/// Future that gives up on another future once a deadline has passed.
+///
+/// This struct is created by [`timeout()`] and [`timeout_at()`].  See their
+/// documentation for more.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+#[derive(Debug)]
+pub struct Timeout<F> {
+    future: F,
+    sleep: Sleep,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<F> Timeout<F> {
+    /// Get the future, without the timeout.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<F: Future + Unpin> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Ready(output) = Pin::new(&mut this.future).poll(t) {
+            return Ready(Ok(output));
+        }
+
+        Pin::new(&mut this.sleep).poll(t).map(|()| Err(Elapsed))
+    }
+}
+
+/// What an [`Interval`] does after it missed ticks, because it wasn't polled
+/// in time (for example, when a loop blocked).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MissedTicks {
+    /// Produce the missed ticks right away, until caught up with the
+    /// original schedule (default)
+    #[default]
+    Burst,
+    /// Start the schedule over from when the late tick was produced, so that
+    /// there's always at least one period between ticks
+    Delay,
+    /// Drop the missed ticks, and keep to the original schedule
+    Skip,
+}
+
+/// [`Notify`] that produces the scheduled [`Instant`] of each tick, once per
+/// period.
+///
+/// This struct is created by [`interval()`].  See its documentation for more.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub struct Interval {
+    period: Duration,
+    next: Instant,
+    missed: MissedTicks,
+    timer: Timer,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interval")
+            .field("period", &self.period)
+            .field("next", &self.next)
+            .field("missed", &self.missed)
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Interval {
+    /// Set what happens after ticks are missed.
+    pub fn missed_ticks(mut self, missed: MissedTicks) -> Self {
+        self.missed = missed;
+        self
+    }
+
+    /// Get the time between ticks.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Notify for Interval {
+    type Event = Instant;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Instant> {
+        let this = self.get_mut();
+
+        if this.timer.poll(this.next, t).is_pending() {
+            return Pending;
+        }
+
+        let tick = this.next;
+        let now = Instant::now();
+
+        this.next = tick + this.period;
+
+        if now >= this.next {
+            match this.missed {
+                MissedTicks::Burst => {}
+                MissedTicks::Delay => this.next = now + this.period,
+                MissedTicks::Skip => {
+                    let periods =
+                        (now - tick).as_nanos() / this.period.as_nanos();
+                    let periods =
+                        u32::try_from(periods + 1).unwrap_or(u32::MAX);
+
+                    this.next = tick + this.period * periods;
+                }
+            }
+        }
+
+        Ready(tick)
+    }
+}
+
+/// Frame produced by a [`Pacer`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Frame {
+    /// Number of frames since the pacer started, which skips the frames
+    /// that were dropped for running late
+    pub index: u64,
+    /// When the frame was scheduled for
+    pub deadline: Instant,
+}
+
+/// What a [`Pacer`] does while the page is hidden, such as in a background
+/// tab.
+///
+/// Only has an effect when building with feature _`web`_ or _`web_lite`_.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Background {
+    /// Stop producing frames until the page is visible again, like
+    /// `requestAnimationFrame()` (default)
+    #[default]
+    Pause,
+    /// Keep producing frames from `setTimeout()`, which browsers throttle
+    /// (often to once per second) while hidden
+    Continue,
+}
+
+/// [`Notify`] that produces [`Frame`]s at a fixed frequency, for game and
+/// audio loops.
+///
+/// This struct is created by [`pacer()`].  See its documentation for more.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub struct Pacer {
+    start: Instant,
+    frequency: f64,
+    next: u64,
+    timer: Timer,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl fmt::Debug for Pacer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pacer")
+            .field("frequency", &self.frequency)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Pacer {
+    /// Set what happens while the page is hidden.
+    pub fn background(mut self, background: Background) -> Self {
+        self.timer = Timer::frames(background);
+        self
+    }
+
+    // Get the deadline of the frame at `index`.
+    fn deadline(&self, index: u64) -> Instant {
+        #[allow(clippy::cast_precision_loss)]
+        let index = index as f64;
+
+        self.start + Duration::from_secs_f64(index / self.frequency)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Notify for Pacer {
+    type Event = Frame;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Frame> {
+        let this = self.get_mut();
+        let deadline = this.deadline(this.next);
+
+        if this.timer.poll(deadline, t).is_pending() {
+            return Pending;
+        }
+
+        let elapsed = (Instant::now() - this.start).as_secs_f64();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let current = (elapsed * this.frequency) as u64;
+        // Skip to the latest frame that's due, dropping the ones missed
+        let index = this.next.max(current);
+        let deadline = this.deadline(index);
+
+        this.next = index + 1;
+
+        Ready(Frame { index, deadline })
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+struct Fed {
+    at: Cell<Instant>,
+    timeout: Duration,
+    // Woken by `feed()`, to restart an `Expired` that's already fired
+    expired: RefCell<WakerSlot>,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Fed {
+    fn deadline(&self) -> Instant {
+        self.at.get() + self.timeout
+    }
+}
+
+/// Supervisor for a task that's expected to check in periodically, such as
+/// a control loop.
+///
+/// The watchdog expires if it isn't [fed](Watchdog::feed()) within its
+/// timeout.  Clones share the same timeout, so one can be moved into the
+/// supervised task and another kept to watch [`Watchdog::expired()`].
+///
+/// ```rust
+/// use pasts::{
+///     prelude::*,
+///     time::{self, Duration, Watchdog},
+/// };
+///
+/// pasts::Executor::default().block_on(async {
+///     let watchdog = Watchdog::new(Duration::from_millis(20));
+///     let mut expired = watchdog.expired();
+///
+///     for _ in 0..3 {
+///         time::sleep(Duration::from_millis(5)).await;
+///         watchdog.feed();
+///     }
+///
+///     // Expires a timeout after the last feeding
+///     assert_eq!(expired.next().await, watchdog.deadline());
+/// });
+/// ```
+#[cfg(any(feature = "std", feature = "web_lite"))]
+#[derive(Clone)]
+pub struct Watchdog(Rc<Fed>);
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("timeout", &self.0.timeout)
+            .field("deadline", &self.0.deadline())
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Watchdog {
+    /// Create a watchdog that expires if not fed within `timeout`, starting
+    /// now.
+    pub fn new(timeout: Duration) -> Self {
+        Self(Rc::new(Fed {
+            at: Cell::new(Instant::now()),
+            timeout,
+            expired: RefCell::new(WakerSlot::new()),
+        }))
+    }
+
+    /// Reset the watchdog's timeout, starting now.
+    pub fn feed(&self) {
+        self.0.at.set(Instant::now());
+        self.0.expired.borrow_mut().wake();
+    }
+
+    /// Get the instant the watchdog expires at, unless it's fed first.
+    pub fn deadline(&self) -> Instant {
+        self.0.deadline()
+    }
+
+    /// Get a [`Notify`] that produces the missed deadline each time the
+    /// watchdog expires.
+    ///
+    /// After expiring, the watchdog doesn't expire again until it's been fed
+    /// and then gone another timeout without being fed.  Only the most
+    /// recently polled [`Expired`] is woken by feeding, so use one at a time.
+    pub fn expired(&self) -> Expired {
+        Expired {
+            fed: self.0.clone(),
+            deadline: self.0.deadline(),
+            fired: false,
+            timer: Timer::default(),
+        }
+    }
+}
+
+/// [`Notify`] that produces the missed deadline each time a [`Watchdog`]
+/// expires.
+///
+/// This struct is created by [`Watchdog::expired()`].  See its
+/// documentation for more.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub struct Expired {
+    fed: Rc<Fed>,
+    deadline: Instant,
+    fired: bool,
+    timer: Timer,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl fmt::Debug for Expired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Expired")
+            .field("deadline", &self.deadline)
+            .field("fired", &self.fired)
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Notify for Expired {
+    type Event = Instant;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Instant> {
+        let this = self.get_mut();
+
+        loop {
+            let deadline = this.fed.deadline();
+
+            if this.fired {
+                if deadline == this.deadline {
+                    this.fed.expired.borrow_mut().register(t.waker());
+                    return Pending;
+                }
+
+                // Fed since firing, so start over
+                this.fired = false;
+                this.deadline = deadline;
+                this.timer.reset();
+            }
+
+            if this.timer.poll(this.deadline, t).is_pending() {
+                return Pending;
+            }
+
+            // Feeding doesn't touch the timer, so check whether it moved the
+            // deadline while waiting
+            if deadline > this.deadline {
+                this.deadline = deadline;
+                continue;
+            }
+
+            this.fired = true;
+
+            return Ready(this.deadline);
+        }
+    }
+}
+
+/// Key for removing an item from a [`DelayQueue`] before it expires.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DelayKey {
+    deadline: Instant,
+    index: u64,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl DelayKey {
+    /// Get the instant that the item expires at.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+}
+
+/// [`Notify`] that produces items once their deadlines pass, earliest first.
+///
+/// Useful for retries, expiring sessions, and scheduled jobs.  Items with
+/// the same deadline are produced in the order they were inserted.
+///
+/// ```rust
+/// use pasts::{
+///     prelude::*,
+///     time::{DelayQueue, Duration},
+/// };
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut queue = DelayQueue::new();
+///
+///     queue.insert("later", Duration::from_millis(10));
+///     queue.insert("sooner", Duration::from_millis(5));
+///
+///     let cancelled = queue.insert("never", Duration::from_millis(1));
+///
+///     assert_eq!(queue.remove(cancelled), Some("never"));
+///     assert_eq!(queue.next().await, "sooner");
+///     assert_eq!(queue.next().await, "later");
+///     assert!(queue.is_empty());
+/// });
+/// ```
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub struct DelayQueue<T> {
+    items: BTreeMap<DelayKey, T>,
+    next_index: u64,
+    // Deadline the timer is waiting for
+    armed: Option<Instant>,
+    timer: Timer,
+    // Woken when an item is inserted ahead of the armed deadline
+    waker: WakerSlot,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<T> fmt::Debug for DelayQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DelayQueue")
+            .field("len", &self.len())
+            .field("next", &self.next_deadline())
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<T> DelayQueue<T> {
+    /// Create an empty queue.
+    pub fn new() -> Self {
+        Self {
+            items: BTreeMap::new(),
+            next_index: 0,
+            armed: None,
+            timer: Timer::default(),
+            waker: WakerSlot::new(),
+        }
+    }
+
+    /// Insert an item that expires once `delay` has elapsed.
+    pub fn insert(&mut self, item: T, delay: Duration) -> DelayKey {
+        self.insert_at(item, Instant::now() + delay)
+    }
+
+    /// Insert an item that expires once `deadline` has passed.
+    pub fn insert_at(&mut self, item: T, deadline: Instant) -> DelayKey {
+        let key = DelayKey {
+            deadline,
+            index: self.next_index,
+        };
+
+        self.next_index += 1;
+        self.items.insert(key, item);
+
+        if self.armed.is_none_or(|armed| deadline < armed) {
+            self.waker.wake();
+        }
+
+        key
+    }
+
+    /// Remove an item before it expires, returning it if it was still in the
+    /// queue.
+    pub fn remove(&mut self, key: DelayKey) -> Option<T> {
+        self.items.remove(&key)
+    }
+
+    /// Get the deadline of the item that expires next.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.items.keys().next().map(DelayKey::deadline)
+    }
+
+    /// Get the number of items in the queue.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<T> Unpin for DelayQueue<T> {}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl<T> Notify for DelayQueue<T> {
+    type Event = T;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        let Some(deadline) = this.next_deadline() else {
+            this.waker.register(t.waker());
+            return Pending;
+        };
+
+        if this.armed != Some(deadline) {
+            this.armed = Some(deadline);
+            this.timer.reset();
+        }
+
+        if this.timer.poll(deadline, t).is_pending() {
+            this.waker.register(t.waker());
+            return Pending;
+        }
+
+        this.armed = None;
+
+        let (_, item) = this.items.pop_first().expect("queue isn't empty");
+
+        Ready(item)
+    }
+}
+
+/// What a [`Schedule`] does when it's polled after more than one of its
+/// times have passed (for example, after the machine was asleep).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CatchUp {
+    /// Produce every missed time right away
+    All,
+    /// Produce only the latest of the missed times (default)
+    #[default]
+    Latest,
+    /// Drop the missed times, and wait for the next one
+    Skip,
+}
+
+/// [`Notify`] that produces wall-clock times aligned to a period, like a
+/// cron job.
+///
+/// Each event is the scheduled time, as the [`Duration`] since the Unix
+/// epoch.
+///
+/// This struct is created by [`schedule()`].  See its documentation for more.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub struct Schedule {
+    period: Duration,
+    offset: Duration,
+    catch_up: CatchUp,
+    // Next scheduled time, since the Unix epoch
+    next: Duration,
+    deadline: Instant,
+    timer: Timer,
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl fmt::Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Schedule")
+            .field("period", &self.period)
+            .field("offset", &self.offset)
+            .field("catch_up", &self.catch_up)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Schedule {
+    /// Shift the times later by `offset` (for example, every hour at 15
+    /// minutes past).  Offsets longer than the period wrap around.
+    pub fn offset(mut self, offset: Duration) -> Self {
+        self.offset = nanos(offset.as_nanos() % self.period.as_nanos());
+        self.arm(self.after(wall_clock()));
+        self
+    }
+
+    /// Set what happens after more than one time is missed.
+    pub fn catch_up(mut self, catch_up: CatchUp) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+
+    /// Get the next scheduled time, as the [`Duration`] since the Unix
+    /// epoch.
+    pub fn next_time(&self) -> Duration {
+        self.next
+    }
+
+    // Get the latest scheduled time at or before `time`.
+    fn latest(&self, time: Duration) -> Option<Duration> {
+        let since = time.checked_sub(self.offset)?.as_nanos();
+        let period = self.period.as_nanos();
+
+        Some(nanos(since - since % period) + self.offset)
+    }
+
+    // Get the first scheduled time after `time`.
+    fn after(&self, time: Duration) -> Duration {
+        self.latest(time)
+            .map_or(self.offset, |latest| latest + self.period)
+    }
+
+    // Wait for the scheduled time `next`.
+    fn arm(&mut self, next: Duration) {
+        self.next = next;
+        self.deadline = Instant::now() + next.saturating_sub(wall_clock());
+        self.timer.reset();
+    }
+}
+
+#[cfg(any(feature = "std", feature = "web_lite"))]
+impl Notify for Schedule {
+    type Event = Duration;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Duration> {
+        let this = self.get_mut();
+
+        loop {
+            if this.timer.poll(this.deadline, t).is_pending() {
+                return Pending;
+            }
+
+            let now = wall_clock();
+            let tick = this.next;
+            // The wall clock may have been set back while waiting
+            let Some(latest) = this.latest(now).filter(|&l| l >= tick) else {
+                this.arm(tick);
+                continue;
+            };
+
+            match this.catch_up {
+                CatchUp::All => this.arm(tick + this.period),
+                CatchUp::Latest => {
+                    this.arm(latest + this.period);
+                    return Ready(latest);
+                }
+                CatchUp::Skip if latest != tick => {
+                    this.arm(latest + this.period);
+                    continue;
+                }
+                CatchUp::Skip => this.arm(tick + this.period),
+            }
+
+            return Ready(tick);
+        }
+    }
+}
+
+/// Create a [`Notify`] that produces the wall-clock times that are a
+/// multiple of `period` since the Unix epoch, starting with the next one.
+///
+/// Times are aligned to the wall clock, so a period of one minute fires at
+/// the start of every minute; see [`Schedule::offset()`] to shift them.
+/// Times are recalculated from the wall clock after each one, so the
+/// schedule follows changes to the system's time.  See
+/// [`Schedule::catch_up()`] for what happens when times are missed.
+///
+/// ```rust,no_run
+/// use pasts::{
+///     prelude::*,
+///     time::{self, Duration},
+/// };
+///
+/// pasts::Executor::default().block_on(async {
+///     // Every hour, at 30 minutes past
+///     let mut hourly = time::schedule(Duration::from_secs(60 * 60))
+///         .offset(Duration::from_secs(30 * 60));
+///
+///     loop {
+///         let time = hourly.next().await;
+///
+///         println!("Cleaning up at {}s since the epoch", time.as_secs());
+///     }
+/// });
+/// ```
+///
+/// # Panics
+/// If `period` is zero.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub fn schedule(period: Duration) -> Schedule {
+    assert!(!period.is_zero(), "schedule period must be non-zero");
+
+    let mut schedule = Schedule {
+        period,
+        offset: Duration::ZERO,
+        catch_up: CatchUp::Latest,
+        next: Duration::ZERO,
+        deadline: Instant::now(),
+        timer: Timer::default(),
+    };
+
+    schedule.arm(schedule.after(wall_clock()));
+    schedule
+}
+
+/// Create a [`Notify`] that produces [`Frame`]s `frequency` times per
+/// second, starting now.
+///
+/// Each frame's deadline is calculated from when the pacer started, rather
+/// than from the previous frame, so that rounding and late wakeups don't
+/// accumulate into drift.  Frames that are missed (because handling a frame
+/// took too long) are dropped.
+///
+/// ```rust
+/// use pasts::{prelude::*, time};
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut frames = time::pacer(240.0);
+///
+///     for index in 0..3 {
+///         assert_eq!(frames.next().await.index, index);
+///     }
+/// });
+/// ```
+///
+/// # Platform-Specific Behavior
+/// When building with feature _`web`_, frames wait for the browser's
+/// `requestAnimationFrame()`, so the frequency is limited to the display's
+/// refresh rate.  Animation frames stop while the page is hidden, unless
+/// [`Pacer::background()`] is set to [`Background::Continue`].
+///
+/// # Panics
+/// If `frequency` isn't positive and finite.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub fn pacer(frequency: f64) -> Pacer {
+    assert!(
+        frequency.is_finite() && frequency > 0.0,
+        "pacer frequency must be positive and finite",
+    );
+
+    Pacer {
+        start: Instant::now(),
+        frequency,
+        next: 0,
+        timer: Timer::frames(Background::default()),
+    }
+}
+
+/// Create a future that completes once `duration` has elapsed.
+///
+/// ```rust
+/// use pasts::time::{self, Duration, Instant};
+///
+/// pasts::Executor::default().block_on(async {
+///     let start = Instant::now();
+///
+///     time::sleep(Duration::from_millis(10)).await;
+///     assert!(start.elapsed() >= Duration::from_millis(10));
+/// });
+/// ```
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub fn sleep(duration: Duration) -> Sleep {
+    sleep_until(Instant::now() + duration)
+}
+
+/// Create a future that completes once `deadline` has passed.
+///
+/// Sleeping until absolute deadlines (rather than for durations) keeps
+/// schedules from drifting, by not counting the time it took to get around to
+/// sleeping.
+///
+/// ```rust
+/// use pasts::time::{self, Duration, Instant};
+///
+/// pasts::Executor::default().block_on(async {
+///     let start = Instant::now();
+///     let mut sleep = time::sleep_until(start + Duration::from_millis(5));
+///
+///     for frame in 1..=3 {
+///         (&mut sleep).await;
+///         sleep.reset(sleep.deadline() + Duration::from_millis(5));
+///     }
+///
+///     assert!(start.elapsed() >= Duration::from_millis(15));
+/// });
+/// ```
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep {
+        deadline,
+        timer: Timer::default(),
+    }
+}
+
+/// Run a future, giving up with [`Elapsed`] once `duration` has elapsed.
+///
+/// `!Unpin` futures can be pinned with [`pasts::pin!()`](crate::pin!()).
+///
+/// ```rust
+/// use pasts::time::{self, Duration, Elapsed};
+///
+/// pasts::Executor::default().block_on(async {
+///     let forever = core::future::pending::<()>();
+///     let result = time::timeout(Duration::from_millis(5), forever).await;
+///
+///     assert_eq!(result, Err(Elapsed));
+/// });
+/// ```
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    timeout_at(Instant::now() + duration, future)
+}
+
+/// Run a future, giving up with [`Elapsed`] once `deadline` has passed.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub fn timeout_at<F: Future>(deadline: Instant, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep_until(deadline),
+    }
+}
+
+/// Create a [`Notify`] that ticks once every `period`, starting one period
+/// from now.
+///
+/// Ticks are scheduled at absolute instants, so the interval doesn't drift
+/// when handling ticks takes time.  See [`Interval::missed_ticks()`] for what
+/// happens when ticks are late.
+///
+/// ```rust
+/// use pasts::{
+///     prelude::*,
+///     time::{self, Duration},
+/// };
+///
+/// pasts::Executor::default().block_on(async {
+///     let mut ticks = time::interval(Duration::from_millis(5));
+///     let first = ticks.next().await;
+///     let second = ticks.next().await;
+///
+///     assert_eq!(second - first, Duration::from_millis(5));
+/// });
+/// ```
+///
+/// # Panics
+/// If `period` is zero.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+pub fn interval(period: Duration) -> Interval {
+    assert!(!period.is_zero(), "interval period must be non-zero");
+
+    Interval {
+        period,
+        next: Instant::now() + period,
+        missed: MissedTicks::Burst,
+        timer: Timer::default(),
+    }
+}
+
+// Convert nanoseconds into a `Duration`, saturating.
+#[cfg(any(feature = "std", feature = "web_lite"))]
+fn nanos(nanos: u128) -> Duration {
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
+// Time since the Unix epoch, from the system's clock.
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+fn wall_clock() -> Duration {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+}
+
+// Time since the Unix epoch, from `Date.now()`.
+#[cfg(feature = "web_lite")]
+fn wall_clock() -> Duration {
+    Duration::from_secs_f64(js_sys::Date::now().max(0.0) / 1000.0)
+}
+
+// Time since the first call, so that instants fit in a `Duration`.
+#[cfg(all(feature = "std", not(feature = "web_lite")))]
+fn now() -> Duration {
+    use std::{sync::OnceLock, time};
+
+    static START: OnceLock<time::Instant> = OnceLock::new();
+
+    START.get_or_init(time::Instant::now).elapsed()
+}
+
+// Clock installed with `set_clock()`.
+#[cfg(not(any(feature = "std", feature = "web_lite")))]
+static CLOCK: once_cell::race::OnceBox<fn() -> Duration> =
+    once_cell::race::OnceBox::new();
+
+/// Install the clock that [`Instant::now()`] reads, which returns the time
+/// since any fixed point (such as boot).
+///
+/// The clock can only be installed once; returns false if one already was.
+///
+/// ```rust,ignore
+/// use pasts::time::{self, Duration};
+///
+/// // Read a hardware counter, which ticks once per microsecond
+/// fn clock() -> Duration {
+///     Duration::from_micros(read_counter())
+/// }
+///
+/// time::set_clock(clock);
+/// ```
+///
+/// # Panics
+/// [`Instant::now()`] panics if it's called before a clock is installed.
+#[cfg(not(any(feature = "std", feature = "web_lite")))]
+pub fn set_clock(clock: fn() -> Duration) -> bool {
+    CLOCK.set(alloc::boxed::Box::new(clock)).is_ok()
+}
+
+// Time from the clock installed with `set_clock()`.
+#[cfg(not(any(feature = "std", feature = "web_lite")))]
+fn now() -> Duration {
+    let clock = CLOCK
+        .get()
+        .expect("no clock installed with `pasts::time::set_clock()`");
+
+    clock()
+}
+
+// Time since the page loaded, from `performance.now()`.  Falls back to the
+// time since the epoch when there's no `performance` (which isn't monotonic).
+#[cfg(feature = "web_lite")]
+fn now() -> Duration {
+    use js_sys::{Date, Function, Reflect};
+
+    let millis = Reflect::get(&js_sys::global(), &"performance".into())
+        .ok()
+        .and_then(|performance| {
+            let now = Reflect::get(&performance, &"now".into()).ok()?;
+
+            if !now.is_function() {
+                return None;
+            }
+
+            Function::from(now).call0(&performance).ok()
+        })
+        .and_then(|now| now.as_f64())
+        .unwrap_or_else(Date::now);
+
+    Duration::from_secs_f64(millis.max(0.0) / 1000.0)
+}
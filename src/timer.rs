@@ -0,0 +1,296 @@
+// Wakes tasks once their deadlines pass, for the futures in `time`.
+//
+// With std, deadlines are kept on a heap by a single timer thread, which
+// sleeps until the earliest one.  With feature `io`, async-io's reactor runs
+// the timers instead (on Linux, with a timerfd), which the default park
+// drives from the executor's thread.  On the web, each timer is a callback
+// from `setTimeout()`.
+
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+use alloc::sync::{Arc, Weak};
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+use core::{cmp::Ordering, task::Waker};
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+use std::{
+    collections::BinaryHeap,
+    sync::{Condvar, Mutex, MutexGuard, OnceLock, PoisonError},
+    thread,
+};
+
+#[cfg(feature = "web_lite")]
+use crate::web::{self, Promise, Visibility};
+use crate::{
+    prelude::*,
+    time::{Background, Instant},
+};
+
+// Registration with the timer, which wakes a task at a deadline.
+#[derive(Debug, Default)]
+pub(crate) struct Timer {
+    #[cfg(not(any(feature = "web_lite", feature = "io")))]
+    slot: Option<Arc<Mutex<Waker>>>,
+    #[cfg(all(feature = "io", not(feature = "web_lite")))]
+    reactor: Option<async_io::Timer>,
+    #[cfg(feature = "web_lite")]
+    promise: Option<Promise>,
+    #[cfg(feature = "web_lite")]
+    frames: Option<Background>,
+}
+
+impl Timer {
+    // Create a timer for pacing animation frames, which waits for
+    // `requestAnimationFrame()` on the web.
+    pub(crate) fn frames(background: Background) -> Self {
+        #[cfg(not(feature = "web_lite"))]
+        let _ = background;
+
+        Self {
+            #[cfg(feature = "web_lite")]
+            frames: Some(background),
+            ..Self::default()
+        }
+    }
+
+    // Check if `deadline` has passed, or register to wake the task once it
+    // does.  The deadline can only change once it's ready, or after `reset()`.
+    #[cfg(not(any(feature = "web_lite", feature = "io")))]
+    pub(crate) fn poll(&mut self, deadline: Instant, t: &mut Task<'_>) -> Poll {
+        if Instant::now() >= deadline {
+            self.slot = None;
+            return Ready(());
+        }
+
+        match self.slot {
+            Some(ref slot) => {
+                let mut waker = lock(slot);
+
+                if !waker.will_wake(t.waker()) {
+                    *waker = t.waker().clone();
+                }
+            }
+            None => {
+                let slot = Arc::new(Mutex::new(t.waker().clone()));
+
+                timers().push(deadline, Arc::downgrade(&slot));
+                self.slot = Some(slot);
+            }
+        }
+
+        Pending
+    }
+
+    // Check if `deadline` has passed, or register to wake the task once it
+    // does.  The deadline can only change once it's ready, or after `reset()`.
+    #[cfg(all(feature = "io", not(feature = "web_lite")))]
+    pub(crate) fn poll(&mut self, deadline: Instant, t: &mut Task<'_>) -> Poll {
+        loop {
+            let now = Instant::now();
+
+            if now >= deadline {
+                self.reactor = None;
+                return Ready(());
+            }
+
+            let reactor = self
+                .reactor
+                .get_or_insert_with(|| async_io::Timer::after(deadline - now));
+
+            if Pin::new(reactor).poll(t).is_pending() {
+                return Pending;
+            }
+
+            // The reactor's clock may be ahead of ours, so check again
+            self.reactor = None;
+        }
+    }
+
+    // Check if `deadline` has passed, or register to wake the task once it
+    // does.  The deadline can only change once it's ready, or after `reset()`.
+    #[cfg(feature = "web_lite")]
+    pub(crate) fn poll(&mut self, deadline: Instant, t: &mut Task<'_>) -> Poll {
+        loop {
+            let now = Instant::now();
+
+            if now >= deadline {
+                self.promise = None;
+                return Ready(());
+            }
+
+            let frames = self.frames;
+            let promise = self
+                .promise
+                .get_or_insert_with(|| set_timeout(deadline - now, frames));
+
+            if Pin::new(promise).poll(t).is_pending() {
+                return Pending;
+            }
+
+            // Timeouts may fire early, so check again
+            self.promise = None;
+        }
+    }
+
+    // Forget the deadline that's registered, so that it can change.
+    pub(crate) fn reset(&mut self) {
+        #[cfg(not(any(feature = "web_lite", feature = "io")))]
+        {
+            self.slot = None;
+        }
+
+        #[cfg(all(feature = "io", not(feature = "web_lite")))]
+        {
+            self.reactor = None;
+        }
+
+        #[cfg(feature = "web_lite")]
+        {
+            self.promise = None;
+        }
+    }
+}
+
+// Create a promise that resolves after `duration`, or on the next animation
+// frame if `frames` is set.
+#[cfg(feature = "web_lite")]
+fn set_timeout(
+    duration: core::time::Duration,
+    frames: Option<Background>,
+) -> Promise {
+    use js_sys::{Function, Reflect};
+
+    // How late an animation frame can be before giving up on it, in case
+    // the page was hidden while waiting
+    const LATE_MILLIS: f64 = 100.0;
+
+    let millis = (duration.as_secs_f64() * 1000.0).ceil();
+    let continues = frames == Some(Background::Continue);
+    let hidden = web::visibility() == Visibility::Hidden;
+
+    Promise::new(|resolve| {
+        let global = js_sys::global();
+        let function = |name: &str| {
+            Reflect::get(&global, &name.into())
+                .ok()
+                .filter(|function| function.is_function())
+                .map(Function::from)
+        };
+        let animation_frame = function("requestAnimationFrame")
+            .filter(|_| frames.is_some() && !(continues && hidden));
+
+        // Without `setTimeout()`, fall back to checking the time repeatedly
+        let _ = match (animation_frame, function("setTimeout")) {
+            (Some(animation_frame), set_timeout) => {
+                if let Some(set_timeout) = set_timeout.filter(|_| continues) {
+                    let late = millis + LATE_MILLIS;
+                    let _ = set_timeout.call2(&global, resolve, &late.into());
+                }
+
+                animation_frame.call1(&global, resolve)
+            }
+            (None, Some(set_timeout)) => {
+                set_timeout.call2(&global, resolve, &millis.into())
+            }
+            (None, None) => Function::from(resolve.clone()).call0(&global),
+        };
+    })
+}
+
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+struct Entry {
+    deadline: Instant,
+    waker: Weak<Mutex<Waker>>,
+}
+
+// Order entries so that the earliest deadline is at the top of the heap.
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+impl Eq for Entry {}
+
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+#[derive(Default)]
+struct Timers {
+    heap: Mutex<BinaryHeap<Entry>>,
+    condvar: Condvar,
+}
+
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+impl Timers {
+    fn push(&self, deadline: Instant, waker: Weak<Mutex<Waker>>) {
+        lock(&self.heap).push(Entry { deadline, waker });
+        self.condvar.notify_one();
+    }
+
+    // Wake tasks as their deadlines pass, forever.
+    fn run(&self) {
+        let mut heap = lock(&self.heap);
+
+        loop {
+            let now = Instant::now();
+
+            while heap.peek().is_some_and(|entry| entry.deadline <= now) {
+                let Some(entry) = heap.pop() else { break };
+
+                // Skip timers that were dropped or reset
+                if let Some(waker) = entry.waker.upgrade() {
+                    lock(&waker).wake_by_ref();
+                }
+            }
+
+            let timeout = heap.peek().map(|entry| entry.deadline - now);
+
+            heap = match timeout {
+                Some(timeout) => {
+                    self.condvar
+                        .wait_timeout(heap, timeout)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .condvar
+                    .wait(heap)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+}
+
+// Get the timer thread, starting it on first use.
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+fn timers() -> &'static Timers {
+    static TIMERS: OnceLock<Timers> = OnceLock::new();
+
+    TIMERS.get_or_init(|| {
+        thread::Builder::new()
+            .name("pasts-timer".into())
+            .spawn(|| timers().run())
+            .expect("failed to spawn timer thread");
+
+        Timers::default()
+    })
+}
+
+// Nothing panics while holding these locks, so they can't be poisoned.
+#[cfg(not(any(feature = "web_lite", feature = "io")))]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,446 @@
+//! Browser integration.
+//!
+//! Browsers pause `requestAnimationFrame()` in background tabs, and throttle
+//! timers.  [`visibility_change()`] lets loops react to the page being hidden
+//! or shown, and [`Background`](crate::time::Background) picks whether
+//! [`time::pacer()`](crate::time::pacer()) keeps going while it's hidden.
+//!
+//! ```rust,no_run
+//! use pasts::{prelude::*, web};
+//!
+//! pasts::Executor::default().block_on(async {
+//!     let mut changes = web::visibility_change();
+//!
+//!     while changes.next().await == web::Visibility::Hidden {
+//!         // Save work before the browser freezes the tab
+//!     }
+//! });
+//! ```
+
+// Tasks and callbacks run on the browser's event loop.  With _`web`_, this
+// is done by wasm-bindgen-futures.  With only _`web_lite`_, a minimal
+// scheduler polls woken tasks from a microtask instead, and callbacks are
+// plain closures, to keep binary size down.
+
+use alloc::rc::Rc;
+#[cfg(not(feature = "web"))]
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    task::Wake,
+};
+#[cfg(not(feature = "web"))]
+use core::task::Waker;
+use core::{
+    cell::{Cell, RefCell},
+    fmt,
+};
+
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{closure::Closure, JsValue};
+#[cfg(feature = "web")]
+use wasm_bindgen_futures::JsFuture;
+
+#[cfg(not(feature = "web"))]
+use crate::sync::{AtomicBool, Ordering};
+use crate::{notify::WakerSlot, prelude::*};
+
+/// Whether the page can be seen, from `document.hidden`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    /// At least partly visible, or there's no document to check
+    Visible,
+    /// In a background tab, minimized, or behind a lock screen
+    Hidden,
+}
+
+/// Get the page's current [`Visibility`].
+pub fn visibility() -> Visibility {
+    let hidden = document()
+        .and_then(|document| Reflect::get(&document, &"hidden".into()).ok())
+        .and_then(|hidden| hidden.as_bool())
+        .unwrap_or(false);
+
+    if hidden {
+        Visibility::Hidden
+    } else {
+        Visibility::Visible
+    }
+}
+
+/// Create a [`Notify`] that produces the page's [`Visibility`] each time it
+/// changes.
+///
+/// Outside of a browser (where there's no `document`), it never produces
+/// anything.
+pub fn visibility_change() -> VisibilityChange {
+    let changed = Rc::new(Changed::default());
+    let listener = document().and_then(|document| {
+        let callback = Closure::<dyn FnMut()>::new({
+            let changed = changed.clone();
+
+            move || {
+                changed.fired.set(true);
+                changed.waker.borrow_mut().wake();
+            }
+        });
+
+        listen(&document, "addEventListener", &callback)?;
+        Some((document, callback))
+    });
+
+    VisibilityChange {
+        last: visibility(),
+        changed,
+        listener,
+    }
+}
+
+/// [`Notify`] that produces the page's [`Visibility`] when it changes.
+///
+/// This struct is created by [`visibility_change()`].  See its documentation
+/// for more.
+pub struct VisibilityChange {
+    last: Visibility,
+    changed: Rc<Changed>,
+    // The document and callback, for removing the listener on drop
+    listener: Option<(JsValue, Closure<dyn FnMut()>)>,
+}
+
+#[derive(Default)]
+struct Changed {
+    fired: Cell<bool>,
+    waker: RefCell<WakerSlot>,
+}
+
+impl fmt::Debug for VisibilityChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VisibilityChange")
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for VisibilityChange {
+    fn drop(&mut self) {
+        if let Some((ref document, ref callback)) = self.listener {
+            listen(document, "removeEventListener", callback);
+        }
+    }
+}
+
+impl Notify for VisibilityChange {
+    type Event = Visibility;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Visibility> {
+        let this = self.get_mut();
+
+        // Register before checking, so a change in between isn't lost
+        this.changed.waker.borrow_mut().register(t.waker());
+
+        if !this.changed.fired.replace(false) {
+            return Pending;
+        }
+
+        // Hiding and showing again between polls isn't a change
+        let visibility = visibility();
+
+        if core::mem::replace(&mut this.last, visibility) == visibility {
+            return Pending;
+        }
+
+        Ready(visibility)
+    }
+}
+
+#[cfg(all(feature = "web", target_feature = "atomics"))]
+type ThreadSpawner = Box<dyn Fn(Box<dyn FnOnce() + Send>) + Send + Sync>;
+
+#[cfg(all(feature = "web", target_feature = "atomics"))]
+static THREAD_SPAWNER: std::sync::OnceLock<ThreadSpawner> =
+    std::sync::OnceLock::new();
+
+/// Set how threads are started, when building for wasm with atomics.
+///
+/// Browsers don't let wasm start threads itself; instead, each thread is a
+/// web worker that loads the same module and shares its memory, which takes
+/// javascript glue specific to how the app is bundled.  `spawner` is called
+/// with the closure to run on a new worker, for
+/// [`Executor::spawn_blocking()`](crate::Executor::spawn_blocking()) and
+/// [`Workers::spawn()`](crate::Workers::spawn()).
+///
+/// Only available with feature _`web`_, since waking a task on another
+/// thread relies on `wasm-bindgen-futures`.
+///
+/// # Panics
+/// If a spawner was already set.
+#[cfg(all(feature = "web", target_feature = "atomics"))]
+pub fn thread_spawner(
+    spawner: impl Fn(Box<dyn FnOnce() + Send>) + Send + Sync + 'static,
+) {
+    assert!(
+        THREAD_SPAWNER.set(Box::new(spawner)).is_ok(),
+        "thread spawner was already set",
+    );
+}
+
+// Start a thread with the spawner from `thread_spawner()`.
+#[cfg(all(feature = "web", target_feature = "atomics"))]
+pub(crate) fn spawn_thread(f: Box<dyn FnOnce() + Send>) {
+    let spawner = THREAD_SPAWNER
+        .get()
+        .expect("no thread spawner set with `web::thread_spawner()`");
+
+    spawner(f);
+}
+
+// Get the number of threads that can run at once, from
+// `navigator.hardwareConcurrency`.
+#[cfg(all(feature = "web", target_feature = "atomics"))]
+pub(crate) fn hardware_concurrency() -> usize {
+    Reflect::get(&js_sys::global(), &"navigator".into())
+        .ok()
+        .and_then(|navigator| {
+            Reflect::get(&navigator, &"hardwareConcurrency".into()).ok()
+        })
+        .and_then(|concurrency| concurrency.as_f64())
+        .filter(|concurrency| *concurrency >= 1.0)
+        .map_or(1, |concurrency| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let concurrency = concurrency as usize;
+
+            concurrency
+        })
+}
+
+// Get the page's `document`, if there is one.
+fn document() -> Option<JsValue> {
+    Reflect::get(&js_sys::global(), &"document".into())
+        .ok()
+        .filter(JsValue::is_object)
+}
+
+// Add or remove the `visibilitychange` listener.
+fn listen(
+    document: &JsValue,
+    method: &str,
+    callback: &Closure<dyn FnMut()>,
+) -> Option<()> {
+    let method = Reflect::get(document, &method.into())
+        .ok()
+        .filter(JsValue::is_function)
+        .map(Function::from)?;
+
+    method
+        .call2(document, &"visibilitychange".into(), callback.as_ref())
+        .ok()
+        .map(drop)
+}
+
+// Spawn a task on the browser's event loop.
+#[cfg(feature = "web")]
+pub(crate) fn spawn_local(f: impl Future<Output = ()> + 'static) {
+    wasm_bindgen_futures::spawn_local(f);
+}
+
+// Spawn a task on the browser's event loop.
+#[cfg(not(feature = "web"))]
+pub(crate) fn spawn_local(f: impl Future<Output = ()> + 'static) {
+    let wakeup = QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        let wakeup = Arc::new(Wakeup {
+            id: queue.next_id,
+            queued: AtomicBool::new(true),
+        });
+
+        queue.next_id += 1;
+        queue.tasks.insert(wakeup.id, (Box::pin(f), wakeup.clone()));
+        wakeup
+    });
+
+    enqueue(wakeup.id);
+}
+
+#[cfg(not(feature = "web"))]
+type Entry = (Pin<Box<dyn Future<Output = ()>>>, Arc<Wakeup>);
+
+// Tasks spawned on this thread, and the ones that have been woken.
+#[cfg(not(feature = "web"))]
+#[derive(Default)]
+struct Queue {
+    tasks: BTreeMap<u64, Entry>,
+    ready: VecDeque<u64>,
+    next_id: u64,
+    scheduled: bool,
+}
+
+#[cfg(not(feature = "web"))]
+std::thread_local! {
+    static QUEUE: RefCell<Queue> = RefCell::default();
+}
+
+// Waker for a task, which queues it to be polled from the next microtask.
+//
+// Wasm without threads only has the one thread, so the thread-local queue
+// is always the one the task was spawned on.
+#[cfg(not(feature = "web"))]
+struct Wakeup {
+    id: u64,
+    queued: AtomicBool,
+}
+
+#[cfg(not(feature = "web"))]
+impl Wake for Wakeup {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.queued.swap(true, Ordering::SeqCst) {
+            enqueue(self.id);
+        }
+    }
+}
+
+// Queue a task to be polled, scheduling a microtask if there isn't one.
+#[cfg(not(feature = "web"))]
+fn enqueue(id: u64) {
+    let schedule = QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+
+        queue.ready.push_back(id);
+        !core::mem::replace(&mut queue.scheduled, true)
+    });
+
+    if !schedule {
+        return;
+    }
+
+    let callback = Closure::<dyn FnMut()>::once_into_js(run);
+    let global = js_sys::global();
+    let function = |name: &str| {
+        Reflect::get(&global, &name.into())
+            .ok()
+            .filter(JsValue::is_function)
+            .map(Function::from)
+    };
+
+    // Without `queueMicrotask()`, fall back to `setTimeout()`
+    let _ = match (function("queueMicrotask"), function("setTimeout")) {
+        (Some(queue_microtask), _) => queue_microtask.call1(&global, &callback),
+        (None, Some(set_timeout)) => set_timeout.call1(&global, &callback),
+        (None, None) => Ok(JsValue::UNDEFINED),
+    };
+}
+
+// Poll the tasks that were woken before this microtask.
+#[cfg(not(feature = "web"))]
+fn run() {
+    let ready = QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+
+        queue.scheduled = false;
+        core::mem::take(&mut queue.ready)
+    });
+
+    for id in ready {
+        // Taken out of the queue while polling, so that it can spawn tasks
+        let Some((mut task, wakeup)) =
+            QUEUE.with(|queue| queue.borrow_mut().tasks.remove(&id))
+        else {
+            continue;
+        };
+
+        wakeup.queued.store(false, Ordering::SeqCst);
+
+        let waker = Waker::from(wakeup.clone());
+
+        if task
+            .as_mut()
+            .poll(&mut Task::from_waker(&waker))
+            .is_pending()
+        {
+            QUEUE.with(|queue| {
+                queue.borrow_mut().tasks.insert(id, (task, wakeup));
+            });
+        }
+    }
+}
+
+// Future that waits for a javascript callback to be called.
+#[derive(Debug)]
+pub(crate) struct Promise {
+    #[cfg(feature = "web")]
+    future: JsFuture,
+    #[cfg(not(feature = "web"))]
+    resolved: Rc<Resolved>,
+}
+
+#[cfg(not(feature = "web"))]
+#[derive(Debug, Default)]
+struct Resolved {
+    done: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl Promise {
+    // Create a future that's ready once the callback passed to `register`
+    // is called.
+    pub(crate) fn new(register: impl FnOnce(&JsValue)) -> Self {
+        #[cfg(feature = "web")]
+        {
+            let mut register = Some(register);
+            let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+                if let Some(register) = register.take() {
+                    register(&resolve);
+                }
+            });
+
+            Self {
+                future: JsFuture::from(promise),
+            }
+        }
+
+        #[cfg(not(feature = "web"))]
+        {
+            let resolved = Rc::new(Resolved::default());
+            // May be called more than once, like resolving a promise
+            let callback = Closure::<dyn FnMut()>::new({
+                let resolved = resolved.clone();
+
+                move || {
+                    resolved.done.set(true);
+
+                    if let Some(waker) = resolved.waker.take() {
+                        waker.wake();
+                    }
+                }
+            });
+
+            register(&callback.into_js_value());
+
+            Self { resolved }
+        }
+    }
+}
+
+impl Future for Promise {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll {
+        #[cfg(feature = "web")]
+        {
+            Pin::new(&mut self.get_mut().future).poll(t).map(|_| ())
+        }
+
+        #[cfg(not(feature = "web"))]
+        {
+            if self.resolved.done.get() {
+                return Ready(());
+            }
+
+            *self.resolved.waker.borrow_mut() = Some(t.waker().clone());
+            Pending
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Run an [`Executor`] on [winit](https://docs.rs/winit)'s event loop.
+//!
+//! Winit owns the main thread's event loop, so rather than blocking on a
+//! future, [`run()`] hands the thread over to winit and calls
+//! [`Ticker::tick()`](crate::Ticker::tick()) after each event.  The events
+//! themselves are received from [`Events`], which is a [`Notify`], so that the
+//! application logic can be written as a pasts [`Loop`](crate::Loop).
+//!
+//! ```rust,no_run
+//! use pasts::{prelude::*, winit::{Event, Events}, Executor, Loop};
+//! use winit::{event::WindowEvent, window::Window};
+//!
+//! struct App {
+//!     events: Events,
+//!     window: Option<Window>,
+//! }
+//!
+//! impl App {
+//!     fn event(&mut self, event: Event) -> Poll {
+//!         match event {
+//!             Event::Window {
+//!                 event: WindowEvent::CloseRequested,
+//!                 ..
+//!             } => Ready(()),
+//!             _ => Pending,
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     pasts::winit::run(Executor::default(), |mut events| async move {
+//!         // Windows can only be created once the application has resumed
+//!         while !matches!(events.next().await, Event::Resumed) {}
+//!
+//!         let window = events.create_window(Window::default_attributes());
+//!         let window = Some(window.await.unwrap());
+//!         let mut app = App { events, window };
+//!
+//!         Loop::new(&mut app).on(|s| &mut s.events, App::event).await;
+//!     })
+//!     .unwrap();
+//! }
+//! ```
+
+use alloc::{collections::VecDeque, rc::Rc, vec::Vec};
+use core::{cell::RefCell, fmt};
+
+use winit::{
+    application::ApplicationHandler,
+    error::{EventLoopError, OsError},
+    event::{DeviceEvent, DeviceId, WindowEvent},
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowAttributes, WindowId},
+};
+
+use crate::{notify::WakerSlot, prelude::*, Executor, Ticker};
+
+/// Event from winit's event loop, received from [`Events`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Event {
+    /// The application has been resumed, and windows may be created
+    Resumed,
+    /// The application has been suspended
+    Suspended,
+    /// Event for one of the application's windows
+    Window {
+        /// Which window the event is for
+        id: WindowId,
+        /// What happened
+        event: WindowEvent,
+    },
+    /// Event from an input device
+    Device {
+        /// Which device the event is from
+        id: DeviceId,
+        /// What happened
+        event: DeviceEvent,
+    },
+    /// The system is low on memory
+    MemoryWarning,
+}
+
+// Window waiting to be created on the next event loop callback.
+struct Request {
+    attributes: Option<WindowAttributes>,
+    window: Option<Result<Window, OsError>>,
+    waker: WakerSlot,
+}
+
+#[derive(Default)]
+struct Shared {
+    events: VecDeque<Event>,
+    waker: WakerSlot,
+    requests: Vec<Rc<RefCell<Request>>>,
+    exit: bool,
+}
+
+/// [`Notify`] of the events from winit's event loop, passed to the future
+/// started by [`run()`].
+///
+/// Clones receive from the same queue of events.
+#[derive(Clone, Default)]
+pub struct Events(Rc<RefCell<Shared>>);
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shared = self.0.borrow();
+
+        f.debug_struct("Events")
+            .field("events", &shared.events.len())
+            .field("exit", &shared.exit)
+            .finish()
+    }
+}
+
+impl Events {
+    /// Create a window on the event loop's next callback.
+    ///
+    /// Windows can't be created before [`Event::Resumed`] on some platforms.
+    pub fn create_window(&self, attributes: WindowAttributes) -> CreateWindow {
+        let request = Rc::new(RefCell::new(Request {
+            attributes: Some(attributes),
+            window: None,
+            waker: WakerSlot::new(),
+        }));
+
+        self.0.borrow_mut().requests.push(request.clone());
+        CreateWindow(request)
+    }
+
+    /// Stop the event loop, even if there are tasks left on the executor.
+    pub fn exit(&self) {
+        self.0.borrow_mut().exit = true;
+    }
+}
+
+impl Notify for Events {
+    type Event = Event;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Event> {
+        let mut shared = self.0.borrow_mut();
+
+        match shared.events.pop_front() {
+            Some(event) => Ready(event),
+            None => {
+                shared.waker.register(t.waker());
+                Pending
+            }
+        }
+    }
+}
+
+/// Future returned from [`Events::create_window()`].
+#[derive(Debug)]
+pub struct CreateWindow(Rc<RefCell<Request>>);
+
+impl fmt::Debug for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Request")
+            .field("created", &self.window.is_some())
+            .finish()
+    }
+}
+
+impl Future for CreateWindow {
+    type Output = Result<Window, OsError>;
+
+    fn poll(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Self::Output> {
+        let mut request = self.0.borrow_mut();
+
+        match request.window.take() {
+            Some(window) => Ready(window),
+            None => {
+                request.waker.register(t.waker());
+                Pending
+            }
+        }
+    }
+}
+
+// Wakes the event loop when a task is woken.
+#[derive(Debug)]
+struct Wake;
+
+struct App {
+    ticker: Ticker,
+    events: Events,
+}
+
+impl App {
+    // Queue an event for the tasks, and let them handle it.
+    fn event(&mut self, event_loop: &ActiveEventLoop, event: Event) {
+        let mut shared = self.events.0.borrow_mut();
+
+        shared.events.push_back(event);
+        shared.waker.wake();
+        drop(shared);
+        self.tick(event_loop);
+    }
+
+    // Run the tasks, creating requested windows, until they're waiting.
+    fn tick(&mut self, event_loop: &ActiveEventLoop) {
+        loop {
+            let poll = self.ticker.tick();
+            let requests =
+                core::mem::take(&mut self.events.0.borrow_mut().requests);
+
+            if self.events.0.borrow().exit || poll.is_ready() {
+                event_loop.exit();
+                return;
+            }
+
+            if requests.is_empty() {
+                return;
+            }
+
+            for request in requests {
+                let mut request = request.borrow_mut();
+
+                if let Some(attributes) = request.attributes.take() {
+                    request.window = Some(event_loop.create_window(attributes));
+                    request.waker.wake();
+                }
+            }
+        }
+    }
+}
+
+impl ApplicationHandler<Wake> for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        self.event(event_loop, Event::Resumed);
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.event(event_loop, Event::Suspended);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        id: WindowId,
+        event: WindowEvent,
+    ) {
+        self.event(event_loop, Event::Window { id, event });
+    }
+
+    fn device_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        self.event(event_loop, Event::Device { id, event });
+    }
+
+    fn memory_warning(&mut self, event_loop: &ActiveEventLoop) {
+        self.event(event_loop, Event::MemoryWarning);
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, _: Wake) {
+        self.tick(event_loop);
+    }
+}
+
+/// Run `main` on `executor`, with winit's event loop driving the executor
+/// instead of [`Executor::block_on()`].
+///
+/// `main` is called with the [`Events`] from the event loop.  Returns once
+/// there are no tasks left on the executor, or [`Events::exit()`] is called.
+///
+/// The executor's [`wake_hook`](Executor::wake_hook()) is replaced, to wake
+/// the event loop whenever one of its tasks is woken.
+pub fn run<F>(
+    executor: Executor,
+    main: impl FnOnce(Events) -> F,
+) -> Result<(), EventLoopError>
+where
+    F: Future<Output = ()> + 'static,
+{
+    let event_loop = EventLoop::<Wake>::with_user_event().build()?;
+    let proxy = event_loop.create_proxy();
+    let executor = executor.wake_hook(move |_| {
+        let _ = proxy.send_event(Wake);
+    });
+    let events = Events::default();
+
+    executor.spawn_local(main(events.clone()));
+    event_loop.run_app(&mut App {
+        ticker: executor.ticker(),
+        events,
+    })
+}
@@ -0,0 +1,243 @@
+use alloc::{collections::VecDeque, sync::Arc};
+use core::{fmt, task::Waker};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::prelude::*;
+
+struct Queue<T> {
+    items: VecDeque<T>,
+    // Receivers waiting for an item, in the order they started waiting
+    waiters: VecDeque<(u64, Waker)>,
+    next_id: u64,
+    senders: usize,
+}
+
+impl<T> Queue<T> {
+    // Wake the receiver that's been waiting the longest, if any.
+    fn wake_one(&mut self) -> Option<Waker> {
+        self.waiters.pop_front().map(|(_, waker)| waker)
+    }
+}
+
+struct Shared<T>(Mutex<Queue<T>>);
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, Queue<T>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Create a work queue, where each item sent is received by only one of the
+/// receivers.
+///
+/// Clone the [`WorkReceiver`] to add workers, which compete for items.  Once
+/// every [`WorkSender`] is dropped, receivers drain the remaining items, and
+/// then produce [`None`].
+///
+/// ```rust
+/// use pasts::{prelude::*, sync, Executor};
+///
+/// let executor = Executor::default();
+/// let (sender, receiver) = sync::work_queue();
+///
+/// for worker in 0..2 {
+///     let mut receiver = receiver.clone();
+///
+///     executor.spawn(async move {
+///         while let Some(job) = receiver.next().await {
+///             println!("worker {worker} got job {job}");
+///         }
+///     });
+/// }
+///
+/// for job in 0..4 {
+///     sender.send(job);
+/// }
+///
+/// drop((sender, receiver));
+/// executor.block_on(async {});
+/// ```
+pub fn work_queue<T>() -> (WorkSender<T>, WorkReceiver<T>) {
+    let shared = Arc::new(Shared(Mutex::new(Queue {
+        items: VecDeque::new(),
+        waiters: VecDeque::new(),
+        next_id: 1,
+        senders: 1,
+    })));
+    let receiver = WorkReceiver {
+        shared: shared.clone(),
+        id: 0,
+        closed: false,
+    };
+
+    (WorkSender(shared), receiver)
+}
+
+/// Sending side of a [`work_queue()`].
+///
+/// Clones send into the same queue.
+pub struct WorkSender<T>(Arc<Shared<T>>);
+
+impl<T> fmt::Debug for WorkSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkSender")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> Clone for WorkSender<T> {
+    fn clone(&self) -> Self {
+        self.0.lock().senders += 1;
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Drop for WorkSender<T> {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut queue = self.0.lock();
+
+            queue.senders -= 1;
+
+            if queue.senders != 0 {
+                return;
+            }
+
+            core::mem::take(&mut queue.waiters)
+        };
+
+        // Let the waiting receivers know the queue is closed
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> WorkSender<T> {
+    /// Add an item to the queue, waking one waiting receiver.
+    pub fn send(&self, item: T) {
+        let waker = {
+            let mut queue = self.0.lock();
+
+            queue.items.push_back(item);
+            queue.wake_one()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Get the number of items that haven't been received yet.
+    pub fn len(&self) -> usize {
+        self.0.lock().items.len()
+    }
+
+    /// Check if every item sent has been received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Receiving side of a [`work_queue()`], which is a [`Notify`] for the items.
+///
+/// Clones compete for items, with each item going to one of them.
+/// Produces [`None`] once, after every [`WorkSender`] is dropped and the
+/// queue is empty.
+pub struct WorkReceiver<T> {
+    shared: Arc<Shared<T>>,
+    id: u64,
+    closed: bool,
+}
+
+impl<T> fmt::Debug for WorkReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkReceiver")
+            .field("closed", &self.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Clone for WorkReceiver<T> {
+    fn clone(&self) -> Self {
+        let mut queue = self.shared.lock();
+        let id = queue.next_id;
+
+        queue.next_id += 1;
+
+        Self {
+            shared: self.shared.clone(),
+            id,
+            closed: self.closed,
+        }
+    }
+}
+
+impl<T> Drop for WorkReceiver<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut queue = self.shared.lock();
+
+            queue.waiters.retain(|(id, _)| *id != self.id);
+
+            // This receiver may have been woken for an item it won't take
+            if queue.items.is_empty() {
+                return;
+            }
+
+            queue.wake_one()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Unpin for WorkReceiver<T> {}
+
+impl<T> Notify for WorkReceiver<T> {
+    type Event = Option<T>;
+
+    fn poll_next(self: Pin<&mut Self>, t: &mut Task<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+
+        if this.closed {
+            return Pending;
+        }
+
+        let mut queue = this.shared.lock();
+
+        if let Some(item) = queue.items.pop_front() {
+            // Pass the rest of the items on to other waiting receivers
+            let waker = (!queue.items.is_empty())
+                .then(|| queue.wake_one())
+                .flatten();
+
+            drop(queue);
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+
+            return Ready(Some(item));
+        }
+
+        if queue.senders == 0 {
+            this.closed = true;
+            return Ready(None);
+        }
+
+        let id = this.id;
+
+        match queue.waiters.iter_mut().find(|(waiter, _)| *waiter == id) {
+            Some((_, waker)) if waker.will_wake(t.waker()) => {}
+            Some((_, waker)) => *waker = t.waker().clone(),
+            None => queue.waiters.push_back((id, t.waker().clone())),
+        }
+
+        Pending
+    }
+}
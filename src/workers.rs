@@ -0,0 +1,250 @@
+use alloc::{string::String, sync::Arc};
+use core::fmt;
+#[cfg(not(feature = "web_lite"))]
+use std::thread;
+
+use crate::{blocking, prelude::*, Executor};
+
+type Hook = Arc<dyn Fn(usize) + Send + Sync>;
+type Name = Arc<dyn Fn(usize) -> String + Send + Sync>;
+
+// Calls the stop hook when the worker exits, even if it's unwinding.
+struct Stop(Option<Hook>, usize);
+
+impl Drop for Stop {
+    fn drop(&mut self) {
+        if let Some(ref on_stop) = self.0 {
+            on_stop(self.1);
+        }
+    }
+}
+
+/// Thread-per-core runner, with a separate [`Executor`] on each worker
+/// thread.
+///
+/// # Scope
+/// Tasks are sticky: they always stay on the worker that spawned them, and
+/// there's no work stealing.  Nothing is shared between workers, which keeps
+/// cache behavior predictable, at the cost of balancing work across workers
+/// being up to the application (for example, by handing each worker its own
+/// set of connections).
+///
+/// There's no built-in option to pin worker threads to cores either, since
+/// it needs platform-specific calls.  Applications that want pinning can
+/// call into a crate such as `core_affinity` from [`Workers::on_start()`],
+/// which runs on each worker thread with the worker's index before its
+/// executor starts.
+///
+/// ```rust
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use pasts::Workers;
+///
+/// let started = Arc::new(AtomicUsize::new(0));
+///
+/// Workers::new().count(4).block_on(|index, executor| {
+///     let started = started.clone();
+///
+///     executor.spawn(async move {
+///         println!("Hello from worker {index}");
+///     });
+///
+///     async move {
+///         started.fetch_add(1, Ordering::Relaxed);
+///     }
+/// });
+///
+/// assert_eq!(started.load(Ordering::Relaxed), 4);
+/// ```
+#[derive(Clone)]
+pub struct Workers {
+    count: usize,
+    name: Option<Name>,
+    on_start: Option<Hook>,
+    on_stop: Option<Hook>,
+}
+
+impl fmt::Debug for Workers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Workers")
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl Default for Workers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Workers {
+    /// Create a runner with one worker per core.
+    pub fn new() -> Self {
+        let count = blocking::available_parallelism();
+
+        Self {
+            count,
+            name: None,
+            on_start: None,
+            on_stop: None,
+        }
+    }
+
+    /// Set the number of worker threads (at least one).
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count.max(1);
+        self
+    }
+
+    /// Name each worker thread with the string returned from `name`, which is
+    /// called with the worker's index.
+    ///
+    /// Thread names show up in panic messages, debuggers and profilers.
+    pub fn name(
+        mut self,
+        name: impl Fn(usize) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.name = Some(Arc::new(name));
+        self
+    }
+
+    /// Call `hook` on each worker thread, with the worker's index, before its
+    /// executor starts.
+    ///
+    /// This can be used to set up thread-local state (such as profilers,
+    /// allocators or thread priorities) on the worker threads, or to pin
+    /// each worker to a core:
+    ///
+    /// ```rust,ignore
+    /// let cores = core_affinity::get_core_ids().unwrap();
+    ///
+    /// Workers::new()
+    ///     .count(cores.len())
+    ///     .on_start(move |index| {
+    ///         core_affinity::set_for_current(cores[index]);
+    ///     })
+    /// ```
+    pub fn on_start(
+        mut self,
+        hook: impl Fn(usize) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_start = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` on each worker thread, with the worker's index, after its
+    /// executor has finished (including when the worker panics).
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_, the executor keeps running tasks
+    /// on the worker's event loop, so this is called once the future
+    /// returned from `main` completes instead.
+    pub fn on_stop(
+        mut self,
+        hook: impl Fn(usize) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_stop = Some(Arc::new(hook));
+        self
+    }
+
+    /// Start a thread for each worker, and run the future returned from
+    /// `main` on it, along with any tasks it spawns.
+    ///
+    /// `main` is called on each worker thread with the worker's index, and
+    /// the executor for that thread.  Returns once every worker's executor
+    /// has no tasks left.
+    ///
+    /// # Panics
+    /// If any worker thread panics, once the others have finished, or if the
+    /// operating system fails to create a thread.
+    ///
+    /// # Platform-Specific Behavior
+    /// Not available when building with feature _`web`_, since the browser's
+    /// main thread can't block; use [`Workers::spawn()`] instead.
+    #[cfg(not(feature = "web_lite"))]
+    pub fn block_on<M, F>(self, main: M)
+    where
+        M: Fn(usize, Executor) -> F + Sync,
+        F: Future<Output = ()> + 'static,
+    {
+        thread::scope(|scope| {
+            for index in 0..self.count {
+                let main = &main;
+                let this = &self;
+                let mut builder = thread::Builder::new();
+
+                if let Some(ref name) = self.name {
+                    builder = builder.name(name(index));
+                }
+
+                builder
+                    .spawn_scoped(scope, move || this.work(index, main))
+                    .expect("failed to spawn worker thread");
+            }
+        });
+    }
+
+    /// Start a thread for each worker, and run the future returned from
+    /// `main` on it, without waiting for the workers to finish.
+    ///
+    /// Like [`Workers::block_on()`], but `main` has to be `'static`, since
+    /// the workers can outlive the caller.
+    ///
+    /// # Panics
+    /// If the operating system fails to create a thread.
+    ///
+    /// # Platform-Specific Behavior
+    /// When building with feature _`web`_ for wasm with atomics, workers are
+    /// started with the spawner set with
+    /// [`web::thread_spawner()`](crate::web::thread_spawner()), and panics
+    /// if there isn't one.
+    pub fn spawn<M, F>(self, main: M)
+    where
+        M: Fn(usize, Executor) -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + 'static,
+    {
+        let this = Arc::new(self);
+        let main = Arc::new(main);
+
+        for index in 0..this.count {
+            let name = this.name.as_ref().map(|name| name(index));
+            let (this, main) = (this.clone(), main.clone());
+
+            blocking::spawn_thread(name, move || this.work(index, &*main))
+                .expect("failed to spawn worker thread");
+        }
+    }
+
+    // Run worker `index` on the current thread.
+    fn work<M, F>(&self, index: usize, main: &M)
+    where
+        M: Fn(usize, Executor) -> F,
+        F: Future<Output = ()> + 'static,
+    {
+        let stop = Stop(self.on_stop.clone(), index);
+        let executor = Executor::default();
+
+        if let Some(ref on_start) = self.on_start {
+            on_start(index);
+        }
+
+        let main = main(index, executor.clone());
+
+        // On the web, `block_on()` returns right away, so stop with `main`
+        #[cfg(feature = "web_lite")]
+        let main = async move {
+            let _stop = stop;
+
+            main.await;
+        };
+
+        executor.block_on(main);
+
+        #[cfg(not(feature = "web_lite"))]
+        drop(stop);
+    }
+}
@@ -0,0 +1,69 @@
+//! Model check the executor's park / unpark and cross-thread wake paths.
+//!
+//! ```shell
+//! RUSTFLAGS="--cfg loom" cargo test --test loom --release
+//! ```
+
+#![cfg(loom)]
+
+use std::{future, sync::Arc, task::Poll};
+
+use pasts::{
+    sync::{thread, AtomicBool, Ordering},
+    Executor,
+};
+
+// Wait for `threads` threads to each set a flag and wake the task.
+async fn woken_from_threads(threads: usize) {
+    let flags: Vec<_> = (0..threads)
+        .map(|_| Arc::new(AtomicBool::new(false)))
+        .collect();
+    let mut started = false;
+
+    future::poll_fn(move |t| {
+        if !started {
+            started = true;
+
+            for flag in &flags {
+                let flag = flag.clone();
+                let waker = t.waker().clone();
+
+                thread::spawn(move || {
+                    flag.store(true, Ordering::SeqCst);
+                    waker.wake();
+                });
+            }
+        }
+
+        if flags.iter().all(|flag| flag.load(Ordering::SeqCst)) {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    })
+    .await;
+}
+
+#[test]
+fn wake_from_another_thread() {
+    loom::model(|| {
+        Executor::default().block_on(woken_from_threads(1));
+    });
+}
+
+#[test]
+fn wakes_from_racing_threads() {
+    loom::model(|| {
+        Executor::default().block_on(woken_from_threads(2));
+    });
+}
+
+#[test]
+fn wake_spawned_task_from_another_thread() {
+    loom::model(|| {
+        let executor = Executor::default();
+
+        executor.spawn(woken_from_threads(1));
+        executor.block_on(async {});
+    });
+}
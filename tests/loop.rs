@@ -0,0 +1,337 @@
+use std::{cell::RefCell, rc::Rc};
+
+use pasts::{notify, prelude::*, Executor, Loop, PollOrder};
+
+type Source = notify::PollFn<fn(&mut Task<'_>) -> Poll<usize>>;
+
+struct App {
+    first: Source,
+    second: Source,
+    events: Rc<RefCell<Vec<usize>>>,
+}
+
+impl App {
+    fn event(&mut self, source: usize) -> Poll {
+        let mut events = self.events.borrow_mut();
+
+        events.push(source);
+
+        if events.len() == 4 {
+            return Ready(());
+        }
+
+        Pending
+    }
+}
+
+// Run a loop over two always-ready sources, returning which ones won.
+fn run(order: Option<PollOrder>, batch: bool) -> Vec<usize> {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let mut app = App {
+        first: notify::poll_fn(|_| Ready(0)),
+        second: notify::poll_fn(|_| Ready(1)),
+        events: events.clone(),
+    };
+
+    Executor::default().block_on(async move {
+        let mut looper = Loop::new(&mut app)
+            .on(|s| &mut s.first, App::event)
+            .on(|s| &mut s.second, App::event);
+
+        if let Some(order) = order {
+            looper = looper.order(order);
+        }
+
+        if batch {
+            looper = looper.batch();
+        }
+
+        looper.await;
+    });
+
+    events.take()
+}
+
+#[test]
+fn loop_poll_order() {
+    assert_eq!(run(None, false), [1, 1, 1, 1]);
+    assert_eq!(run(Some(PollOrder::NewestFirst), false), [1, 1, 1, 1]);
+    assert_eq!(run(Some(PollOrder::OldestFirst), false), [0, 0, 0, 0]);
+    assert_eq!(run(Some(PollOrder::RoundRobin), false), [0, 1, 0, 1]);
+}
+
+#[test]
+fn loop_batch() {
+    assert_eq!(run(None, true), [1, 0, 1, 0]);
+    assert_eq!(run(Some(PollOrder::OldestFirst), true), [0, 1, 0, 1]);
+}
+
+#[test]
+fn pin_loop_over_unpin_state() {
+    use std::{cell::Cell, marker::PhantomPinned};
+
+    use pasts::PinLoop;
+
+    // Notify that can't be moved once pinned
+    struct Counter {
+        count: Cell<u32>,
+        _pinned: PhantomPinned,
+    }
+
+    impl Notify for Counter {
+        type Event = u32;
+
+        fn poll_next(self: Pin<&mut Self>, _: &mut Task<'_>) -> Poll<u32> {
+            self.count.set(self.count.get() + 1);
+            Ready(self.count.get())
+        }
+    }
+
+    Executor::default().block_on(async {
+        let state = core::pin::pin!(Counter {
+            count: Cell::new(0),
+            _pinned: PhantomPinned,
+        });
+        let count = PinLoop::new(state)
+            .on(
+                |s| s,
+                |s, count| {
+                    assert_eq!(s.count.get(), count);
+
+                    if count == 3 {
+                        return Ready(count);
+                    }
+
+                    Pending
+                },
+            )
+            .await;
+
+        assert_eq!(count, 3);
+    });
+}
+
+#[test]
+fn loop_flow_and_poll_handlers() {
+    use pasts::Flow;
+
+    struct Counter {
+        ticks: notify::PollFn<fn(&mut Task<'_>) -> Poll<u32>>,
+        never: notify::Pending<u32>,
+        seen: u32,
+    }
+
+    impl Counter {
+        fn flow(&mut self, _tick: u32) -> Flow<u32> {
+            self.seen += 1;
+
+            if self.seen == 3 {
+                return Flow::Break(self.seen);
+            }
+
+            Flow::Continue
+        }
+
+        fn poll(&mut self, _tick: u32) -> Poll<u32> {
+            self.seen += 10;
+            Pending
+        }
+    }
+
+    Executor::default().block_on(async {
+        let mut counter = Counter {
+            ticks: notify::poll_fn(|_| Ready(1)),
+            never: notify::pending(),
+            seen: 0,
+        };
+        let seen = Loop::new(&mut counter)
+            .on(|s| &mut s.never, Counter::poll)
+            .on(|s| &mut s.ticks, Counter::flow)
+            .await;
+
+        assert_eq!(seen, 3);
+    });
+}
+
+#[test]
+fn loop_into_notify_merges_outputs() {
+    struct State {
+        first: notify::Ready<u32>,
+        second: notify::Ready<u32>,
+        handled: u32,
+    }
+
+    impl State {
+        fn event(&mut self, n: u32) -> Poll<u32> {
+            self.handled += 1;
+            Ready(n * 10)
+        }
+    }
+
+    Executor::default().block_on(async {
+        let mut state = State {
+            first: notify::ready(1),
+            second: notify::ready(2),
+            handled: 0,
+        };
+        let mut merged = Loop::new(&mut state)
+            .on(|s| &mut s.first, State::event)
+            .on(|s| &mut s.second, State::event)
+            .order(PollOrder::OldestFirst)
+            .into_notify();
+
+        assert_eq!(merged.next().await, 10);
+        assert_eq!(merged.next().await, 20);
+        drop(merged);
+        assert_eq!(state.handled, 2);
+    });
+}
+
+#[test]
+fn loop_on_future_rearms() {
+    struct State {
+        started: u32,
+        ticks: notify::Ready<u32>,
+        outputs: Vec<u32>,
+    }
+
+    impl State {
+        fn output(&mut self, output: u32) -> Poll<Vec<u32>> {
+            self.outputs.push(output);
+
+            if self.outputs.len() == 4 {
+                return Ready(core::mem::take(&mut self.outputs));
+            }
+
+            Pending
+        }
+    }
+
+    Executor::default().block_on(async {
+        let mut state = State {
+            started: 0,
+            ticks: notify::ready(100),
+            outputs: Vec::new(),
+        };
+        let outputs = Loop::new(&mut state)
+            .on_future(
+                |s| {
+                    s.started += 1;
+
+                    let started = s.started;
+
+                    async move { started }
+                },
+                State::output,
+            )
+            .on(|s| &mut s.ticks, State::output)
+            .await;
+
+        assert_eq!(outputs, [100, 1, 2, 3]);
+        // Not started again after the loop exited
+        assert_eq!(state.started, 3);
+    });
+}
+
+#[test]
+fn switch_only_runs_active_mode_handlers() {
+    use pasts::{Flow, ModeLoop, Transition};
+
+    #[derive(Copy, Clone, Debug)]
+    enum Mode {
+        Even,
+        Odd,
+    }
+
+    struct Modes {
+        numbers: LocalBoxNotify<'static, u32>,
+        log: Vec<(&'static str, u32)>,
+    }
+
+    impl Modes {
+        fn even(&mut self, n: u32) -> Flow<Transition<Mode, usize>> {
+            self.log.push(("even", n));
+            Flow::Break(Transition::To(Mode::Odd))
+        }
+
+        fn odd(&mut self, n: u32) -> Flow<Transition<Mode, usize>> {
+            self.log.push(("odd", n));
+
+            if n == 5 {
+                return Flow::Break(Transition::Exit(self.log.len()));
+            }
+
+            Flow::Break(Transition::To(Mode::Even))
+        }
+    }
+
+    fn run(modes: &mut Modes, mode: Mode) -> ModeLoop<'_, Mode, usize> {
+        match mode {
+            Mode::Even => {
+                Box::pin(Loop::new(modes).on(|s| &mut s.numbers, Modes::even))
+            }
+            Mode::Odd => {
+                Box::pin(Loop::new(modes).on(|s| &mut s.numbers, Modes::odd))
+            }
+        }
+    }
+
+    Executor::default().block_on(async {
+        let mut next = 0;
+        let mut modes = Modes {
+            numbers: Box::pin(notify::poll_fn(move |_| {
+                next += 1;
+                Ready(next - 1)
+            })),
+            log: Vec::new(),
+        };
+        let handled = pasts::switch(&mut modes, Mode::Even, run).await;
+
+        assert_eq!(handled, 6);
+        assert_eq!(
+            modes.log,
+            [
+                ("even", 0),
+                ("odd", 1),
+                ("even", 2),
+                ("odd", 3),
+                ("even", 4),
+                ("odd", 5),
+            ],
+        );
+    });
+}
+
+#[test]
+fn abortable_stops_one_handler() {
+    use pasts::{AbortHandle, Abortable, Aborted};
+
+    struct State {
+        ticks: Abortable<notify::Pending<u32>>,
+        abort: notify::Ready<()>,
+        handle: AbortHandle,
+    }
+
+    impl State {
+        fn abort(&mut self, (): ()) -> Poll<Result<u32, Aborted>> {
+            self.handle.abort();
+            Pending
+        }
+    }
+
+    Executor::default().block_on(async {
+        let (handle, registration) = AbortHandle::new_pair();
+        let mut state = State {
+            ticks: Abortable::new(notify::pending(), registration),
+            abort: notify::ready(()),
+            handle,
+        };
+        let result = Loop::new(&mut state)
+            .on(|s| &mut s.ticks, |_, tick| Ready(tick))
+            .on(|s| &mut s.abort, State::abort)
+            .await;
+
+        assert_eq!(result, Err(Aborted));
+        assert!(state.ticks.is_aborted());
+    });
+}
@@ -0,0 +1,767 @@
+use std::cell::Cell;
+
+use pasts::{notify, prelude::*, Executor};
+
+#[test]
+fn select_rotates_between_ready_notifys() {
+    Executor::default().block_on(async {
+        let mut a = notify::poll_fn(|_| Ready(0));
+        let mut b = notify::poll_fn(|_| Ready(1));
+        let mut c = notify::poll_fn(|_| Ready(2));
+        let mut select = notify::select([&mut a, &mut b, &mut c]);
+        let mut counts = [0; 3];
+
+        for _ in 0..99 {
+            counts[select.next().await] += 1;
+        }
+
+        assert_eq!(counts, [33, 33, 33]);
+    });
+}
+
+#[test]
+fn select_dyn_rotates_between_ready_notifys() {
+    Executor::default().block_on(async {
+        let mut a = notify::poll_fn(|_| Ready(0));
+        let mut b = notify::poll_fn(|_| Ready(1));
+        let mut c = notify::poll_fn(|_| Ready(2));
+        let mut d = notify::poll_fn(|_| Ready(3));
+        let mut select = notify::select_dyn([]);
+        let mut counts = [0; 4];
+
+        select.push(&mut a);
+        select.push(&mut b);
+        select.push(&mut c);
+        select.push(&mut d);
+
+        for _ in 0..100 {
+            counts[select.next().await] += 1;
+        }
+
+        assert_eq!(counts, [25, 25, 25, 25]);
+
+        // Removing a notify keeps the rotation going where it left off
+        select.remove(0);
+
+        for _ in 0..99 {
+            counts[select.next().await] += 1;
+        }
+
+        assert_eq!(counts, [25, 58, 58, 58]);
+    });
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn select_random_start() {
+    Executor::default().block_on(async {
+        let mut a = notify::poll_fn(|_| Ready(0));
+        let mut b = notify::poll_fn(|_| Ready(1));
+        let mut c = notify::poll_fn(|_| Ready(2));
+        let mut firsts = [0; 3];
+
+        for _ in 0..300 {
+            let mut select =
+                notify::select([&mut a, &mut b, &mut c]).random_start();
+
+            firsts[select.next().await] += 1;
+        }
+
+        // Every notify should get to go first sometimes
+        assert!(firsts.iter().all(|&count| count > 0), "{firsts:?}");
+    });
+}
+
+#[test]
+fn zip_and_all_wait_for_every_notify() {
+    Executor::default().block_on(async {
+        let mut count = 0;
+        let mut slow = notify::poll_fn(|task| {
+            count += 1;
+
+            if count % 2 == 0 {
+                return Ready(count);
+            }
+
+            task.waker().wake_by_ref();
+            Pending
+        });
+        let mut fast = notify::poll_fn(|_| Ready('a'));
+
+        // Each pair waits for the slow side
+        let mut zip = (&mut fast).zip(&mut slow);
+
+        assert_eq!(zip.next().await, ('a', 2));
+        assert_eq!(zip.next().await, ('a', 4));
+
+        let mut notifys: [LocalBoxNotify<'_, i32>; 2] =
+            [Box::pin(async { 1 }.fuse()), Box::pin(async { 2 }.fuse())];
+
+        assert_eq!(notify::all(&mut notifys).await, [1, 2]);
+    });
+}
+
+#[test]
+fn never_is_never_ready() {
+    use core::{convert::Infallible, future};
+
+    let mut never = notify::never::<u8>();
+
+    assert_eq!(format!("{never:?}"), "Never<u8>");
+
+    Executor::default().block_on(async move {
+        let mut future = Box::pin(notify::never::<Infallible>());
+        let mut disabled = notify::never::<Infallible>().map(|x| match x {});
+        let mut ready = notify::ready(1);
+
+        assert_eq!(notify::select([&mut disabled, &mut ready]).next().await, 1);
+
+        future::poll_fn(|task| {
+            assert!(Pin::new(&mut never).poll_next(task).is_pending());
+            assert!(future.as_mut().poll(task).is_pending());
+            Ready(())
+        })
+        .await;
+    });
+}
+
+#[test]
+fn next_fn_produces_futures() {
+    Executor::default().block_on(async {
+        let mut notifys: [LocalBoxNotify<'_, i32>; 2] =
+            [Box::pin(async { 1 }.fuse()), Box::pin(async { 2 }.fuse())];
+        let mut selected = &mut notifys[..];
+        let mut next = notify::next_fn(&mut selected);
+        let (first, second) = (next(), next());
+
+        assert_eq!(first.await, (0, 1));
+        assert_eq!(second.await, (1, 2));
+    });
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn timestamped_events_are_ordered() {
+    Executor::default().block_on(async {
+        let mut count = 0;
+        let mut counter = notify::poll_fn(|_| {
+            count += 1;
+            Ready(count)
+        })
+        .timestamped();
+        let (first, one) = counter.next().await;
+        let (second, two) = counter.next().await;
+
+        assert_eq!((one, two), (1, 2));
+        assert!(first <= second);
+    });
+}
+
+#[test]
+fn replay_late_consumers() {
+    Executor::default().block_on(async {
+        let mut count = 0;
+        let mut counter = notify::poll_fn(|_| {
+            count += 1;
+            Ready(count)
+        })
+        .replay(3);
+
+        counter.attach();
+        assert_eq!(counter.next().await, 1);
+        assert_eq!(counter.next().await, 2);
+        assert_eq!(counter.history().copied().collect::<Vec<_>>(), [1, 2]);
+
+        // Reattaching partway through a replay starts over
+        counter.attach();
+        assert_eq!(counter.next().await, 1);
+        counter.attach();
+        assert_eq!(counter.next().await, 1);
+        assert_eq!(counter.next().await, 2);
+        assert_eq!(counter.next().await, 3);
+        assert_eq!(counter.next().await, 4);
+        assert_eq!(counter.history().copied().collect::<Vec<_>>(), [2, 3, 4]);
+
+        let mut disabled = notify::ready(1).replay(0);
+
+        assert_eq!(disabled.next().await, 1);
+        disabled.attach();
+        assert_eq!(disabled.history().count(), 0);
+    });
+}
+
+#[test]
+fn ready_when_pauses_producer() {
+    use std::{cell::Cell, rc::Rc};
+
+    use pasts::notify::WakerSlot;
+
+    Executor::default().block_on(async {
+        let space = Rc::new(Cell::new(1));
+        let waiter = Rc::new(Cell::new(WakerSlot::new()));
+        let produced = Rc::new(Cell::new(0));
+        let (s, w, p) = (space.clone(), waiter.clone(), produced.clone());
+        let mut producer = notify::poll_fn(move |_| {
+            p.set(p.get() + 1);
+            Ready(p.get())
+        })
+        .ready_when(move |task| {
+            if s.get() == 0 {
+                let mut slot = w.take();
+
+                slot.register(task.waker());
+                w.set(slot);
+                return Pending;
+            }
+
+            s.set(s.get() - 1);
+            Ready(())
+        });
+
+        assert_eq!(producer.next().await, 1);
+
+        // Out of space: the producer isn't polled until space is released
+        let mut pending = 0;
+
+        core::future::poll_fn(|task| {
+            if Pin::new(&mut producer).poll_next(task).is_pending() {
+                pending += 1;
+                return Ready(());
+            }
+
+            Pending
+        })
+        .await;
+
+        assert_eq!((pending, produced.get()), (1, 1));
+
+        space.set(1);
+        waiter.take().wake();
+        assert_eq!(producer.next().await, 2);
+    });
+}
+
+#[test]
+fn coalesce_merges_by_key() {
+    Executor::default().block_on(async {
+        let mut storm = vec![('b', 4), ('a', 3), ('b', 2), ('a', 1)];
+        let mut sums = notify::poll_fn(|_| match storm.pop() {
+            Some(event) => Ready(event),
+            None => Pending,
+        })
+        .coalesce(|sum, value| *sum += value);
+
+        assert_eq!(sums.next().await, ('a', 4));
+        assert_eq!(sums.next().await, ('b', 6));
+    });
+}
+
+#[test]
+fn coalesce_returns_when_always_ready() {
+    Executor::default().block_on(async {
+        let mut counts = notify::poll_fn(|_| Ready(('a', 1)))
+            .coalesce(|count, value| *count += value);
+
+        let (key, first) = counts.next().await;
+        let (_, second) = counts.next().await;
+
+        assert_eq!(key, 'a');
+        assert!(first > 1);
+        assert_eq!(first, second);
+    });
+}
+
+#[test]
+fn results_collect_errors() {
+    Executor::default().block_on(async {
+        let mut results = notify::results([
+            notify::ready(Ok(1)),
+            notify::ready(Err('a')),
+            notify::ready(Ok(3)),
+            notify::ready(Err('b')),
+        ]);
+
+        assert_eq!(results.next().await, (0, 1));
+        assert_eq!(results.next().await, (2, 3));
+        assert_eq!(results.errors(), [(1, 'a')]);
+        assert_eq!(results.active(), 3);
+        assert_eq!(results.take_errors(), [(1, 'a')]);
+        assert!(results.errors().is_empty());
+    });
+}
+
+#[test]
+fn fuse_on_stops_after_terminal_event() {
+    #[derive(Debug, PartialEq)]
+    enum Socket {
+        Data(u32),
+        Closed,
+    }
+
+    Executor::default().block_on(async {
+        let polls = Cell::new(0);
+        let mut socket = notify::poll_fn(|_| {
+            polls.set(polls.get() + 1);
+            Ready(match polls.get() {
+                2 => Socket::Closed,
+                n => Socket::Data(n),
+            })
+        })
+        .fuse_on(|event| *event == Socket::Closed);
+        let mut other = notify::ready(Socket::Data(0));
+
+        assert_eq!(socket.next().await, Socket::Data(1));
+        assert_eq!(socket.next().await, Socket::Closed);
+        assert!(socket.is_done());
+
+        let event = notify::select([&mut socket, &mut other]).next().await;
+
+        assert_eq!(event, Socket::Data(0));
+        assert_eq!(polls.get(), 2);
+    });
+}
+
+#[test]
+fn until_cancelled() {
+    use pasts::CancelToken;
+
+    Executor::default().block_on(async {
+        let token = CancelToken::new();
+        let mut count = 0;
+        let mut counter = notify::poll_fn(|_| {
+            count += 1;
+            Ready(count)
+        })
+        .until(token.clone());
+        let mut other = notify::ready(Some(0));
+
+        assert_eq!(counter.next().await, Some(1));
+        assert_eq!(counter.next().await, Some(2));
+
+        token.clone().cancel();
+        token.cancelled().await;
+        assert_eq!(counter.next().await, None);
+
+        // Never ready again after the final `None`
+        let event = notify::select([&mut counter, &mut other]).next().await;
+
+        assert_eq!(event, Some(0));
+        assert!(token.is_cancelled());
+    });
+}
+
+#[test]
+fn merge_prefer_drains_first() {
+    Executor::default().block_on(async {
+        let mut control = 0;
+        let control = notify::poll_fn(|_| {
+            control += 1;
+            if control <= 3 {
+                Ready(control)
+            } else {
+                Pending
+            }
+        });
+        let data = notify::poll_fn(|_| Ready(0));
+        let mut merged = notify::merge_prefer(control, data);
+        let mut events = Vec::new();
+
+        for _ in 0..5 {
+            events.push(merged.next().await);
+        }
+
+        assert_eq!(events, [1, 2, 3, 0, 0]);
+    });
+}
+
+#[test]
+fn weighted_select_deficit_round_robin() {
+    Executor::default().block_on(async {
+        let mut a = notify::poll_fn(|_| Ready('a'));
+        let mut b = notify::poll_fn(|_| Ready('b'));
+        let mut idle = notify::pending();
+        let a: &mut (dyn Notify<Event = char> + Unpin) = &mut a;
+        let mut select =
+            notify::weighted_select([(a, 3), (&mut idle, 5), (&mut b, 1)]);
+        let mut events = String::new();
+
+        for _ in 0..8 {
+            events.push(select.next().await);
+        }
+
+        assert_eq!(events, "aaabaaab");
+
+        select.set_weight(0, 1);
+        select.set_weight(2, 2);
+        events.clear();
+
+        for _ in 0..6 {
+            events.push(select.next().await);
+        }
+
+        assert_eq!(events, "abbabb");
+        assert_eq!(select.weight(2), 2);
+    });
+}
+
+#[test]
+fn weighted_select_remove_current() {
+    Executor::default().block_on(async {
+        let mut a = notify::poll_fn(|_| Ready('a'));
+        let mut b = notify::poll_fn(|_| Ready('b'));
+        let mut c = notify::poll_fn(|_| Ready('c'));
+        let a: &mut (dyn Notify<Event = char> + Unpin) = &mut a;
+        let mut select =
+            notify::weighted_select([(a, 2), (&mut b, 1), (&mut c, 2)]);
+        let mut events = String::new();
+
+        events.push(select.next().await);
+        // The next notify takes over the turn of the removed one
+        select.remove(0);
+
+        for _ in 0..6 {
+            events.push(select.next().await);
+        }
+
+        assert_eq!(events, "abccbcc");
+
+        events.clear();
+        // Removing the last notify gives the first one its turn
+        select.remove(1);
+
+        for _ in 0..2 {
+            events.push(select.next().await);
+        }
+
+        assert_eq!(events, "bb");
+    });
+}
+
+#[test]
+fn notify_unordered_polls_woken_entries() {
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::Arc,
+        task::{Wake, Waker},
+    };
+
+    use pasts::notify::NotifyUnordered;
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[derive(Default)]
+    struct Entry {
+        polls: Cell<usize>,
+        ready: Cell<bool>,
+        waker: RefCell<Option<Waker>>,
+    }
+
+    let entry = |shared: Rc<Entry>| {
+        notify::poll_fn(move |t| {
+            shared.polls.set(shared.polls.get() + 1);
+            *shared.waker.borrow_mut() = Some(t.waker().clone());
+
+            if shared.ready.take() {
+                Ready(())
+            } else {
+                Pending
+            }
+        })
+    };
+    let a = Rc::new(Entry::default());
+    let b = Rc::new(Entry::default());
+    let mut set = NotifyUnordered::new();
+    let key_a = set.push(entry(a.clone()));
+    let _key_b = set.push(entry(b.clone()));
+    let waker = Waker::from(Arc::new(Noop));
+    let mut t = Task::from_waker(&waker);
+
+    assert!(set.poll_next_unpin(&mut t).is_pending());
+    assert_eq!((a.polls.get(), b.polls.get()), (1, 1));
+
+    // Nothing woken, so nothing polled
+    assert!(set.poll_next_unpin(&mut t).is_pending());
+    assert_eq!((a.polls.get(), b.polls.get()), (1, 1));
+
+    a.ready.set(true);
+    a.waker.borrow().as_ref().unwrap().wake_by_ref();
+    assert_eq!(set.poll_next_unpin(&mut t), Ready((key_a, ())));
+    assert_eq!((a.polls.get(), b.polls.get()), (2, 1));
+
+    // Entries are polled again after producing an event
+    assert!(set.poll_next_unpin(&mut t).is_pending());
+    assert_eq!((a.polls.get(), b.polls.get()), (3, 1));
+
+    assert!(set.remove(key_a));
+    assert!(!set.remove(key_a));
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn merge_all_rotates_and_removes() {
+    let repeat = |n: u32| -> BoxNotify<'static, u32> {
+        Box::pin(notify::poll_fn(move |_| Ready(n)))
+    };
+    let mut merged = notify::merge_all(vec![repeat(1), repeat(2)]);
+
+    Executor::default().block_on(async move {
+        merged.push(repeat(3));
+
+        let mut events = Vec::new();
+
+        for _ in 0..4 {
+            events.push(merged.next().await);
+        }
+
+        assert_eq!(events, [1, 2, 3, 1]);
+        assert_eq!(merged.remove(1).next().await, 2);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged.next().await, 3);
+        assert_eq!(merged.next().await, 1);
+    });
+}
+
+#[cfg(feature = "test_util")]
+#[test]
+fn conformance_accepts_registering_notify() {
+    let mut count = 0;
+    let mut slot = None;
+    let events = notify::conformance::check(notify::poll_fn(|task| {
+        if count == 3 {
+            slot = Some(task.waker().clone());
+            return Pending;
+        }
+
+        count += 1;
+        Ready(count)
+    }));
+
+    assert_eq!(events, [1, 2, 3]);
+}
+
+#[cfg(feature = "test_util")]
+#[test]
+#[should_panic(expected = "without registering the waker")]
+fn conformance_rejects_lost_wakeup() {
+    notify::conformance::check(notify::pending::<()>());
+}
+
+#[cfg(feature = "test_util")]
+#[test]
+#[should_panic(expected = "woke itself")]
+fn conformance_rejects_busy_loop() {
+    notify::conformance::check(notify::poll_fn::<(), _>(|task| {
+        task.waker().wake_by_ref();
+        Pending
+    }));
+}
+
+#[cfg(not(loom))]
+#[cfg(feature = "std")]
+#[test]
+fn waker_bridge_wakes_across_runtimes() {
+    use pasts::WakerBridge;
+
+    let bridge = WakerBridge::new();
+    let mut sleep =
+        Box::pin(async_std::task::sleep(core::time::Duration::from_millis(1)));
+
+    Executor::default().block_on(async move {
+        core::future::poll_fn(|t| bridge.poll(t, |t| sleep.as_mut().poll(t)))
+            .await;
+    });
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn waker_bridge_forwards_to_last_task() {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Wake, Waker},
+    };
+
+    use pasts::WakerBridge;
+
+    #[derive(Default)]
+    struct Count(AtomicUsize);
+
+    impl Wake for Count {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let bridge = WakerBridge::new();
+    let counts = [Arc::new(Count::default()), Arc::new(Count::default())];
+    let mut wakers = Vec::new();
+
+    for count in &counts {
+        let waker = Waker::from(count.clone());
+        let poll = bridge.poll(&mut Task::from_waker(&waker), |t| {
+            wakers.push(t.waker().clone());
+            Pending::<()>
+        });
+
+        assert!(poll.is_pending());
+    }
+
+    // The inner notify sees the same waker, and wakes the last task
+    assert!(wakers[0].will_wake(&wakers[1]));
+    wakers[0].wake_by_ref();
+    wakers[1].wake_by_ref();
+    assert_eq!(counts[0].0.load(Ordering::SeqCst), 0);
+    assert_eq!(counts[1].0.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(all(unix, feature = "io"))]
+#[test]
+fn io_incoming_accepts_unix_connections() {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::{UnixListener, UnixStream},
+    };
+
+    use pasts::io::Source;
+
+    let path = std::env::temp_dir()
+        .join(format!("pasts-io-incoming-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = Source::<UnixListener>::bind(&path).unwrap();
+
+    Executor::default().block_on(async move {
+        let client = Source::<UnixStream>::connect(&path).unwrap();
+        let server = listener.incoming().next().await.unwrap();
+
+        client.writable().next().await.unwrap();
+        client.get_ref().write_all(b"hello").unwrap();
+        server.readable().next().await.unwrap();
+
+        let mut buf = [0; 5];
+
+        server.get_ref().read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+        std::fs::remove_file(&path).unwrap();
+    });
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn io_tcp_echoes_between_tasks() {
+    use pasts::io::{AsyncTcpListener, AsyncTcpStream};
+
+    let executor = Executor::default();
+    let listener = AsyncTcpListener::bind(([127, 0, 0, 1], 0)).unwrap();
+    let addr = listener.source().get_ref().local_addr().unwrap();
+
+    executor.spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 4];
+        let len = stream.read(&mut buf).await.unwrap();
+
+        stream.write(&buf[..len]).await.unwrap();
+    });
+
+    executor.block_on(async move {
+        let stream = AsyncTcpStream::connect(addr).await.unwrap();
+        let mut buf = [0; 4];
+
+        stream.write(b"echo").await.unwrap();
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 4);
+        assert_eq!(&buf, b"echo");
+    });
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn io_udp_sends_datagrams() {
+    use pasts::io::AsyncUdpSocket;
+
+    let a = AsyncUdpSocket::bind(([127, 0, 0, 1], 0)).unwrap();
+    let b = AsyncUdpSocket::bind(([127, 0, 0, 1], 0)).unwrap();
+    let a_addr = a.source().get_ref().local_addr().unwrap();
+
+    Executor::default().block_on(async move {
+        let mut buf = [0; 8];
+
+        b.send_to(b"ping", a_addr).await.unwrap();
+
+        let (len, from) = a.recv_from(&mut buf).await.unwrap();
+
+        assert_eq!(&buf[..len], b"ping");
+        assert_eq!(from, b.source().get_ref().local_addr().unwrap());
+    });
+}
+
+#[cfg(all(unix, feature = "io"))]
+#[test]
+fn io_device_reads_when_readable() {
+    use std::{
+        fs::File,
+        os::{fd::OwnedFd, unix::net::UnixStream},
+    };
+
+    use pasts::io::Device;
+
+    // A socket stands in for a character device, since both can be polled
+    let (a, b) = UnixStream::pair().unwrap();
+    let a = Device::new(File::from(OwnedFd::from(a))).unwrap();
+    let b = Device::new(File::from(OwnedFd::from(b))).unwrap();
+
+    Executor::default().block_on(async move {
+        let mut buf = [0; 3];
+
+        a.write(b"tty").await.unwrap();
+        b.source().readable().next().await.unwrap();
+        assert_eq!(b.read(&mut buf).await.unwrap(), 3);
+        assert_eq!(&buf, b"tty");
+    });
+}
+
+#[test]
+fn raw_wake_dispatches_outside_of_handler() {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Wake, Waker},
+    };
+
+    use pasts::RawWake;
+
+    #[derive(Default)]
+    struct Count(AtomicUsize);
+
+    impl Wake for Count {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    static RAW: RawWake = RawWake::new();
+
+    let count = Arc::new(Count::default());
+    let waker = Waker::from(count.clone());
+    let mut task = Task::from_waker(&waker);
+    let mut notify = RAW.notify();
+
+    assert!(!RAW.dispatch());
+    assert!(Pin::new(&mut notify).poll_next(&mut task).is_pending());
+
+    // Only the dispatch wakes the task
+    RAW.handle().wake();
+    RAW.handle().wake();
+    assert_eq!(count.0.load(Ordering::SeqCst), 0);
+    assert!(RAW.dispatch());
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+    assert!(Pin::new(&mut notify).poll_next(&mut task).is_ready());
+    assert!(Pin::new(&mut notify).poll_next(&mut task).is_pending());
+}
@@ -1,3 +1,5 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
 use pasts::Executor;
 use whisk::Channel;
 
@@ -17,3 +19,1888 @@ fn spawn_inside_block_on() {
         assert_eq!(0xDEADBEEFu32, channel.recv().await);
     });
 }
+
+#[test]
+fn spawn_plain_future_inside_block_on() {
+    let executor = Executor::default();
+    let channel = Channel::new();
+    let sender = channel.clone();
+
+    executor.clone().block_on(async move {
+        // Neither boxed nor fused by the caller
+        executor.spawn(async move {
+            sender.send(0xDEADBEEFu32).await;
+        });
+    });
+
+    Executor::default().block_on(async move {
+        assert_eq!(0xDEADBEEFu32, channel.recv().await);
+    });
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn watchdog_reports_slow_poll() {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use pasts::Diagnostic;
+
+    let slow_polls = Arc::new(AtomicUsize::new(0));
+    let counter = slow_polls.clone();
+    let limit = Duration::from_millis(5);
+    let executor = Executor::default().watchdog(limit, limit, move |d| {
+        if let Diagnostic::SlowPoll { elapsed, .. } = d {
+            assert!(elapsed > limit);
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    executor.block_on(async {
+        thread::sleep(Duration::from_millis(20));
+    });
+
+    assert_eq!(slow_polls.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn watchdog_reports_stall_without_wakers() {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use pasts::Diagnostic;
+
+    let stalls = Arc::new(AtomicUsize::new(0));
+    let counter = stalls.clone();
+    let limit = Duration::from_millis(5);
+    let executor = Executor::default().watchdog(limit, limit, move |d| {
+        if let Diagnostic::Stall { .. } = d {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    // The timer holds the task's waker, so sleeping past the limit is fine
+    executor.clone().block_on(async {
+        pasts::time::sleep(Duration::from_millis(30)).await;
+    });
+    assert_eq!(stalls.load(Ordering::SeqCst), 0);
+
+    // Nothing can wake a task that's never going to complete
+    let _ = executor
+        .block_on_timeout(core::future::pending(), Duration::from_millis(30));
+    assert!(stalls.load(Ordering::SeqCst) > 0);
+}
+
+#[cfg(all(feature = "std", debug_assertions))]
+#[test]
+fn watchdog_reports_lost_waker() {
+    use std::{
+        future,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::Poll,
+        time::Duration,
+    };
+
+    use pasts::Diagnostic;
+
+    let lost = Arc::new(AtomicUsize::new(0));
+    let counter = lost.clone();
+    let limit = Duration::from_secs(10);
+    let executor = Executor::default().watchdog(limit, limit, move |d| {
+        if let Diagnostic::LostWaker(_) = d {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    executor.clone().block_on(async move {
+        // Keep the executor busy by yielding a few times
+        executor.spawn(async {
+            let mut yields = 0;
+
+            future::poll_fn(|task| {
+                yields += 1;
+                if yields == 4 {
+                    return Poll::Ready(());
+                }
+                task.waker().wake_by_ref();
+                Poll::Pending
+            })
+            .await;
+        });
+
+        // Forget to register a waker
+        let mut polls = 0;
+
+        future::poll_fn(|_task| {
+            polls += 1;
+            if polls == 3 {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    });
+
+    assert_eq!(lost.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn poll_hooks_receive_task_ids() {
+    use std::sync::{Arc, Mutex};
+
+    use pasts::TaskId;
+
+    let log = Arc::new(Mutex::new(Vec::<(bool, TaskId)>::new()));
+    let (before, after) = (log.clone(), log.clone());
+    let executor = Executor::default().poll_hooks(
+        move |id| before.lock().unwrap().push((true, id)),
+        move |id| after.lock().unwrap().push((false, id)),
+    );
+
+    executor.clone().block_on(async move {
+        executor.spawn(async {});
+    });
+
+    let log = log.lock().unwrap();
+    let ids: Vec<_> = log.iter().map(|(_, id)| id.to_string()).collect();
+
+    assert_eq!(ids, ["task 0", "task 0", "task 1", "task 1"]);
+    assert!(log.chunks(2).all(|pair| pair[0].0 && !pair[1].0));
+}
+
+#[test]
+fn wake_hook_attributes_wakes() {
+    use std::{
+        future,
+        sync::{Arc, Mutex},
+        task::Poll,
+    };
+
+    let woken = Arc::new(Mutex::new(Vec::new()));
+    let log = woken.clone();
+    let executor = Executor::default()
+        .wake_hook(move |id| log.lock().unwrap().push(id.to_string()));
+
+    executor.clone().block_on(async move {
+        executor.spawn(async {
+            let mut yielded = false;
+
+            future::poll_fn(|task| {
+                if yielded {
+                    return Poll::Ready(());
+                }
+                yielded = true;
+                task.waker().wake_by_ref();
+                Poll::Pending
+            })
+            .await;
+        });
+    });
+
+    assert_eq!(*woken.lock().unwrap(), ["task 1"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn watchdog_reports_reentrant_park() {
+    use std::{
+        future,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        task::{Poll, Waker},
+        time::Duration,
+    };
+
+    use pasts::Diagnostic;
+
+    let release = Arc::new((AtomicBool::new(false), Mutex::new(None::<Waker>)));
+    let hook = release.clone();
+    let limit = Duration::from_secs(10);
+    let executor = Executor::default().watchdog(limit, limit, move |d| {
+        if let Diagnostic::Reentrant { depth } = d {
+            assert_eq!(depth, 2);
+            hook.0.store(true, Ordering::SeqCst);
+            hook.1.lock().unwrap().take().unwrap().wake();
+        }
+    });
+
+    executor.clone().block_on(future::poll_fn(move |task| {
+        // Outer task wants to run again, but is blocked by nested executor
+        task.waker().wake_by_ref();
+
+        let release = release.clone();
+
+        executor.clone().block_on(future::poll_fn(move |task| {
+            if release.0.load(Ordering::SeqCst) {
+                return Poll::Ready(());
+            }
+            *release.1.lock().unwrap() = Some(task.waker().clone());
+            Poll::Pending
+        }));
+
+        Poll::Ready(())
+    }));
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "nested `block_on()` (depth 2)")]
+fn reentrant_park_panics_without_watchdog() {
+    use std::{future, task::Poll};
+
+    let executor = Executor::default();
+
+    executor.clone().block_on(future::poll_fn(move |task| {
+        // Outer task wants to run again, but is blocked by nested executor
+        task.waker().wake_by_ref();
+
+        executor.clone().block_on(future::pending::<()>());
+
+        Poll::Ready(())
+    }));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn watchdog_reports_deadlock() {
+    use std::{
+        future,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::Poll,
+        time::Duration,
+    };
+
+    use pasts::Diagnostic;
+
+    let deadlocks = Arc::new(AtomicUsize::new(0));
+    let counter = deadlocks.clone();
+    let limit = Duration::from_millis(10);
+    let executor = Executor::default().watchdog(limit, limit, move |d| {
+        if let Diagnostic::Deadlock { tasks } = d {
+            assert_eq!(tasks, 1);
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let mut polls = 0;
+
+    // Forget to register a waker, only getting polled again after the stall
+    executor.block_on(future::poll_fn(move |_task| {
+        polls += 1;
+        if polls == 2 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }));
+
+    assert_eq!(deadlocks.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn watchdog_reports_deadlock_of_detached_task() {
+    use std::{
+        future,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::Poll,
+        time::Duration,
+    };
+
+    use pasts::Diagnostic;
+
+    let deadlocks = Arc::new(AtomicUsize::new(0));
+    let counter = deadlocks.clone();
+    let limit = Duration::from_millis(10);
+    let executor = Executor::default().watchdog(limit, limit, move |d| {
+        if let Diagnostic::Deadlock { .. } = d {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let mut polls = 0;
+
+    // The detached handle can't wake the task, so it doesn't count as a waker
+    executor
+        .spawn_with_handle(future::poll_fn(move |_task| {
+            polls += 1;
+            if polls == 2 {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }))
+        .detach();
+    executor.block_on(async {});
+
+    assert_eq!(deadlocks.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn deadlock_without_watchdog_keeps_running() {
+    use std::{
+        future,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, Thread},
+    };
+
+    use pasts::{prelude::*, Park, Pool};
+
+    #[derive(Default)]
+    struct SendPool(Mutex<Vec<BoxNotify<'static>>>);
+
+    impl Pool for SendPool {
+        type Park = ThreadPark;
+
+        fn push(&self, _task: LocalBoxNotify<'static>) {
+            unreachable!("only spawned to with `Executor::spawn()`");
+        }
+
+        fn push_send(&self, task: BoxNotify<'static>) {
+            self.0.lock().unwrap().push(task);
+        }
+
+        fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool {
+            let mut queue = self.0.lock().unwrap();
+            let drained = !queue.is_empty();
+
+            for task in queue.drain(..) {
+                tasks.push(task);
+            }
+
+            drained
+        }
+    }
+
+    struct ThreadPark(Thread);
+
+    impl Default for ThreadPark {
+        fn default() -> Self {
+            Self(thread::current())
+        }
+    }
+
+    impl Park for ThreadPark {
+        fn park(&self) {
+            thread::park();
+        }
+
+        fn unpark(&self) {
+            self.0.unpark();
+        }
+    }
+
+    let executor = Executor::new(SendPool::default());
+    let done = Arc::new(AtomicBool::new(false));
+    let spawner = thread::spawn({
+        let executor = executor.clone();
+        let done = done.clone();
+
+        move || {
+            executor.spawn(async move { done.store(true, Ordering::SeqCst) });
+        }
+    });
+
+    // Looks deadlocked without a waker, but the spawn wakes the executor
+    executor.block_on(future::poll_fn(move |_task| {
+        if done.load(Ordering::SeqCst) {
+            Ready(())
+        } else {
+            Pending
+        }
+    }));
+    spawner.join().unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn panic_hook_cancels_or_restarts_tasks() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use pasts::PanicPolicy;
+
+    let panics = Arc::new(AtomicUsize::new(0));
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = panics.clone();
+    let executor = Executor::default().panic_hook(move |id, payload| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"oops"));
+        match id.to_string().as_str() {
+            "task 1" => PanicPolicy::CancelTask,
+            _ => PanicPolicy::RestartTask,
+        }
+    });
+    let restarts = runs.clone();
+
+    executor.clone().block_on(async move {
+        executor.spawn(async { panic!("oops") });
+        executor.spawn_restartable(move || {
+            let run = restarts.fetch_add(1, Ordering::SeqCst);
+
+            async move {
+                if run == 0 {
+                    panic!("oops");
+                }
+            }
+        });
+        // Can't restart, so it's cancelled
+        executor.spawn(async { panic!("oops") });
+    });
+
+    assert_eq!(panics.load(Ordering::SeqCst), 3);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn poll_hooks_pair_up_when_tasks_panic() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use pasts::PanicPolicy;
+
+    let before = Arc::new(AtomicUsize::new(0));
+    let after = Arc::new(AtomicUsize::new(0));
+    let executor = Executor::default()
+        .panic_hook(|_, _| PanicPolicy::CancelTask)
+        .poll_hooks(
+            {
+                let before = before.clone();
+
+                move |_| {
+                    before.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+            {
+                let after = after.clone();
+
+                move |_| {
+                    after.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+        );
+
+    executor.clone().block_on(async move {
+        executor.spawn(async { panic!("oops") });
+    });
+
+    assert_eq!(before.load(Ordering::SeqCst), 2);
+    assert_eq!(after.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn block_on_timeout() {
+    use std::{future, time::Duration};
+
+    use pasts::TimedOut;
+
+    let executor = Executor::default();
+    let timeout = Duration::from_millis(10);
+
+    executor
+        .clone()
+        .block_on_timeout(async {}, timeout)
+        .unwrap();
+
+    let result = executor.clone().block_on_timeout(
+        async move {
+            executor.spawn(future::pending());
+            future::pending::<()>().await;
+        },
+        timeout,
+    );
+
+    assert_eq!(result, Err(TimedOut { tasks: 2 }));
+}
+
+#[test]
+fn join_handle_detach_and_cancel() {
+    use std::{cell::Cell, rc::Rc};
+
+    let executor = Executor::default();
+    let ran = Rc::new(Cell::new(0));
+    let (a, b, c) = (ran.clone(), ran.clone(), ran.clone());
+
+    executor.clone().block_on(async move {
+        let handle = executor.spawn_with_handle(async { 21 * 2 });
+
+        assert_eq!(handle.await, Ok(42));
+
+        // Detached tasks keep running, cancelled tasks never do
+        executor.spawn_with_handle(async move { a.set(a.get() + 1) });
+        executor
+            .spawn_with_handle(async move { b.set(b.get() + 10) })
+            .cancel_on_drop();
+        executor
+            .spawn_with_handle(async move { c.set(c.get() + 100) })
+            .detach();
+    });
+
+    assert_eq!(ran.get(), 101);
+}
+
+#[test]
+fn spawn_trait_object() {
+    use std::{
+        cell::Cell,
+        rc::Rc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use pasts::{prelude::*, Spawn};
+
+    // Middleware that doesn't care which executor it's handed
+    fn start(
+        spawner: &dyn Spawn,
+        local: Rc<Cell<usize>>,
+        sent: Arc<AtomicUsize>,
+    ) {
+        spawner.spawn_local(Box::pin(async move { local.set(1) }.fuse()));
+        spawner.spawn_send(Box::pin(
+            async move { sent.store(2, Ordering::SeqCst) }.fuse(),
+        ));
+    }
+
+    let executor = Executor::default();
+    let spawner = executor.spawner();
+    let local = Rc::new(Cell::new(0));
+    let sent = Arc::new(AtomicUsize::new(0));
+    let (l, s) = (local.clone(), sent.clone());
+
+    executor.block_on(async move { start(&spawner, l, s) });
+
+    assert_eq!(local.get(), 1);
+    assert_eq!(sent.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "async_main")]
+#[test]
+fn async_main_spawn() {
+    use std::{cell::Cell, rc::Rc};
+
+    fn start(spawner: &impl async_main::Spawn, ran: Rc<Cell<bool>>) {
+        spawner.spawn_local(async move { ran.set(true) });
+    }
+
+    let executor = Executor::default();
+    let ran = Rc::new(Cell::new(false));
+    let r = ran.clone();
+
+    executor
+        .clone()
+        .block_on(async move { start(&executor, r) });
+
+    assert!(ran.get());
+}
+
+#[cfg(feature = "bench")]
+#[test]
+fn bench_workloads_are_deterministic() {
+    use pasts::bench;
+
+    assert_eq!(bench::ping_pong(4, 10), bench::ping_pong(4, 10));
+    assert_eq!(bench::ping_pong(4, 10).events, 40);
+    assert_eq!(bench::notifies(4, 10), bench::notifies(4, 10));
+    assert_eq!(bench::notifies(4, 10).events, 40);
+    assert_eq!(bench::notifies(0, 10).events, 0);
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn spawn_in_allocator() {
+    use std::{alloc::System, cell::Cell, rc::Rc};
+
+    let executor = Executor::default();
+    let ran = Rc::new(Cell::new(false));
+    let r = ran.clone();
+
+    executor.clone().block_on(async move {
+        executor.spawn_in(async move { r.set(true) }, System);
+    });
+
+    assert!(ran.get());
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn try_spawn_alloc_runs_task() {
+    use std::{cell::Cell, rc::Rc};
+
+    let executor = Executor::default();
+    let ran = Rc::new(Cell::new(false));
+    let r = ran.clone();
+
+    executor
+        .try_spawn_alloc(async move { r.set(true) })
+        .unwrap();
+    executor.block_on(async {});
+    assert!(ran.get());
+}
+
+#[test]
+fn time_slice_skips_spinning_tasks() {
+    use std::{
+        cell::Cell,
+        future,
+        rc::Rc,
+        sync::{Arc, Mutex},
+        task::Waker,
+    };
+
+    use pasts::prelude::*;
+
+    // Record which task was polled, for a spinning main task and a task
+    // waiting on it
+    let record = |time_slice| {
+        let polls = Arc::new(Mutex::new(Vec::new()));
+        let p = polls.clone();
+        let executor = Executor::default().time_slice(time_slice).poll_hooks(
+            move |id| p.lock().unwrap().push(id.to_string()),
+            |_| {},
+        );
+
+        executor.clone().block_on(async move {
+            let done = Rc::new(Cell::new(false));
+            let waiter = Rc::new(Cell::new(None::<Waker>));
+            let (d, w) = (done.clone(), waiter.clone());
+
+            executor.spawn_local(future::poll_fn(move |task| {
+                if d.get() {
+                    return Ready(());
+                }
+
+                w.set(Some(task.waker().clone()));
+                Pending
+            }));
+
+            for _ in 0..6 {
+                let mut yielded = false;
+
+                future::poll_fn(|task| {
+                    if yielded {
+                        return Ready(());
+                    }
+
+                    yielded = true;
+                    task.waker().wake_by_ref();
+                    Pending
+                })
+                .await;
+            }
+
+            done.set(true);
+            waiter.take().unwrap().wake();
+        });
+
+        let polls = polls.lock().unwrap().clone();
+
+        polls.windows(2).any(|w| w == ["task 1", "task 1"])
+    };
+
+    // Without time-slicing, the spinning task is polled every pass
+    assert!(!record(0));
+    assert!(record(2));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn workers_run_on_separate_threads() {
+    use std::{
+        sync::{Arc, Mutex},
+        thread::{self, ThreadId},
+    };
+
+    use pasts::Workers;
+
+    let seen = Arc::new(Mutex::new(Vec::<(usize, ThreadId)>::new()));
+
+    Workers::new().count(3).block_on(|index, executor| {
+        let seen = seen.clone();
+
+        executor.spawn(async move {
+            seen.lock().unwrap().push((index, thread::current().id()));
+        });
+
+        async {}
+    });
+
+    let mut seen = seen.lock().unwrap().clone();
+
+    seen.sort_by_key(|(index, _)| *index);
+    assert_eq!(seen.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_ne!(seen[0].1, seen[1].1);
+    assert_ne!(seen[1].1, seen[2].1);
+    assert_ne!(seen[0].1, thread::current().id());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn workers_hooks_and_names() {
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use pasts::Workers;
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (start, stop, run) = (events.clone(), events.clone(), events.clone());
+
+    Workers::new()
+        .count(2)
+        .name(|index| format!("worker-{index}"))
+        .on_start(move |index| {
+            let name = thread::current().name().map(String::from);
+
+            start
+                .lock()
+                .unwrap()
+                .push(format!("start {index} {name:?}"));
+        })
+        .on_stop(move |index| {
+            stop.lock().unwrap().push(format!("stop {index}"));
+        })
+        .block_on(|index, _executor| {
+            let run = run.clone();
+
+            async move {
+                run.lock().unwrap().push(format!("run {index}"));
+            }
+        });
+
+    let events = events.lock().unwrap();
+
+    for index in 0..2 {
+        let position = |event: String| {
+            events.iter().position(|e| *e == event).expect(&event)
+        };
+        let start = position(format!("start {index} Some(\"worker-{index}\")"));
+        let run = position(format!("run {index}"));
+        let stop = position(format!("stop {index}"));
+
+        assert!(start < run && run < stop);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn workers_spawn_without_waiting() {
+    use std::{sync::mpsc, thread};
+
+    use pasts::Workers;
+
+    let (sender, receiver) = mpsc::channel();
+
+    Workers::new()
+        .count(2)
+        .name(|index| format!("spawned-{index}"))
+        .spawn(move |index, executor| {
+            let sender = sender.clone();
+
+            executor.spawn(async move {
+                let name = thread::current().name().map(String::from);
+
+                sender.send((index, name)).unwrap();
+            });
+
+            async {}
+        });
+
+    let mut seen: Vec<_> = receiver.iter().take(2).collect();
+
+    seen.sort();
+    assert_eq!(
+        seen,
+        [
+            (0, Some("spawned-0".to_string())),
+            (1, Some("spawned-1".to_string())),
+        ],
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn stats_count_cross_thread_wakes() {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    use pasts::prelude::*;
+
+    let executor = Executor::default();
+    let stats = executor.stats();
+    let monitor = stats.clone();
+    let flag = Arc::new(AtomicBool::new(false));
+
+    executor.block_on(async move {
+        let mut waker = None;
+
+        core::future::poll_fn(|task| {
+            if flag.load(Ordering::SeqCst) {
+                return Ready(());
+            }
+
+            // Wake the task from another thread, once
+            if waker.is_none() {
+                let (f, w) = (flag.clone(), task.waker().clone());
+
+                waker = Some(thread::spawn(move || {
+                    f.store(true, Ordering::SeqCst);
+                    w.wake();
+                }));
+            }
+
+            Pending
+        })
+        .await;
+
+        // Tasks are counted as of the last pass
+        assert_eq!(monitor.snapshot().tasks, 1);
+    });
+
+    let snapshot = stats.snapshot();
+
+    assert_eq!(snapshot.tasks, 0);
+    assert!(snapshot.wakes >= 1);
+    assert_eq!(snapshot.cross_thread_wakes, snapshot.wakes);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn wake_from_thread_local_destructor() {
+    use std::{
+        cell::RefCell,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::Waker,
+        thread,
+    };
+
+    use pasts::prelude::*;
+
+    // Wakes the task when the thread's locals are destroyed
+    struct WakeOnDrop(RefCell<Option<Waker>>);
+
+    impl Drop for WakeOnDrop {
+        fn drop(&mut self) {
+            if let Some(waker) = self.0.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    thread_local! {
+        static WAKER: WakeOnDrop = const { WakeOnDrop(RefCell::new(None)) };
+    }
+
+    let flag = Arc::new(AtomicBool::new(false));
+    let executor = Executor::default();
+    let stats = executor.stats();
+
+    executor.block_on(async move {
+        let mut spawned = false;
+
+        core::future::poll_fn(|task| {
+            if flag.load(Ordering::SeqCst) {
+                return Ready(());
+            }
+
+            if !spawned {
+                let (f, w) = (flag.clone(), task.waker().clone());
+
+                spawned = true;
+                thread::spawn(move || {
+                    f.store(true, Ordering::SeqCst);
+                    WAKER.with(|waker| *waker.0.borrow_mut() = Some(w));
+                });
+            }
+
+            Pending
+        })
+        .await;
+    });
+
+    assert!(stats.snapshot().cross_thread_wakes >= 1);
+}
+
+#[test]
+fn tick_runs_woken_tasks() {
+    use std::{cell::Cell, future, rc::Rc};
+
+    use pasts::prelude::*;
+
+    let executor = Executor::default();
+    let mut ticker = executor.ticker();
+    let polls = Rc::new(Cell::new(0));
+    let p = polls.clone();
+
+    assert!(ticker.tick().is_ready());
+
+    executor.spawn_local(future::poll_fn(move |_| {
+        p.set(p.get() + 1);
+
+        if p.get() == 3 {
+            return Ready(());
+        }
+
+        Pending
+    }));
+
+    // Spawned tasks are polled on the next tick, and then not until woken
+    assert!(ticker.tick().is_pending());
+    assert!(ticker.tick().is_pending());
+    assert_eq!(polls.get(), 1);
+
+    ticker.waker().wake();
+    assert!(ticker.tick().is_pending());
+    assert_eq!(polls.get(), 2);
+
+    ticker.waker().wake();
+    assert!(ticker.tick().is_ready());
+    assert_eq!(polls.get(), 3);
+}
+
+#[test]
+fn spawn_static_runs_in_place() {
+    use pasts::prelude::*;
+
+    let channel = Channel::new();
+    let sender = channel.clone();
+    let task = Box::leak(Box::new(
+        async move {
+            sender.send(7u32).await;
+        }
+        .fuse(),
+    ));
+    let executor = Executor::default();
+
+    executor.spawn_static(Pin::static_mut(task));
+    executor.block_on(async move {
+        assert_eq!(channel.recv().await, 7);
+    });
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn spawn_blocking_waits_for_a_thread() {
+    use std::sync::{mpsc, Arc, Mutex};
+
+    use pasts::prelude::*;
+
+    let executor = Executor::default().blocking_threads(1);
+    let (gate, wait) = mpsc::channel::<()>();
+    let wait = Arc::new(Mutex::new(wait));
+
+    executor.clone().block_on(async move {
+        let mut queue = executor.blocking_queue();
+
+        assert_eq!(queue.next().await, 0);
+
+        let first = executor.spawn_blocking(move || {
+            wait.lock().unwrap().recv().unwrap();
+            1
+        });
+        let second = executor.spawn_blocking(|| 2);
+
+        // The second closure waits for the only thread
+        assert_eq!(queue.next().await, 1);
+        gate.send(()).unwrap();
+        assert_eq!(queue.next().await, 0);
+        assert_eq!(first.await + second.await, 3);
+    });
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "blocking")]
+fn spawn_blocking_continues_panics() {
+    let executor = Executor::default();
+
+    executor.clone().block_on(async move {
+        executor.spawn_blocking(|| panic!("blocking")).await;
+    });
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn cleanup_runs_on_shutdown_only() {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    let cleaned = Rc::new(Cell::new(0));
+    let executor = Executor::default();
+    let cleanup = {
+        let cleaned = cleaned.clone();
+
+        move || cleaned.set(cleaned.get() + 1)
+    };
+
+    executor
+        .spawn_with_cleanup(async {}, cleanup.clone())
+        .detach();
+    executor
+        .spawn_with_cleanup(core::future::pending::<()>(), cleanup)
+        .detach();
+
+    let timed_out =
+        executor.block_on_timeout(async {}, Duration::from_millis(10));
+
+    assert!(timed_out.is_err());
+    assert_eq!(cleaned.get(), 1);
+}
+
+#[test]
+fn background_tasks_stop_on_shutdown() {
+    use std::{cell::Cell, rc::Rc};
+
+    use pasts::{notify, prelude::*, Loop};
+
+    struct Background {
+        work: notify::Pending<()>,
+        shutdown: Option<pasts::OnShutdown>,
+    }
+
+    let executor = Executor::default();
+    let stopped = Rc::new(Cell::new(false));
+    let mut background = Background {
+        work: notify::pending(),
+        shutdown: Some(executor.on_shutdown()),
+    };
+
+    executor.spawn_local({
+        let stopped = stopped.clone();
+
+        async move {
+            Loop::new(&mut background)
+                .on(|s| &mut s.work, |_, ()| Pending)
+                .on(|s| &mut s.shutdown, |_, ()| Ready(()))
+                .await;
+            stopped.set(true);
+        }
+    });
+    executor.block_on(async {});
+
+    assert!(stopped.get());
+}
+
+#[test]
+fn spawn_eager_polls_before_queueing() {
+    use std::{cell::Cell, rc::Rc};
+
+    let executor = Executor::default();
+    let polls = Rc::new(Cell::new(0));
+    let channel = Channel::new();
+    let sender = channel.clone();
+
+    executor.spawn_eager({
+        let polls = polls.clone();
+
+        async move {
+            polls.set(polls.get() + 1);
+            sender.send(5u32).await;
+        }
+    });
+
+    // Ran up to its first await, on this stack
+    assert_eq!(polls.get(), 1);
+
+    executor.spawn_eager(async move {
+        assert_eq!(channel.recv().await, 5);
+    });
+    executor.block_on(async {});
+    assert_eq!(polls.get(), 1);
+}
+
+#[test]
+fn task_size_hook_reports_spawned_tasks() {
+    use std::sync::{Arc, Mutex};
+
+    let sizes = Arc::new(Mutex::new(Vec::new()));
+    let executor = Executor::default().task_size_hook({
+        let sizes = sizes.clone();
+
+        move |size| sizes.lock().unwrap().push(size)
+    });
+    let big = [0u8; 4096];
+
+    executor.spawn(async move {
+        async {}.await;
+        let _ = big.len();
+    });
+    executor.block_on(async {});
+
+    let sizes = sizes.lock().unwrap();
+
+    assert_eq!(sizes.len(), 2);
+    assert!(sizes[0] >= 4096);
+    assert!(sizes[1] < 4096);
+}
+
+#[test]
+fn try_spawn_rejects_when_full() {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        thread::{self, Thread},
+    };
+
+    use pasts::{prelude::*, Park, Pool};
+
+    #[derive(Default)]
+    struct BoundedPool(RefCell<Vec<LocalBoxNotify<'static>>>);
+
+    impl Pool for BoundedPool {
+        type Park = ThreadPark;
+
+        fn push(&self, task: LocalBoxNotify<'static>) {
+            self.0.borrow_mut().push(task);
+        }
+
+        fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool {
+            let mut queue = self.0.borrow_mut();
+            let drained = !queue.is_empty();
+
+            tasks.append(&mut queue);
+            drained
+        }
+
+        fn is_full(&self) -> bool {
+            self.0.borrow().len() >= 2
+        }
+    }
+
+    struct ThreadPark(Thread);
+
+    impl Default for ThreadPark {
+        fn default() -> Self {
+            Self(thread::current())
+        }
+    }
+
+    impl Park for ThreadPark {
+        fn park(&self) {
+            thread::park();
+        }
+
+        fn unpark(&self) {
+            self.0.unpark();
+        }
+    }
+
+    let executor = Executor::new(BoundedPool::default());
+    let ran = Rc::new(Cell::new(0));
+    let task = || {
+        let ran = ran.clone();
+
+        async move { ran.set(ran.get() + 1) }
+    };
+
+    assert!(executor.try_spawn(task()).is_ok());
+    assert!(executor.try_spawn(task()).is_ok());
+
+    let rejected = executor.try_spawn(task()).unwrap_err();
+
+    executor.clone().block_on(async {});
+    assert_eq!(ran.get(), 2);
+
+    // Room again once the queue is drained
+    assert!(executor.try_spawn(rejected).is_ok());
+    executor.block_on(async {});
+    assert_eq!(ran.get(), 3);
+}
+
+#[test]
+fn block_on_with_spawns_siblings() {
+    use std::{cell::Cell, rc::Rc};
+
+    let order = Rc::new(Cell::new(0));
+
+    Executor::default().block_on_with({
+        let order = order.clone();
+
+        |spawner| async move {
+            let handle = spawner.spawn_with_handle(async { 7 });
+
+            assert_eq!(handle.await, Ok(7));
+            spawner.spawn_local(async move { order.set(order.get() + 1) });
+        }
+    });
+    assert_eq!(order.get(), 1);
+}
+
+#[cfg(feature = "test_util")]
+#[test]
+fn recording_executor_traces_scheduling() {
+    use std::{thread, time::Duration};
+
+    use pasts::test_util::RecordingExecutor;
+
+    let executor = RecordingExecutor::default();
+    let local = Channel::new();
+    let sender = local.clone();
+
+    executor.spawn_local(async move {
+        sender.send(1u32).await;
+    });
+
+    let trace = executor.block_on(async move {
+        assert_eq!(local.recv().await, 1);
+
+        // Park until woken from another thread
+        let remote = Channel::new();
+        let sender = remote.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            Executor::default().block_on(async move {
+                sender.send(2u32).await;
+            });
+        });
+
+        assert_eq!(remote.recv().await, 2);
+    });
+
+    let events: Vec<String> =
+        trace.events().iter().map(ToString::to_string).collect();
+
+    assert_eq!(
+        events[..5],
+        [
+            "poll task 0",
+            "poll task 0",
+            "poll task 1",
+            "wake task 0",
+            "poll task 0",
+        ],
+    );
+    assert_eq!(events[5], "park");
+    assert_eq!(events[events.len() - 2..], ["wake task 0", "poll task 0"]);
+}
+
+#[cfg(feature = "stress_test")]
+#[test]
+fn stress_default_pool() {
+    pasts::stress::run_all(Executor::default);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn spawn_catching_returns_panic() {
+    use std::{cell::Cell, rc::Rc};
+
+    let executor = Executor::default();
+    let state = Rc::new(Cell::new(0));
+    let handle = executor.spawn_catching({
+        let state = state.clone();
+
+        async move {
+            // Not unwind safe, but no `AssertUnwindSafe` is needed
+            state.set(1);
+            panic!("task {}", state.get());
+        }
+    });
+    let other = executor.spawn_with_handle(async { 7 });
+
+    executor.block_on(async move {
+        let error = handle.await.unwrap().unwrap_err();
+
+        assert_eq!(error.message(), Some("task 1"));
+        assert_eq!(error.to_string(), "task panicked: task 1");
+        assert_eq!(other.await, Ok(7));
+    });
+}
+
+#[test]
+fn parker_keeps_early_unparks() {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    use pasts::Parker;
+
+    let parker: Parker = Parker::new();
+
+    // Unparking before parking makes the next park return right away
+    parker.unparker().unpark();
+    parker.unparker().unpark();
+    parker.park();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let waker = parker.waker();
+    let thread = thread::spawn({
+        let count = count.clone();
+
+        move || {
+            for _ in 0..100 {
+                count.fetch_add(1, Ordering::SeqCst);
+                waker.wake_by_ref();
+            }
+        }
+    });
+
+    while count.load(Ordering::SeqCst) < 100 {
+        parker.park();
+    }
+
+    thread.join().unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn idle_hook_parks_for_external_spawns() {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, Thread},
+        time::Duration,
+    };
+
+    use pasts::{prelude::*, Idle, Park, Pool};
+
+    #[derive(Default)]
+    struct SharedPool(Mutex<Vec<BoxNotify<'static>>>);
+
+    impl Pool for SharedPool {
+        type Park = ThreadPark;
+
+        fn push(&self, _task: LocalBoxNotify<'static>) {
+            unreachable!("only spawned to from the other thread");
+        }
+
+        fn push_send(&self, task: BoxNotify<'static>) {
+            self.0.lock().unwrap().push(task);
+        }
+
+        fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool {
+            let mut queue = self.0.lock().unwrap();
+            let drained = !queue.is_empty();
+
+            for task in queue.drain(..) {
+                tasks.push(task);
+            }
+
+            drained
+        }
+    }
+
+    struct ThreadPark(Thread);
+
+    impl Default for ThreadPark {
+        fn default() -> Self {
+            Self(thread::current())
+        }
+    }
+
+    impl Park for ThreadPark {
+        fn park(&self) {
+            thread::park();
+        }
+
+        fn unpark(&self) {
+            self.0.unpark();
+        }
+    }
+
+    let ran = Arc::new(AtomicUsize::new(0));
+    let executor = Executor::new(SharedPool::default()).idle_hook({
+        let ran = ran.clone();
+
+        move || match ran.load(Ordering::SeqCst) {
+            0..3 => Idle::Park,
+            _ => Idle::Return,
+        }
+    });
+    let spawner = thread::spawn({
+        let executor = executor.clone();
+        let ran = ran.clone();
+
+        move || {
+            for _ in 0..3 {
+                let ran = ran.clone();
+
+                executor.spawn(async move {
+                    ran.fetch_add(1, Ordering::SeqCst);
+                });
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    });
+
+    // The main task finishes right away, then the executor waits
+    executor.block_on(async {});
+    spawner.join().unwrap();
+
+    assert_eq!(ran.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn executor_sends_with_sync_pool() {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::{self, Thread},
+    };
+
+    use pasts::{prelude::*, Park, Pool};
+
+    struct EmptyPool;
+
+    impl Pool for EmptyPool {
+        type Park = ThreadPark;
+
+        fn push(&self, _task: LocalBoxNotify<'static>) {
+            unreachable!("nothing is spawned");
+        }
+
+        fn drain(&self, _tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool {
+            false
+        }
+    }
+
+    struct ThreadPark(Thread);
+
+    impl Default for ThreadPark {
+        fn default() -> Self {
+            Self(thread::current())
+        }
+    }
+
+    impl Park for ThreadPark {
+        fn park(&self) {
+            thread::park();
+        }
+
+        fn unpark(&self) {
+            self.0.unpark();
+        }
+    }
+
+    let executor = Executor::new(EmptyPool);
+    let shutdown = executor.on_shutdown();
+    let ran = Arc::new(AtomicBool::new(false));
+    let flag = ran.clone();
+
+    thread::spawn(move || {
+        executor.block_on(async move {
+            flag.store(true, Ordering::SeqCst);
+        });
+    })
+    .join()
+    .unwrap();
+
+    assert!(ran.load(Ordering::SeqCst));
+    // Shutdown is seen from the thread the executor was created on
+    Executor::default().block_on(shutdown);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn spawn_from_another_thread() {
+    use std::{
+        sync::{Arc, Mutex},
+        task::Waker,
+        thread::{self, Thread},
+        time::Duration,
+    };
+
+    use pasts::{prelude::*, Park, Pool};
+
+    #[derive(Default)]
+    struct SendPool(Mutex<Vec<BoxNotify<'static>>>);
+
+    impl Pool for SendPool {
+        type Park = ThreadPark;
+
+        fn push(&self, _task: LocalBoxNotify<'static>) {
+            unreachable!("only spawned to with `Executor::spawn()`");
+        }
+
+        fn push_send(&self, task: BoxNotify<'static>) {
+            self.0.lock().unwrap().push(task);
+        }
+
+        fn drain(&self, tasks: &mut Vec<LocalBoxNotify<'static>>) -> bool {
+            let mut queue = self.0.lock().unwrap();
+            let drained = !queue.is_empty();
+
+            for task in queue.drain(..) {
+                tasks.push(task);
+            }
+
+            drained
+        }
+    }
+
+    struct ThreadPark(Thread);
+
+    impl Default for ThreadPark {
+        fn default() -> Self {
+            Self(thread::current())
+        }
+    }
+
+    impl Park for ThreadPark {
+        fn park(&self) {
+            thread::park();
+        }
+
+        fn park_timeout(&self, timeout: Duration) {
+            thread::park_timeout(timeout);
+        }
+
+        fn unpark(&self) {
+            self.0.unpark();
+        }
+    }
+
+    // Number of tasks that ran, and the main task's waker
+    let state = Arc::new(Mutex::new((0, None::<Waker>)));
+    let executor = Executor::new(SendPool::default());
+    let spawner = executor.clone();
+    let ran = state.clone();
+
+    thread::spawn(move || {
+        for _ in 0..3 {
+            let ran = ran.clone();
+
+            spawner.spawn(async move {
+                let mut state = ran.lock().unwrap();
+
+                state.0 += 1;
+
+                if let Some(waker) = state.1.take() {
+                    waker.wake();
+                }
+            });
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    let main = core::future::poll_fn(move |task| {
+        let mut state = state.lock().unwrap();
+
+        if state.0 == 3 {
+            return Ready(());
+        }
+
+        state.1 = Some(task.waker().clone());
+        Pending
+    });
+
+    executor
+        .block_on_timeout(main, Duration::from_secs(10))
+        .expect("spawned tasks didn't wake the executor");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn cpu_time_finds_busiest_task() {
+    use std::time::{Duration, Instant};
+
+    use pasts::{prelude::*, sync};
+
+    let executor = Executor::default().cpu_time();
+    let stats = executor.stats();
+    let spawner = executor.clone();
+    let times = stats.clone();
+
+    executor.block_on(async move {
+        // Both tasks wait until the sender is dropped
+        let (sender, mut receiver) = sync::work_queue::<()>();
+
+        spawner.spawn_local({
+            let mut receiver = receiver.clone();
+
+            async move {
+                let start = Instant::now();
+
+                while start.elapsed() < Duration::from_millis(5) {}
+                receiver.next().await;
+            }
+        });
+        spawner.spawn_local(async move {
+            receiver.next().await;
+        });
+
+        // Let both tasks have their first poll
+        pasts::time::sleep(Duration::from_millis(1)).await;
+
+        // Nested runs don't reuse identifiers, or forget the outer tasks
+        spawner.clone().block_on(async {});
+
+        let times = times.cpu_times();
+
+        assert_eq!(times.len(), 3);
+        assert!(times[0].1 >= Duration::from_millis(5));
+        assert!(times[1].1 < Duration::from_millis(5));
+        drop(sender);
+    });
+
+    // Tasks are forgotten once they complete
+    assert!(stats.cpu_times().is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn demote_slow_polls_slow_tasks_less_often() {
+    use std::{
+        cell::Cell,
+        rc::Rc,
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    use pasts::{prelude::*, Diagnostic};
+
+    let demoted = Arc::new(Mutex::new(Vec::new()));
+    let executor = Executor::default()
+        .demote_slow(Duration::from_millis(1), 2)
+        .watchdog(Duration::from_secs(1), Duration::from_secs(1), {
+            let demoted = demoted.clone();
+
+            move |diagnostic| {
+                if let Diagnostic::Demoted(task) = diagnostic {
+                    demoted.lock().unwrap().push(task);
+                }
+            }
+        });
+    // Polls of the slow and fast tasks, and of the slow one when the fast
+    // one completed
+    let polls = Rc::new([Cell::new(0), Cell::new(0), Cell::new(0)]);
+
+    // Both tasks keep waking themselves, but only the first one is slow
+    for slow in [true, false] {
+        let polls = polls.clone();
+
+        executor.spawn_local(core::future::poll_fn(move |t| {
+            let count = &polls[usize::from(!slow)];
+
+            count.set(count.get() + 1);
+
+            if count.get() == 20 {
+                if !slow {
+                    polls[2].set(polls[0].get());
+                }
+
+                return Ready(());
+            }
+
+            if slow {
+                thread::sleep(Duration::from_millis(2));
+            }
+
+            t.waker().wake_by_ref();
+            Pending
+        }));
+    }
+
+    executor.block_on(async {});
+
+    // Once demoted, the slow task only gets every other pass
+    assert_eq!(demoted.lock().unwrap().len(), 1);
+    assert!(polls[2].get() <= 12, "{}", polls[2].get());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn drain_policy_drops_remaining_tasks() {
+    use std::{
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use pasts::DrainPolicy;
+
+    // Dropped along with the task that holds it
+    let held = Rc::new(());
+    let executor = Executor::default().drain_policy(DrainPolicy::Cancel);
+    let task = held.clone();
+
+    executor.spawn_local(async move {
+        let _task = task;
+
+        core::future::pending::<()>().await;
+    });
+    executor.block_on(async {});
+    assert_eq!(Rc::strong_count(&held), 1);
+
+    let executor = Executor::default()
+        .drain_policy(DrainPolicy::Timeout(Duration::from_millis(10)));
+    let task = held.clone();
+    let start = Instant::now();
+
+    executor.spawn_local(async move {
+        let _task = task;
+
+        core::future::pending::<()>().await;
+    });
+    executor.block_on(async {});
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert_eq!(Rc::strong_count(&held), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn keep_alive_false_spawns_daemons() {
+    use std::{cell::Cell, rc::Rc};
+
+    use pasts::prelude::*;
+
+    let executor = Executor::default();
+    let daemon = executor.clone().keep_alive(false);
+    let finished = Rc::new(Cell::new(false));
+    let (sender, mut receiver) = pasts::sync::work_queue::<()>();
+
+    // The daemon never completes, so it's dropped once the rest are done
+    daemon.spawn_local(core::future::pending());
+    executor.spawn_local({
+        let finished = finished.clone();
+
+        async move {
+            receiver.next().await;
+            finished.set(true);
+        }
+    });
+    executor.block_on(async move {
+        drop(sender);
+    });
+    assert!(finished.get());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn keep_alive_counts_daemons_per_run() {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    let executor = Executor::default();
+    let daemon = executor.clone().keep_alive(false);
+    let finished = Rc::new(Cell::new(false));
+    let nested = executor.clone();
+
+    daemon.spawn_local(core::future::pending());
+    executor.block_on({
+        let finished = finished.clone();
+
+        async move {
+            // Let the daemon have its first poll
+            pasts::time::sleep(Duration::from_millis(1)).await;
+
+            // The outer daemon doesn't let the nested run drop its task
+            nested.clone().block_on(async move {
+                nested.spawn_local(async move {
+                    pasts::time::sleep(Duration::from_millis(5)).await;
+                    finished.set(true);
+                });
+            });
+        }
+    });
+    assert!(finished.get());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn join_handle_reports_dropped_task() {
+    use pasts::{PanicPolicy, TaskDropped};
+
+    // Cancelled when the executor shuts down
+    let executor = Executor::default().drain_policy(pasts::DrainPolicy::Cancel);
+    let handle = executor.spawn_with_handle(core::future::pending::<()>());
+
+    executor.block_on(async {});
+    Executor::default().block_on(async move {
+        assert_eq!(handle.await, Err(TaskDropped));
+    });
+
+    // Cancelled after panicking, while the handle is being awaited
+    let executor =
+        Executor::default().panic_hook(|_, _| PanicPolicy::CancelTask);
+    let handle = executor.spawn_with_handle(async {
+        pasts::time::sleep(std::time::Duration::from_millis(1)).await;
+        panic!("cancelled");
+    });
+
+    executor.block_on(async move {
+        assert_eq!(handle.await, Err(TaskDropped));
+    });
+}
+
+#[test]
+fn stats_count_coalesced_wakes() {
+    use pasts::prelude::*;
+
+    let executor = Executor::default();
+    let stats = executor.stats();
+
+    executor.block_on(async {
+        let mut woken = false;
+
+        core::future::poll_fn(|task| {
+            if woken {
+                return Ready(());
+            }
+
+            // The second wake is picked up by the same pass as the first
+            woken = true;
+            task.waker().wake_by_ref();
+            task.waker().wake_by_ref();
+
+            Pending
+        })
+        .await;
+    });
+
+    let snapshot = stats.snapshot();
+
+    assert_eq!(snapshot.wakes, 2);
+    assert_eq!(snapshot.coalesced_wakes, 1);
+}
@@ -0,0 +1,221 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use pasts::{
+    sync::{Mutex, MutexGuard, OwnedMutexGuard},
+    Executor,
+};
+
+#[test]
+fn mutex_waiters_take_turns_across_awaits() {
+    let executor = Executor::default();
+    let mutex = Arc::new(Mutex::new(Vec::new()));
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    for task in 0..3 {
+        let mutex = mutex.clone();
+        let log = log.clone();
+
+        executor.spawn_local(async move {
+            let mut guard = mutex.lock_owned().await;
+
+            log.borrow_mut().push(task);
+            // Yield while holding the lock
+            async_yield().await;
+            guard.push(task);
+            drop(guard);
+        });
+    }
+
+    executor.block_on(async {});
+
+    let values = Arc::into_inner(mutex).unwrap().into_inner();
+
+    assert_eq!(values, *log.borrow());
+    assert_eq!(values.len(), 3);
+}
+
+#[test]
+fn mutex_try_lock_fails_while_locked() {
+    let mutex = Mutex::new(1);
+    let guard = mutex.try_lock().unwrap();
+
+    assert!(mutex.try_lock().is_none());
+    drop(guard);
+    assert_eq!(*mutex.try_lock().unwrap(), 1);
+}
+
+#[test]
+fn mutex_mapped_guard_unlocks_on_drop() {
+    let mutex = Arc::new(Mutex::new((0u32, String::new())));
+    let mut name = OwnedMutexGuard::map(
+        mutex.clone().try_lock_owned().unwrap(),
+        |v| &v.1,
+        |v| &mut v.1,
+    );
+
+    name.push_str("pasts");
+    assert!(mutex.try_lock().is_none());
+    drop(name);
+
+    let mut count =
+        MutexGuard::map(mutex.try_lock().unwrap(), |v| &v.0, |v| &mut v.0);
+
+    *count += 1;
+    drop(count);
+    assert_eq!(*mutex.try_lock().unwrap(), (1, "pasts".to_string()));
+}
+
+// Yield to the executor once.
+async fn async_yield() {
+    let mut yielded = false;
+
+    core::future::poll_fn(|task| {
+        if yielded {
+            return core::task::Poll::Ready(());
+        }
+
+        yielded = true;
+        task.waker().wake_by_ref();
+        core::task::Poll::Pending
+    })
+    .await;
+}
+
+#[test]
+fn condvar_wakes_waiters_in_order() {
+    use pasts::sync::Condvar;
+
+    let executor = Executor::default();
+    let shared = Arc::new((Mutex::new(0u32), Condvar::new()));
+    let woken = Rc::new(RefCell::new(Vec::new()));
+
+    for task in 1..=3 {
+        let shared = shared.clone();
+        let woken = woken.clone();
+
+        executor.spawn_local(async move {
+            let (mutex, condvar) = &*shared;
+            let mut turn = mutex.lock().await;
+
+            while *turn != task {
+                turn = condvar.wait(turn).await;
+            }
+
+            woken.borrow_mut().push(task);
+            *turn += 1;
+            condvar.notify_all();
+        });
+    }
+
+    executor.spawn_local({
+        let shared = shared.clone();
+
+        async move {
+            let (mutex, condvar) = &*shared;
+
+            *mutex.lock().await = 1;
+            condvar.notify_all();
+        }
+    });
+    executor.block_on(async {});
+
+    assert_eq!(*woken.borrow(), [1, 2, 3]);
+}
+
+#[test]
+fn condvar_passes_on_notification_when_waiter_dropped() {
+    use core::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Waker},
+    };
+
+    use pasts::sync::Condvar;
+
+    let mut task = Context::from_waker(Waker::noop());
+    let mutex = Mutex::new(());
+    let condvar = Condvar::new();
+    let mut first = condvar.wait(mutex.try_lock().unwrap());
+
+    assert!(Pin::new(&mut first).poll(&mut task).is_pending());
+
+    let mut second = condvar.wait(mutex.try_lock().unwrap());
+
+    assert!(Pin::new(&mut second).poll(&mut task).is_pending());
+
+    // The first waiter gets the notification, but is dropped before it runs
+    condvar.notify_one();
+    drop(first);
+
+    assert!(Pin::new(&mut second).poll(&mut task).is_ready());
+}
+
+#[test]
+fn work_queue_gives_each_item_to_one_receiver() {
+    use std::thread;
+
+    use pasts::{prelude::*, sync::work_queue};
+
+    let (sender, receiver) = work_queue();
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let mut receiver = receiver.clone();
+
+            thread::spawn(move || {
+                let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+                let output = received.clone();
+
+                Executor::default().block_on(async move {
+                    while let Some(item) = receiver.next().await {
+                        output.lock().unwrap().push(item);
+                    }
+                });
+
+                Arc::into_inner(received).unwrap().into_inner().unwrap()
+            })
+        })
+        .collect();
+
+    drop(receiver);
+
+    for item in 0..1000 {
+        sender.send(item);
+    }
+
+    drop(sender);
+
+    let mut received: Vec<u32> = workers
+        .into_iter()
+        .flat_map(|worker| worker.join().unwrap())
+        .collect();
+
+    received.sort_unstable();
+    assert_eq!(received, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn progress_coalesces_updates_between_polls() {
+    use pasts::{prelude::*, sync::Progress};
+
+    let progress = Progress::new(4);
+    let mut watch = progress.watch();
+
+    Executor::default().block_on(async move {
+        assert_eq!(watch.next().await.done, 0);
+
+        // Only the latest of several updates is seen
+        progress.advance(1);
+        progress.advance(2);
+        assert_eq!(watch.next().await.done, 3);
+
+        progress.set_total(6);
+
+        let snapshot = watch.next().await;
+
+        assert_eq!((snapshot.done, snapshot.total), (3, 6));
+        assert!(!snapshot.is_complete());
+        assert_eq!(snapshot.ratio(), 0.5);
+    });
+}
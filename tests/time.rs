@@ -0,0 +1,258 @@
+#![cfg(feature = "std")]
+
+use std::{sync::mpsc, thread};
+
+use pasts::{
+    prelude::*,
+    time::{self, Duration, Elapsed, Instant, MissedTicks},
+    Executor,
+};
+
+const PERIOD: Duration = Duration::from_millis(10);
+
+// Get a tick, block for a few periods, then get the late tick and the one
+// after it.
+fn late_ticks(missed: MissedTicks) -> (Instant, Instant) {
+    let mut interval = time::interval(PERIOD).missed_ticks(missed);
+    let (send, recv) = mpsc::channel();
+
+    Executor::default().block_on(async move {
+        interval.next().await;
+        thread::sleep(PERIOD * 7 / 2);
+
+        let late = interval.next().await;
+        let after = interval.next().await;
+
+        send.send((late, after)).unwrap();
+    });
+
+    recv.recv().unwrap()
+}
+
+#[test]
+fn sleep_waits_for_duration() {
+    Executor::default().block_on(async {
+        let start = Instant::now();
+
+        time::sleep(PERIOD).await;
+        assert!(start.elapsed() >= PERIOD);
+    });
+}
+
+#[test]
+fn interval_bursts_missed_ticks() {
+    let (late, after) = late_ticks(MissedTicks::Burst);
+
+    assert_eq!(after - late, PERIOD);
+}
+
+#[test]
+fn interval_delays_after_missed_ticks() {
+    let (late, after) = late_ticks(MissedTicks::Delay);
+
+    assert!(after - late > PERIOD);
+}
+
+#[test]
+fn interval_skips_missed_ticks() {
+    let (late, after) = late_ticks(MissedTicks::Skip);
+    let skipped = after - late;
+
+    assert!(skipped >= PERIOD * 2);
+    assert_eq!(skipped.as_nanos() % PERIOD.as_nanos(), 0);
+}
+
+#[test]
+fn sleep_until_reset() {
+    Executor::default().block_on(async {
+        let start = Instant::now();
+        let mut sleep = time::sleep_until(start + PERIOD * 100);
+
+        // Moving the deadline earlier wakes at the new one
+        sleep.reset(start + PERIOD);
+        (&mut sleep).await;
+
+        assert!(start.elapsed() >= PERIOD);
+        assert!(start.elapsed() < PERIOD * 100);
+    });
+}
+
+#[test]
+fn timeout_at_deadline() {
+    Executor::default().block_on(async {
+        let deadline = Instant::now() + PERIOD;
+        let quick = async { 1 };
+
+        pasts::pin!(quick);
+
+        let quick = time::timeout_at(deadline, quick);
+        // Far enough past the deadline that a late wake can't complete it first
+        let slow = time::timeout_at(deadline, time::sleep(PERIOD * 10));
+
+        assert_eq!(quick.await, Ok(1));
+        assert_eq!(slow.await, Err(Elapsed));
+        assert!(Instant::now() >= deadline);
+    });
+}
+
+#[test]
+fn pacer_drops_late_frames() {
+    // Long enough that a late wake doesn't drop the second frame
+    const PERIOD: Duration = Duration::from_millis(50);
+
+    Executor::default().block_on(async {
+        let mut frames = time::pacer(20.0);
+        let first = frames.next().await;
+        let second = frames.next().await;
+
+        assert_eq!((first.index, second.index), (0, 1));
+        assert_eq!(second.deadline - first.deadline, PERIOD);
+
+        thread::sleep(PERIOD * 7 / 2);
+
+        let late = frames.next().await;
+
+        let expected = PERIOD * late.index as u32;
+        let actual = late.deadline - first.deadline;
+
+        assert!(late.index >= 4);
+        assert!(actual.abs_diff(expected) < Duration::from_micros(1));
+    });
+}
+
+#[test]
+fn heartbeat_reports_stalls() {
+    use pasts::notify::{self, Beat};
+
+    Executor::default().block_on(async {
+        let mut events = 0;
+        let mut driver = notify::poll_fn(|_| {
+            events += 1;
+            if events <= 2 {
+                Ready(events)
+            } else {
+                Pending
+            }
+        })
+        .heartbeat(PERIOD);
+        let start = Instant::now();
+
+        assert_eq!(driver.next().await, Beat::Event(1));
+        assert_eq!(driver.next().await, Beat::Event(2));
+        assert_eq!(driver.next().await, Beat::Stalled);
+        assert!(start.elapsed() >= PERIOD);
+        assert_eq!(driver.next().await, Beat::Stalled);
+        assert!(start.elapsed() >= PERIOD * 2);
+    });
+}
+
+#[test]
+fn watchdog_expires_only_when_starved() {
+    Executor::default().block_on(async {
+        let watchdog = time::Watchdog::new(PERIOD * 3);
+        let mut expired = watchdog.expired();
+        let start = Instant::now();
+
+        // Keep feeding for a while, so it mustn't expire in the meantime
+        for _ in 0..6 {
+            time::sleep(PERIOD).await;
+            watchdog.feed();
+        }
+
+        let first = expired.next().await;
+
+        assert!(first - start >= PERIOD * 9);
+        assert_eq!(first, watchdog.deadline());
+
+        // Doesn't expire again until fed and starved again
+        watchdog.feed();
+
+        let second = expired.next().await;
+
+        assert!(second - first >= PERIOD * 3);
+    });
+}
+
+#[test]
+fn delay_queue_yields_in_deadline_order() {
+    Executor::default().block_on(async {
+        let mut queue = time::DelayQueue::new();
+        let start = Instant::now();
+
+        queue.insert_at(3, start + PERIOD * 3);
+        queue.insert_at(1, start + PERIOD);
+        queue.insert_at(2, start + PERIOD);
+
+        let removed = queue.insert_at(0, start);
+
+        assert_eq!(queue.remove(removed), Some(0));
+        assert_eq!(queue.remove(removed), None);
+        assert_eq!(queue.next_deadline(), Some(start + PERIOD));
+
+        assert_eq!(queue.next().await, 1);
+        assert_eq!(queue.next().await, 2);
+
+        // Inserting ahead of the armed deadline is picked up
+        queue.insert(4, Duration::ZERO);
+
+        assert_eq!(queue.next().await, 4);
+        assert_eq!(queue.next().await, 3);
+        assert!(start.elapsed() >= PERIOD * 3);
+        assert!(queue.is_empty());
+    });
+}
+
+#[test]
+fn schedule_aligns_to_wall_clock() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    Executor::default().block_on(async {
+        // Catch up on every time, so a late wake doesn't skip one
+        let mut schedule = time::schedule(PERIOD)
+            .offset(PERIOD / 2)
+            .catch_up(time::CatchUp::All);
+        let first = schedule.next().await;
+        let second = schedule.next().await;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+        assert_eq!(
+            first.as_nanos() % PERIOD.as_nanos(),
+            (PERIOD / 2).as_nanos()
+        );
+        assert_eq!(second - first, PERIOD);
+        assert!(second <= now);
+    });
+}
+
+#[test]
+fn schedule_catches_up_by_policy() {
+    use time::CatchUp;
+
+    // Long enough that oversleeping doesn't miss another time
+    const PERIOD: Duration = Duration::from_millis(50);
+
+    // Get the first time, miss a few, then get the next two.
+    fn missed(catch_up: CatchUp) -> Vec<u128> {
+        let (send, recv) = mpsc::channel();
+
+        Executor::default().block_on(async move {
+            let mut schedule = time::schedule(PERIOD).catch_up(catch_up);
+            let first = schedule.next().await;
+
+            thread::sleep(PERIOD * 7 / 2);
+
+            let times = [first, schedule.next().await, schedule.next().await];
+
+            send.send(
+                times.map(|t| (t - first).as_nanos() / PERIOD.as_nanos()),
+            )
+            .unwrap();
+        });
+
+        recv.recv().unwrap().to_vec()
+    }
+
+    assert_eq!(missed(CatchUp::All), [0, 1, 2]);
+    assert_eq!(missed(CatchUp::Latest), [0, 3, 4]);
+    assert_eq!(missed(CatchUp::Skip), [0, 4, 5]);
+}